@@ -57,6 +57,72 @@ pub enum Error {
     },
     /// A required book element is missing.
     RequiredElementMissing(&'static str),
+    /// An ePub breaks a structural rule.
+    InvalidEPub {
+        /// The file in the ePub breaking the rule.
+        file: String,
+        /// What's wrong with it.
+        what: &'static str,
+        /// Additional data.
+        more: Option<String>,
+    },
+    /// Network content needed in offline mode isn't cached.
+    NotCached {
+        /// The URL of the content.
+        url: String,
+    },
+    /// Downloaded network content doesn't have the hash it's pinned to.
+    HashMismatch {
+        /// The URL of the content.
+        url: String,
+        /// The hash it's pinned to.
+        expected: String,
+        /// The hash it has.
+        actual: String,
+    },
+    /// A descriptor line has a key that isn't an element's name.
+    ///
+    /// Exits with 2, like `Parse`.
+    UnknownElement {
+        /// The unknown key.
+        name: String,
+        /// The most similar element name, if any.
+        suggestion: Option<&'static str>,
+    },
+    /// A descriptor value references a variable that isn't defined.
+    ///
+    /// Exits with 2, like `Parse`.
+    UndefinedVariable {
+        /// The variable's name.
+        name: String,
+    },
+    /// A descriptor includes itself, directly or not.
+    IncludeCycle {
+        /// The descriptors in the cycle, in include order, starting and ending with the same one.
+        chain: Vec<String>,
+    },
+    /// A descriptor line is erroneous.
+    ///
+    /// Errors in included descriptors are wrapped in another one for the including line.
+    ///
+    /// Exits with the exit value of the underlying error.
+    Descriptor {
+        /// The descriptor's name.
+        descriptor: String,
+        /// The 1-based number of the line.
+        line: usize,
+        /// The 1-based column, in characters, of the key in the line, if it's what's erroneous (see `is_key_error()`),
+        /// or of the value otherwise.
+        column: usize,
+        /// The whole line.
+        text: String,
+        /// The line's key.
+        key: String,
+        /// The line's raw value.
+        value: String,
+        /// What's wrong with it.
+        error: Box<Error>,
+    },
 }
 
 impl Error {
@@ -104,6 +170,38 @@ impl Error {
                 writeln!(err_out, "Wrong amount of {} elements: {}, must be {} {}.", element, actual, relation, bound).unwrap()
             }
             Error::RequiredElementMissing(element) => writeln!(err_out, "Required element {} not specified.", element).unwrap(),
+            Error::InvalidEPub { ref file, what, ref more } => {
+                write!(err_out, "Invalid ePub: {} {}", file, what).unwrap();
+                if let Some(ref more) = *more {
+                    write!(err_out, ": {}", more).unwrap();
+                }
+                writeln!(err_out, ".").unwrap();
+            }
+            Error::NotCached { ref url } => writeln!(err_out, "{} not cached, but offline.", url).unwrap(),
+            Error::HashMismatch { ref url, ref expected, ref actual } => {
+                writeln!(err_out, "{} has SHA-256 hash {}, but {} was expected.", url, actual, expected).unwrap()
+            }
+            Error::UnknownElement { ref name, suggestion } => {
+                match suggestion {
+                    Some(suggestion) => writeln!(err_out, "Unknown element {}, did you mean {}?", name, suggestion).unwrap(),
+                    None => writeln!(err_out, "Unknown element {}.", name).unwrap(),
+                }
+            }
+            Error::UndefinedVariable { ref name } => writeln!(err_out, "Variable {} not defined.", name).unwrap(),
+            Error::IncludeCycle { ref chain } => writeln!(err_out, "Descriptor include cycle: {}.", chain.join(" -> ")).unwrap(),
+            Error::Descriptor { ref descriptor, line, ref error, .. } if error.is_descriptor() => {
+                writeln!(err_out, "In descriptor included from {}:{}:", descriptor, line).unwrap();
+                error.print_error(err_out);
+            }
+            Error::Descriptor { ref descriptor, line, column, ref text, ref key, ref value, ref error } => {
+                write!(err_out, "{}:{}: ", descriptor, line).unwrap();
+                error.print_error(err_out);
+                writeln!(err_out, "{}", text).unwrap();
+                // Keep tabs so that the caret lines up with the key or value
+                let indent: String = text.chars().take(column - 1).map(|c| if c == '\t' { '\t' } else { ' ' }).collect();
+                let underlined = if error.is_key_error() { key } else { value };
+                writeln!(err_out, "{}^{}", indent, "~".repeat(underlined.chars().count().saturating_sub(1))).unwrap();
+            }
         }
     }
 
@@ -127,6 +225,39 @@ impl Error {
             Error::WrongFileState { .. } => 4,
             Error::WrongElementAmount { .. } => 5,
             Error::RequiredElementMissing(..) => 6,
+            Error::InvalidEPub { .. } => 7,
+            Error::NotCached { .. } => 8,
+            Error::HashMismatch { .. } => 9,
+            Error::UnknownElement { .. } => 2,
+            Error::UndefinedVariable { .. } => 2,
+            Error::IncludeCycle { .. } => 10,
+            Error::Descriptor { ref error, .. } => error.exit_value(),
+        }
+    }
+
+    fn is_descriptor(&self) -> bool {
+        match *self {
+            Error::Descriptor { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Check whether the error lies in a descriptor line's key rather than its value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use gen_epub_book::Error;
+    /// assert!(Error::UnknownElement {
+    ///     name: "Nmae".to_string(),
+    ///     suggestion: Some("Name"),
+    /// }.is_key_error());
+    /// assert!(!Error::UndefinedVariable { name: "VERSION".to_string() }.is_key_error());
+    /// ```
+    pub fn is_key_error(&self) -> bool {
+        match *self {
+            Error::UnknownElement { .. } => true,
+            _ => false,
         }
     }
 }
@@ -8,12 +8,14 @@
 //!
 //! ```text
 //! Options
-//! |> parse_descriptor()
+//! |> parse_descriptor_ext()
 //! |> EPubBook::from_elements()
 //! |> EPubBook::normalise_paths()
 //! |> EPubBook::write_zip()
 //! ```
 //!
+//! Books can be verified afterwards with `check_epub()`.
+//!
 //! # Executable manpage
 //!
 //! Exit values and possible errors:
@@ -25,12 +27,24 @@
 //! 4 - file in wrong state
 //! 5 - incorrect amount of elements
 //! 6 - required element missing
+//! 7 - invalid ePub
+//! 8 - network content not cached in offline mode
+//! 9 - network content doesn't match its pinned hash
+//! 10 - descriptor includes itself
 //! ```
 //!
+//! Errors in descriptor elements are reported as `IN_FILE:LINE: message`,
+//! followed by the offending line with the bad value underlined,
+//! and exit with the value of the underlying error.
+//!
 //! ## SYNOPSIS
 //!
 //! [`gen-epub-book`](https://github.com/nabijaczleweli/gen-epub-book.rs) [OPTIONS] IN_FILE OUT_FILE
 //!
+//! [`gen-epub-book`](https://github.com/nabijaczleweli/gen-epub-book.rs) [OPTIONS] check EPUB_FILE
+//!
+//! [`gen-epub-book`](https://github.com/nabijaczleweli/gen-epub-book.rs) [OPTIONS] --print-hashes IN_FILE
+//!
 //! ## DESCRIPTION
 //!
 //! Generate an ePub book from a simple plaintext descriptor.
@@ -78,10 +92,159 @@
 //! `PATH` is an existing directory.
 //! ```
 //!
+//! --strict
+//!
+//! ```text
+//! Reject descriptor lines with unknown keys, without the separator,
+//! without a key or with an empty value (once variables are expanded),
+//! instead of ignoring them.
+//!
+//! Unknown keys similar to an element's name get it suggested.
+//!
+//! Undefined variables are also rejected, see VARIABLES.
+//!
+//! Default: false.
+//! ```
+//!
+//! -d --define &lt;NAME=VALUE&gt;...
+//!
+//! ```text
+//! Define a variable for the descriptor, overriding its Set entries.
+//!
+//! See VARIABLES for details.
+//! ```
+//!
+//! -E --epub-version &lt;VERSION&gt;
+//!
+//! ```text
+//! Write an ePub of the specified version, either 2 or 3.
+//!
+//! ePub 3 books get a navigation document in addition to the NCX table of contents.
+//!
+//! Default: 2.
+//! ```
+//!
+//! --no-ncx
+//!
+//! ```text
+//! Don't write the NCX table of contents for ePub 3 books.
+//!
+//! Only the navigation document will remain, which older readers don't support.
+//! ```
+//!
+//! -X --xhtml
+//!
+//! ```text
+//! Convert all (X)HTML content, including string and Markdown content,
+//! to well-formed XHTML documents.
+//!
+//! Sloppy HTML (e.g. unclosed elements or no namespace) is parsed the way web
+//! browsers do, and written back with the XHTML namespace and an XML
+//! declaration. Documents without a title get their TOC name,
+//! or the e-book's name.
+//!
+//! Default: false.
+//! ```
+//!
+//! --cache-dir &lt;CACHE_DIR&gt;
+//!
+//! ```text
+//! Cache network content in the specified directory, creating it if needed.
+//!
+//! Cached content is only downloaded again if the server reports it changed.
+//! ```
+//!
+//! --offline
+//!
+//! ```text
+//! Don't download anything, taking all network content from the cache instead.
+//!
+//! Requires --cache-dir. Network content that isn't cached is an error.
+//!
+//! Default: false.
+//! ```
+//!
+//! -j --jobs &lt;JOBS&gt;
+//!
+//! ```text
+//! Download at most JOBS network files at once, before assembling the book.
+//!
+//! Default: 4.
+//! ```
+//!
+//! --timeout &lt;SECONDS&gt;
+//!
+//! ```text
+//! Give up on a server after SECONDS seconds, 0 to wait indefinitely.
+//!
+//! Default: 30.
+//! ```
+//!
+//! --retries &lt;RETRIES&gt;
+//!
+//! ```text
+//! Retry failed downloads up to RETRIES times, waiting 1, 2, 4, &c. seconds
+//! in-between. Connection errors, server errors and "Too Many Requests"
+//! responses are considered failures.
+//!
+//! Default: 0.
+//! ```
+//!
+//! -H --header &lt;HEADER&gt;...
+//!
+//! ```text
+//! Send an additional HTTP header with each request, e.g.
+//! "Authorization: Bearer TOKEN". Format: "Name: value".
+//! ```
+//!
+//! --user-agent &lt;USER_AGENT&gt;
+//!
+//! ```text
+//! Identify as USER_AGENT to servers.
+//! ```
+//!
+//! --proxy &lt;PROXY&gt;
+//!
+//! ```text
+//! Download all network content through the proxy at the PROXY URL.
+//! ```
+//!
+//! --network-images
+//!
+//! ```text
+//! Also download the images referenced from Network-Content pages and add them
+//! to the e-book.
+//! ```
+//!
+//! --print-hashes
+//!
+//! ```text
+//! Instead of assembling an e-book, download all network content and print
+//! its URLs with their SHA-256 hashes to the standard output, in the format
+//! used for pinning, see PINNING NETWORK CONTENT.
+//! ```
+//!
 //! ## DESCRIPTOR FORMAT
 //!
 //! The descriptor consists of multiple lines in the format *"Key: Value"*, unknown
-//! keys are ignored, lines that don't match the format are ignored.
+//! keys are ignored, lines that don't match the format are ignored,
+//! unless --strict is specified, in which case they are errors.
+//!
+//! Lines starting with *"#"* are comments, and are always ignored.
+//!
+//! A value can span multiple lines with a heredoc: if it's *"&lt;&lt;TERMINATOR"*,
+//! where `TERMINATOR` consists of letters, digits, "-"s and "_"s,
+//! the value is all of the following lines, verbatim, up to the one consisting of just
+//! `TERMINATOR`, which makes writing multi-paragraph String-Content or
+//! String-Description easy:
+//!
+//! ```text
+//! String-Description: <<END
+//! The first paragraph.
+//!
+//! The second paragraph.
+//! END
+//! ```
 //!
 //! Name
 //!
@@ -102,6 +265,16 @@
 //! Remarks: see ADDITIONAL CONTENT PROCESSING
 //! ```
 //!
+//! Network-Content
+//!
+//! ```text
+//! Required: no
+//! Type: file URL
+//! Value: URL of (X)HTML page
+//! Amount: any
+//! Remarks: see ADDITIONAL CONTENT PROCESSING
+//! ```
+//!
 //! String-Content
 //!
 //! ```text
@@ -111,6 +284,25 @@
 //! Amount: any
 //! ```
 //!
+//! Markdown-Content
+//!
+//! ```text
+//! Required: no
+//! Type: file path
+//! Value: relative path to CommonMark file
+//! Amount: any
+//! Remarks: see ADDITIONAL CONTENT PROCESSING
+//! ```
+//!
+//! String-Markdown-Content
+//!
+//! ```text
+//! Required: no
+//! Type: CommonMark
+//! Value: CommonMark string
+//! Amount: any
+//! ```
+//!
 //! Image-Content
 //!
 //! ```text
@@ -153,9 +345,20 @@
 //!
 //! ```text
 //! Required: yes
-//! Type: plaintext string
-//! Value: e-book's author
-//! Amount: 1
+//! Type: plaintext string, optionally followed by " file-as=SORT_NAME"
+//! Value: one of the e-book's authors, and the name to sort them by
+//! Amount: 1+
+//! ```
+//!
+//! Contributor
+//!
+//! ```text
+//! Required: no
+//! Type: MARC relator code, plaintext string, optionally followed by
+//!       " file-as=SORT_NAME"
+//! Value: someone else's role, like "trl" for translators or "ill" for
+//!        illustrators, their name, and the name to sort them by
+//! Amount: any
 //! ```
 //!
 //! Date
@@ -177,6 +380,165 @@
 //! Amount: 1
 //! ```
 //!
+//! UUID
+//!
+//! ```text
+//! Required: no
+//! Type: RFC4122-compliant UUID
+//! Value: e-book's unique identifier
+//! Amount: 0-1
+//! Remarks: derived from the rest of the descriptor if not specified
+//! ```
+//!
+//! Publisher
+//!
+//! ```text
+//! Required: no
+//! Type: plaintext string
+//! Value: e-book's publisher
+//! Amount: 0-1
+//! ```
+//!
+//! Rights
+//!
+//! ```text
+//! Required: no
+//! Type: plaintext string
+//! Value: e-book's copyright and licensing statement
+//! Amount: 0-1
+//! ```
+//!
+//! Subject
+//!
+//! ```text
+//! Required: no
+//! Type: plaintext string
+//! Value: topic of the e-book, like a genre or keyword
+//! Amount: any
+//! ```
+//!
+//! Source
+//!
+//! ```text
+//! Required: no
+//! Type: plaintext string
+//! Value: work the e-book is derived from
+//! Amount: 0-1
+//! ```
+//!
+//! Relation
+//!
+//! ```text
+//! Required: no
+//! Type: plaintext string
+//! Value: work the e-book is related to
+//! Amount: any
+//! ```
+//!
+//! ISBN
+//!
+//! ```text
+//! Required: no
+//! Type: ISBN-10 or ISBN-13
+//! Value: e-book's ISBN, with a correct check digit
+//! Amount: 0-1
+//! Remarks: hyphens and spaces are removed
+//! ```
+//!
+//! Identifier
+//!
+//! ```text
+//! Required: no
+//! Type: scheme name, plaintext string
+//! Value: additional identifier, like "DOI 10.1000/182"
+//! Amount: any
+//! ```
+//!
+//! Modified
+//!
+//! ```text
+//! Required: no
+//! Type: RFC3339-compliant date
+//! Value: e-book's last modification date
+//! Amount: 0-1
+//! Remarks: the time of writing the e-book if not specified
+//! ```
+//!
+//! Series
+//!
+//! ```text
+//! Required: no
+//! Type: plaintext string
+//! Value: series the e-book belongs to
+//! Amount: 0-1
+//! ```
+//!
+//! Series-Index
+//!
+//! ```text
+//! Required: no
+//! Type: decimal number
+//! Value: e-book's position in its series
+//! Amount: 0-1
+//! Remarks: requires Series
+//! ```
+//!
+//! Descriptor-Include
+//!
+//! ```text
+//! Required: no
+//! Type: file path
+//! Value: relative path to descriptor whose entries to put in place of this one
+//! Amount: any
+//! Remarks: see DESCRIPTOR INCLUDES
+//! ```
+//!
+//! Set
+//!
+//! ```text
+//! Required: no
+//! Type: NAME=value
+//! Value: variable to define for subsequent entries
+//! Amount: any
+//! Remarks: see VARIABLES
+//! ```
+//!
+//! ## VARIABLES
+//!
+//! "${NAME}" in entry values is replaced with the value of the variable `NAME`,
+//! where `NAME` consists of letters, digits and "_"s, which is, in order:
+//!
+//!   * defined with -d/--define,
+//!   * set by the last preceding Set entry, in this or an included descriptor,
+//!   * `DATE`, the current RFC3339 datetime, or `YEAR`, the current year,
+//!     both taken from `SOURCE_DATE_EPOCH` if set,
+//!   * an environment variable.
+//!
+//! Undefined variables are left as-is, unless --strict is specified,
+//! in which case they are errors. "$${NAME}" stands for a literal "${NAME}".
+//!
+//! ```text
+//! Set: VERSION=1.2
+//! Name: Handbook v${VERSION}
+//! Date: ${DATE}
+//! ```
+//!
+//! ## DESCRIPTOR INCLUDES
+//!
+//! Entries shared between descriptors, like Author, Language or Include,
+//! can be put in a separate descriptor and included with the Descriptor-Include
+//! entry, which is replaced with the included descriptor's entries.
+//!
+//! The included descriptor is looked up in the include directories, like other
+//! files, and can include other descriptors, but not itself, directly or not.
+//!
+//! Errors in included descriptors are preceded by the chain of including lines:
+//!
+//! ```text
+//! In descriptor included from book.epupp:3:
+//! common.epupp:2: Failed to parse datetime for book element: not RFC3339.
+//! ```
+//!
 //! ## ADDITIONAL CONTENT PROCESSING
 //!
 //! When adding content using the `Content` entry, the file will additinally be
@@ -193,6 +555,108 @@
 //!
 //! Optional.
 //!
+//! The TOC can be nested, by specifying the level of the entry like so:
+//!
+//! ```text
+//! <!-- ePub title: "TOC_NAME" level: LEVEL -->
+//! ```
+//!
+//! Where `LEVEL` is a positive integer, 1 by default. Entries are nested under
+//! the closest preceding entry with a lower level, and can be at most one level
+//! deeper than the entry preceding them.
+//!
+//! Content added using the `Markdown-Content` entry is rendered to XHTML,
+//! and, absent the comment, its first heading is used as the TOC name instead.
+//!
+//! Content added using the `Network-Content` entry is downloaded, and, absent the
+//! comment, its `<title>` is used as the TOC name instead. References in it to
+//! other network content in the e-book are rewritten to point at it, and other
+//! relative references are made absolute. With --network-images, the images it
+//! references are also downloaded and added to the e-book; those that fail to
+//! download are left as-is, with a warning.
+//!
+//! Content files (and any CSS files found this way) are also scanned for
+//! references to local files in `src` and `href` attributes and CSS `url()`s,
+//! like images and stylesheets, which are looked up relative to the referencing
+//! file, first in its include directory, then in the others. Files found are added
+//! to the e-book as if with `Include`, and the references are rewritten to point
+//! at them. References that can't be found are left as-is.
+//!
+//! Links (`href`s of `a` and `area` elements) to other files in the e-book are
+//! rewritten the same way, keeping their fragment, so that they keep pointing at
+//! the right place after packing. A warning is printed for links to local files
+//! that aren't in the e-book.
+//!
+//! Network content is packed under the filename suggested by the server in its
+//! `Content-Disposition` header, if any, or the last part of its URL otherwise,
+//! with an extension matching its `Content-Type` added if it has none, and
+//! numbered if that name's already taken. It's listed in the e-book with the
+//! server's `Content-Type`, if any.
+//!
+//! Network entries can also use `file:` URLs, which are read from the local
+//! filesystem as-is, since they're absolute, and `data:` URLs, whose content is
+//! decoded from the URL itself, with its media type. Neither is cached, and both
+//! work in offline mode. Only network pages read from `file:` URLs can have images
+//! from `file:` URLs included with --network-images.
+//!
+//! ## PINNING NETWORK CONTENT
+//!
+//! The values of the `Network-Content`, `Network-Image-Content`, `Network-Cover`,
+//! `Network-Include` and `Network-Description` entries can be followed by
+//! `sha256=HASH`, where HASH is the SHA-256 hash of the content, in hexadecimal,
+//! like
+//!
+//! ```text
+//! Network-Cover: https://i.imgur.com/ViQ2WED.jpg sha256=0123...cdef
+//! ```
+//!
+//! Downloaded content whose hash differs is an error with exit value 9, so that
+//! upstream changes can't silently alter the e-book.
+//!
+//! The current hashes of all network content can be printed with --print-hashes.
+//!
+//! ## GLOB PATTERNS
+//!
+//! The paths in `Content`, `Markdown-Content`, `Image-Content` and `Include`
+//! entries can be glob patterns, i.e. contain `*`, `?` or `[...]`, like
+//!
+//! ```text
+//! Content: chapters/*.html
+//! ```
+//!
+//! These are matched against all include directories and replaced with an entry
+//! for each file found, in natural order (so "ch2.html" comes before "ch10.html").
+//! Paths of existing files, like "notes[1].html", are used as-is, however.
+//!
+//! A glob pattern that doesn't match any files is an error.
+//!
+//! ## CHECK MODE
+//!
+//! With the `check` subcommand, instead of assembling a book, an existing ePub
+//! is checked for the structural rules gen-epub-book itself abides by:
+//!
+//!   * the uncompressed `mimetype` file is the first entry in the archive,
+//!   * `META-INF/container.xml` points at an existing package document,
+//!   * every manifest item is present in the archive,
+//!   * every spine item reference points at a manifest item,
+//!   * IDs are unique within each document,
+//!   * all XHTML documents, the package document and the NCX are well-formed,
+//!   * all NCX and navigation document targets exist.
+//!
+//! The first rule broken is reported with exit value 7.
+//!
+//! ## ENVIRONMENT
+//!
+//! SOURCE_DATE_EPOCH
+//!
+//! ```text
+//! Unix timestamp to use as the modification time of all files in the e-book,
+//! and as the ePub 3 modification date, and for the DATE and YEAR variables,
+//! instead of the current time.
+//!
+//! See https://reproducible-builds.org/specs/source-date-epoch.
+//! ```
+//!
 //! ## FREE DATE FORMAT FEATURE
 //!
 //! With the -D/--free-date flag, you can enable the
@@ -216,6 +680,13 @@ extern crate clap;
 extern crate uuid;
 extern crate url;
 extern crate zip;
+extern crate xml;
+extern crate pulldown_cmark;
+extern crate natord;
+extern crate glob;
+extern crate kuchiki;
+extern crate sha2;
+extern crate base64;
 
 mod error;
 mod options;
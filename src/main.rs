@@ -23,19 +23,47 @@ fn actual_main() -> i32 {
 fn result_main() -> Result<(), Error> {
     let opts = Options::parse();
 
+    if let Some(epub) = opts.check_file.as_ref() {
+        try!(ops::check_epub(&mut try!(File::open(&epub.1).map_err(|_| {
+                                 Error::Io {
+                                     desc: "ePub file",
+                                     op: "open",
+                                     more: None,
+                                 }
+                             })),
+                             opts.verbose,
+                             &mut stderr()));
+        if opts.verbose {
+            let _ = writeln!(stderr(), "{} is valid.", epub.0);
+        }
+        return Ok(());
+    }
+
+    let descriptor_config = ops::DescriptorConfig {
+        strict: opts.strict,
+        include_order: opts.include_directories.clone(),
+        defines: opts.defines.clone(),
+        environment: true,
+        path: opts.source_file.as_ref().map(|infile| infile.1.clone()),
+    };
     let descriptors = try!(if let Some(infile) = opts.source_file.as_ref() {
-        ops::parse_descriptor("input file",
-                              &mut try!(File::open(&infile.1).map_err(|_| {
+        ops::parse_descriptor_ext(&infile.0,
+                                  &mut try!(File::open(&infile.1).map_err(|_| {
             Error::Io {
                 desc: "input file",
                 op: "open",
                 more: None,
             }
         })),
-                              &opts.separator,
-                              opts.free_date)
+                                  &opts.separator,
+                                  opts.free_date,
+                                  &descriptor_config)
     } else {
-        ops::parse_descriptor("stdin", &mut stdin(), &opts.separator, opts.free_date)
+        ops::parse_descriptor_ext("stdin",
+                                  &mut stdin(),
+                                  &opts.separator,
+                                  opts.free_date,
+                                  &descriptor_config)
     });
 
     if opts.verbose {
@@ -55,8 +83,20 @@ fn result_main() -> Result<(), Error> {
     }
 
     let mut book = try!(ops::EPubBook::from_elements(descriptors));
+    book.version = opts.epub_version;
+    book.legacy_ncx = opts.legacy_ncx;
+    book.normalise_html = opts.normalise_html;
+    book.download = opts.download.clone();
+    book.network_images = opts.network_images;
     try!(book.normalise_paths(&opts.include_directories, opts.verbose, &mut stderr()));
 
+    if opts.print_hashes {
+        for (url, hash) in try!(book.hash_network_content(opts.verbose, &mut stderr())) {
+            println!("{} sha256={}", url, hash);
+        }
+        return Ok(());
+    }
+
     if let Some(outfile) = opts.output_file.as_ref() {
         if let Some(p) = outfile.1.parent() {
             if !p.as_os_str().is_empty() && !p.exists() && fs::create_dir_all(p).is_ok() && opts.verbose {
@@ -1,15 +1,24 @@
-use self::super::super::util::{CONTENT_TABLE_HEADER, CONTAINER, MIME_TYPE, write_string_content, xhtml_path_id, book_filename, xhtml_url_id, download_to};
-use self::super::{IncludeDirectory, BookElement, find_title, find_file};
-use mime_guess::{Mime, guess_mime_type_opt};
+use self::super::super::util::{CONTENT_TABLE_HEADER_3, CONTENT_TABLE_HEADER, CONTAINER, MIME_TYPE, write_string_content_ext, xhtml_path_id, book_filename,
+                               xhtml_url_id, url_filename, map_references, map_css_references, render_markdown, html_to_xhtml, download_to_with_client, xml_escape, source_date_epoch,
+                               sha256_hex, DownloadConfig, DownloadInfo};
+use self::super::{IncludeDirectory, BookElement, find_markdown_title_ext, find_html_title_ext, find_title_ext, find_file};
+use mime_guess::{Mime, get_mime_extensions_str, guess_mime_type_opt};
 use zip::write::{ZipWriter, FileOptions};
-use chrono::{DateTime, FixedOffset};
-use std::io::{self, Write, Seek};
-use std::collections::BTreeSet;
-use std::path::{PathBuf, Path};
+use zip::{CompressionMethod, DateTime as ZipDateTime};
+use chrono::{DateTime, FixedOffset, Datelike, Timelike, Utc};
+use std::io::{self, Write, Read, Seek};
+use url::percent_encoding::{DEFAULT_ENCODE_SET, percent_decode, utf8_percent_encode};
+use std::path::{Component, PathBuf, Path};
+use std::collections::{BTreeMap, BTreeSet};
 use self::super::super::Error;
 use std::iter::IntoIterator;
-use std::fmt::Display;
+use std::borrow::Cow;
+use std::str::FromStr;
+use std::{fmt, str};
+use std::sync::{Arc, Mutex, mpsc};
 use std::fs::File;
+use std::thread;
+use std::cmp;
 use uuid::Uuid;
 use url::Url;
 
@@ -17,17 +26,39 @@ use url::Url;
 /// An (ID, filename, data) tuple that represents a single data point of an ePub.
 pub type EPubData = (String, PathBuf, EPubContentType);
 
+/// A (name, name to sort by, [MARC relator code](https://www.loc.gov/marc/relators/relaterm.html)) tuple
+/// that represents a single person responsible for an ePub.
+pub type EPubCreator = (String, Option<String>, String);
+
 /// Full ePub book, bundled together.
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct EPubBook {
     /// E-book's title
     pub name: String,
-    /// E-book's author
-    pub author: String,
+    /// E-book's authors, with the `aut` role if from `Author` elements
+    pub authors: Vec<EPubCreator>,
+    /// E-book's other contributors, like translators or illustrators
+    pub contributors: Vec<EPubCreator>,
     /// E-book's authoring/publishing date
     pub date: DateTime<FixedOffset>,
     /// Language used in e-book
     pub language: String,
+    /// E-book's publisher, if any
+    pub publisher: Option<String>,
+    /// E-book's copyright and licensing statement, if any
+    pub rights: Option<String>,
+    /// Topics of the e-book
+    pub subjects: Vec<String>,
+    /// Work the e-book is derived from, if any
+    pub source: Option<String>,
+    /// Works the e-book is related to
+    pub relations: Vec<String>,
+    /// Additional (scheme, identifier) pairs identifying the e-book, like `("ISBN", "9780306406157")`
+    pub identifiers: Vec<(String, String)>,
+    /// E-book's last modification date, if not the time of writing it
+    pub modified: Option<DateTime<FixedOffset>>,
+    /// Series the e-book belongs to and its position therein, if any
+    pub series: Option<(String, Option<String>)>,
     /// Image to use as e-book cover, if any
     pub cover: Option<EPubData>,
     /// Description of the book, if any.
@@ -36,8 +67,90 @@ pub struct EPubBook {
     content: Vec<EPubData>,
     /// Things that aren't *content* but go in the e-book
     non_content: Vec<EPubData>,
+    /// ePub version to target
+    ///
+    /// Default: `EPubVersion::V2`
+    pub version: EPubVersion,
+    /// Whether to also write the NCX table of contents when targetting ePub 3, for older readers
+    ///
+    /// ePub 2 books always have it.
+    ///
+    /// Default: `true`
+    pub legacy_ncx: bool,
+    /// How to compress textual files, like (X)HTML, CSS, and the tables of contents
+    ///
+    /// Default: `Compression::Deflated`
+    pub compression: Compression,
+    /// How to compress already-compressed media files, like images, audio, video, and fonts
+    ///
+    /// Default: `Compression::Deflated`
+    pub media_compression: Compression,
+    /// Whether to convert all (X)HTML documents, which may be tag-soup HTML, into well-formed XHTML
+    ///
+    /// Documents without a title get their table of contents title, or the e-book's title.
+    ///
+    /// Default: `false`
+    pub normalise_html: bool,
+    /// How to download network content
+    ///
+    /// Default: `DownloadConfig::default()`
+    pub download: DownloadConfig,
+    /// Whether to also download and include images referenced from `Network-Content` pages
+    ///
+    /// Images that fail to download are left as-is, with a warning.
+    ///
+    /// Default: `false`
+    pub network_images: bool,
+    /// SHA-256 hashes, as lowercase hex, network content has to have, by URL
+    ///
+    /// Downloaded content with a different hash is an `Error::HashMismatch`.
+    ///
+    /// Default: the hashes pinned in the elements
+    pub network_hashes: BTreeMap<Url, String>,
     /// E-book's UUID
     uuid: Uuid,
+    /// Packed names of all files in the e-book, by their normalised absolute paths
+    /// and by what references to them resolve to relative to the referencing file
+    ///
+    /// Populated by `normalise_paths()`.
+    packed_names: BTreeMap<PathBuf, PathBuf>,
+    /// Media types of packed files, by their packed names, where they aren't guessed from the packed name
+    ///
+    /// Populated by `with_network_names()`.
+    media_types: BTreeMap<PathBuf, String>,
+    /// Packed names of network content, by URL
+    ///
+    /// Populated by `with_network_names()`.
+    packed_urls: BTreeMap<Url, PathBuf>,
+    /// Packed names of the images shown by image pages, and the URLs of network ones, by the image pages' packed names
+    ///
+    /// Image pages are generated when writing the book, for the targetted ePub version and the final names of network images.
+    image_pages: BTreeMap<PathBuf, (PathBuf, Option<Url>)>,
+}
+
+/// ePub specification version to target when writing a book
+///
+/// Textually, `"2"` or `"3"`.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EPubVersion {
+    /// ePub 2.0.1: OPF 2.0 package and NCX table of contents
+    V2,
+    /// ePub 3: OPF 3.0 package and XHTML navigation document
+    V3,
+}
+
+/// How to compress files in the ePub archive
+///
+/// The `mimetype` file is always stored uncompressed, as required by the specification.
+///
+/// Only the method can be chosen, not the compression level:
+/// the `zip` crate, as of 0.5, always DEFLATEs at the default level, 6, and has no way to add entries compressed elsewhere.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Compression {
+    /// Don't compress at all
+    Stored,
+    /// Compress with DEFLATE, at the default level
+    Deflated,
 }
 
 /// Enum representing what can go in an ePub
@@ -51,6 +164,14 @@ pub enum EPubContentType {
     ///
     /// Wrapped in `util::write_string_content()`.
     Raw(String),
+    /// Sourced from a Markdown file
+    ///
+    /// Rendered to XHTML and wrapped in `util::write_string_content()`.
+    MarkdownFile(PathBuf),
+    /// Sourced from a Markdown string
+    ///
+    /// Rendered to XHTML and wrapped in `util::write_string_content()`.
+    MarkdownRaw(String),
 }
 
 
@@ -59,6 +180,9 @@ impl EPubBook {
     ///
     /// Returns an error upon violating any of the requirements laid forth in the variants of `BookElement`.
     ///
+    /// Absent a `UUID` element, the book's UUID is derived from all the elements,
+    /// so that constructing a book from the same elements always yields the same UUID.
+    ///
     /// # Examples
     ///
     /// ```
@@ -73,13 +197,13 @@ impl EPubBook {
     ///     BookElement::Cover(PathBuf::from("examples/cover.png")),
     ///     BookElement::ImageContent(PathBuf::from("examples/simple/chapter_image.png")),
     ///     BookElement::Content(PathBuf::from("examples/simple/ctnt.html")),
-    ///     BookElement::Author("nabijaczleweli".to_string()),
+    ///     BookElement::Author("nabijaczleweli".to_string(), None),
     ///     BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
     ///     BookElement::Language("en-GB".to_string()),
     /// ]).unwrap();
     ///
     /// assert_eq!(book.name, "Simple ePub demonstration".to_string());
-    /// assert_eq!(book.author, "nabijaczleweli".to_string());
+    /// assert_eq!(book.authors, vec![("nabijaczleweli".to_string(), None, "aut".to_string())]);
     /// assert_eq!(book.date, DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap());
     /// assert_eq!(book.language, "en-GB".to_string());
     /// assert_eq!(book.cover, Some(("cover-content-1".to_string(),
@@ -91,61 +215,103 @@ impl EPubBook {
     /// # }
     /// ```
     pub fn from_elements<E: IntoIterator<Item = BookElement>>(elems: E) -> Result<EPubBook, Error> {
+        lazy_static! {
+            static ref UUID_NAMESPACE: Uuid = Uuid::new_v5(&Uuid::NAMESPACE_URL, b"https://github.com/nabijaczleweli/gen-epub-book.rs");
+        }
+
+        let mut descriptor = String::new();
+        let mut uuid = None;
         let mut name = None;
-        let mut author = None;
+        let mut authors = vec![];
+        let mut contributors = vec![];
         let mut date = None;
         let mut language = None;
+        let mut publisher = None;
+        let mut rights = None;
+        let mut subjects = vec![];
+        let mut source = None;
+        let mut relations = vec![];
+        let mut isbn = None;
+        let mut identifiers = vec![];
+        let mut modified = None;
+        let mut series = None;
+        let mut series_index = None;
         let mut cover = None;
         let mut description = None;
         let mut content = vec![];
         let mut non_content = vec![];
+        let mut network_hashes = BTreeMap::new();
+        let mut image_pages = BTreeMap::new();
 
         for (i, elem) in elems.into_iter().enumerate() {
+            descriptor.push_str(&elem.to_string());
+            descriptor.push('\n');
+
+            match elem {
+                BookElement::NetworkContent(ref u, Some(ref h)) |
+                BookElement::NetworkImageContent(ref u, Some(ref h)) |
+                BookElement::NetworkCover(ref u, Some(ref h)) |
+                BookElement::NetworkInclude(ref u, Some(ref h)) |
+                BookElement::NetworkDescription(ref u, Some(ref h)) => {
+                    network_hashes.insert(u.clone(), h.clone());
+                }
+                _ => {}
+            }
+
             match elem {
                 BookElement::Name(n) => name = try!(EPubBook::handle_essential_element(name, n, "Name")),
                 BookElement::Content(c) => content.push((xhtml_path_id(&c), book_filename(&c), EPubContentType::File(c))),
+                BookElement::NetworkContent(c, _) => content.push((xhtml_url_id(&c), PathBuf::from(url_filename(&c)), EPubContentType::Network(c))),
                 BookElement::StringContent(c) => {
                     content.push((format!("string-content-{}", i), PathBuf::from(format!("string-data-{}.html", i)), EPubContentType::Raw(c)))
                 }
+                BookElement::MarkdownContent(c) => {
+                    content.push((xhtml_path_id(&c), book_filename(&c).with_extension("html"), EPubContentType::MarkdownFile(c)))
+                }
+                BookElement::StringMarkdownContent(c) => {
+                    content.push((format!("string-markdown-content-{}", i),
+                                  PathBuf::from(format!("string-markdown-data-{}.html", i)),
+                                  EPubContentType::MarkdownRaw(c)))
+                }
                 BookElement::ImageContent(c) => {
                     let fname = book_filename(&c);
+                    let page = PathBuf::from(format!("image-data-{}.html", i));
+                    image_pages.insert(page.clone(), (fname.clone(), None));
                     non_content.push((xhtml_path_id(&c), fname.clone(), EPubContentType::File(c)));
-                    content.push((format!("image-content-{}", i),
-                                  PathBuf::from(format!("image-data-{}.html", i)),
-                                  EPubContentType::Raw(format!(r#"<center><img src="{}" alt="{0}"></img></center>"#, fname.display()))));
+                    content.push((format!("image-content-{}", i), page, EPubContentType::Raw(EPubBook::image_page(&fname, EPubVersion::V2))));
                 }
-                BookElement::NetworkImageContent(c) => {
-                    let fname = c.path_segments().unwrap().last().unwrap().to_string();
-                    non_content.push((xhtml_url_id(&c).to_string(), PathBuf::from(&fname), EPubContentType::Network(c)));
-                    content.push((format!("network-image-content-{}", i),
-                                  PathBuf::from(format!("network-image-data-{}.html", i)),
-                                  EPubContentType::Raw(format!(r#"<center><img src="{}" alt="{0}"></img></center>"#, fname))));
+                BookElement::NetworkImageContent(c, _) => {
+                    let fname = PathBuf::from(url_filename(&c));
+                    let page = PathBuf::from(format!("network-image-data-{}.html", i));
+                    image_pages.insert(page.clone(), (fname.clone(), Some(c.clone())));
+                    non_content.push((xhtml_url_id(&c), fname.clone(), EPubContentType::Network(c)));
+                    content.push((format!("network-image-content-{}", i), page, EPubContentType::Raw(EPubBook::image_page(&fname, EPubVersion::V2))));
                 }
                 BookElement::Cover(c) => {
                     let fname = book_filename(&c);
+                    let page = PathBuf::from(format!("cover-data-{}.html", i));
+                    image_pages.insert(page.clone(), (fname.clone(), None));
                     non_content.push(try!(EPubBook::handle_essential_element(cover,
                                                                              (xhtml_path_id(&c), fname.clone(), EPubContentType::File(c)),
                                                                              "Cover and Network-Cover"))
                         .unwrap());
-                    cover = Some((format!("cover-content-{}", i),
-                                  PathBuf::from(format!("cover-data-{}.html", i)),
-                                  EPubContentType::Raw(format!(r#"<center><img src="{}" alt="{0}"></img></center>"#, fname.display()))));
+                    cover = Some((format!("cover-content-{}", i), page, EPubContentType::Raw(EPubBook::image_page(&fname, EPubVersion::V2))));
                 }
-                BookElement::NetworkCover(c) => {
-                    let fname = PathBuf::from(c.path_segments().unwrap().last().unwrap());
+                BookElement::NetworkCover(c, _) => {
+                    let fname = PathBuf::from(url_filename(&c));
+                    let page = PathBuf::from(format!("network-cover-data-{}.html", i));
+                    image_pages.insert(page.clone(), (fname.clone(), Some(c.clone())));
                     non_content.push(try!(EPubBook::handle_essential_element(cover,
                                                                              (format!("network-cover-{}", xhtml_url_id(&c)),
                                                                               fname.clone(),
                                                                               EPubContentType::Network(c)),
                                                                              "Cover and Network-Cover"))
                         .unwrap());
-                    cover = Some((format!("network-cover-content-{}", i),
-                                  PathBuf::from(format!("network-cover-data-{}.html", i)),
-                                  EPubContentType::Raw(format!(r#"<center><img src="{}" alt="{0}"></img></center>"#, fname.display()))));
+                    cover = Some((format!("network-cover-content-{}", i), page, EPubContentType::Raw(EPubBook::image_page(&fname, EPubVersion::V2))));
                 }
                 BookElement::Include(c) => non_content.push((xhtml_path_id(&c), book_filename(&c), EPubContentType::File(c))),
-                BookElement::NetworkInclude(c) => {
-                    non_content.push((xhtml_url_id(&c).to_string(), PathBuf::from(c.path_segments().unwrap().last().unwrap()), EPubContentType::Network(c)));
+                BookElement::NetworkInclude(c, _) => {
+                    non_content.push((xhtml_url_id(&c), PathBuf::from(url_filename(&c)), EPubContentType::Network(c)));
                 }
                 BookElement::Description(c) => {
                     description = try!(EPubBook::handle_essential_element(description,
@@ -157,27 +323,66 @@ impl EPubBook {
                                                                           EPubContentType::Raw(c),
                                                                           "Description, String-Description, and Network-Description"))
                 }
-                BookElement::NetworkDescription(c) => {
+                BookElement::NetworkDescription(c, _) => {
                     description = try!(EPubBook::handle_essential_element(description,
                                                                           EPubContentType::Network(c),
                                                                           "Description, String-Description, and Network-Description"))
                 }
-                BookElement::Author(a) => author = try!(EPubBook::handle_essential_element(author, a, "Author")),
+                BookElement::Author(a, f) => authors.push((a, f, "aut".to_string())),
+                BookElement::Contributor(r, c, f) => contributors.push((c, f, r)),
                 BookElement::Date(d) => date = try!(EPubBook::handle_essential_element(date, d, "Date")),
                 BookElement::Language(l) => language = try!(EPubBook::handle_essential_element(language, l, "Language")),
+                BookElement::Uuid(u) => uuid = try!(EPubBook::handle_essential_element(uuid, u, "UUID")),
+                BookElement::Publisher(p) => publisher = try!(EPubBook::handle_essential_element(publisher, p, "Publisher")),
+                BookElement::Rights(r) => rights = try!(EPubBook::handle_essential_element(rights, r, "Rights")),
+                BookElement::Subject(s) => subjects.push(s),
+                BookElement::Source(s) => source = try!(EPubBook::handle_essential_element(source, s, "Source")),
+                BookElement::Relation(r) => relations.push(r),
+                BookElement::Isbn(i) => {
+                    isbn = try!(EPubBook::handle_essential_element(isbn, (), "ISBN"));
+                    identifiers.push(("ISBN".to_string(), i));
+                }
+                BookElement::Identifier(s, i) => identifiers.push((s, i)),
+                BookElement::Modified(m) => modified = try!(EPubBook::handle_essential_element(modified, m, "Modified")),
+                BookElement::Series(s) => series = try!(EPubBook::handle_essential_element(series, s, "Series")),
+                BookElement::SeriesIndex(i) => series_index = try!(EPubBook::handle_essential_element(series_index, i, "Series-Index")),
             }
         }
+        if series_index.is_some() && series.is_none() {
+            return Err(Error::RequiredElementMissing("Series"));
+        }
 
         Ok(EPubBook {
             name: try!(EPubBook::require_essential_element(name, "Name")),
-            author: try!(EPubBook::require_essential_element(author, "Author")),
+            authors: try!(EPubBook::require_essential_element(Some(authors).filter(|a| !a.is_empty()), "Author")),
+            contributors: contributors,
             date: try!(EPubBook::require_essential_element(date, "Date")),
             language: try!(EPubBook::require_essential_element(language, "Language")),
+            publisher: publisher,
+            rights: rights,
+            subjects: subjects,
+            source: source,
+            relations: relations,
+            identifiers: identifiers,
+            modified: modified,
+            series: series.map(|s| (s, series_index)),
             cover: cover,
             description: description,
             content: content,
             non_content: non_content,
-            uuid: Uuid::new_v4(),
+            version: EPubVersion::V2,
+            legacy_ncx: true,
+            compression: Compression::Deflated,
+            media_compression: Compression::Deflated,
+            normalise_html: false,
+            download: DownloadConfig::default(),
+            network_images: false,
+            network_hashes: network_hashes,
+            uuid: uuid.unwrap_or_else(|| Uuid::new_v5(&UUID_NAMESPACE, descriptor.as_bytes())),
+            packed_names: BTreeMap::new(),
+            media_types: BTreeMap::new(),
+            packed_urls: BTreeMap::new(),
+            image_pages: image_pages,
         })
     }
 
@@ -186,6 +391,16 @@ impl EPubBook {
     ///
     /// Will return an error if the file the path points to doesn't exist or isn't a file.
     ///
+    /// Afterwards, (X)HTML and CSS files (and Markdown content) are scanned for references to local files (see
+    /// [`util::map_references()`](../util/fn.map_references.html) and [`util::map_css_references()`](../util/fn.map_css_references.html)),
+    /// which are looked up relative to the referencing file,
+    /// first in its include directory, then in the rest, and, if found, added to the book
+    /// (and scanned in turn, if they're CSS or (X)HTML themselves).
+    /// The references will be rewritten to point at the packed files when writing the book.
+    ///
+    /// Links (`a` and `area` `href`s) are rewritten the same way, but only to files already in the book;
+    /// a warning is written to the output stream, regardless of verbosity, for links to other local files.
+    ///
     /// # Examples
     ///
     /// ```
@@ -207,7 +422,7 @@ impl EPubBook {
     ///     BookElement::Name("Path normalisation demonstration".to_string()),
     ///     BookElement::Cover(PathBuf::from("cover.png")),
     ///     BookElement::Content(PathBuf::from("content/ch01.html")),
-    ///     BookElement::Author("nabijaczleweli".to_string()),
+    ///     BookElement::Author("nabijaczleweli".to_string(), None),
     ///     BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
     ///     BookElement::Language("en-GB".to_string()),
     /// ]).unwrap();
@@ -237,9 +452,100 @@ impl EPubBook {
             try!(EPubBook::normalise_path(relroot, pb, &mut String::new(), &mut PathBuf::new(), "Description", verbose, verb_out));
         }
 
+        // (relative path, include directory, absolute path, is Markdown) of the files to scan for references
+        let mut referrers = vec![];
         for ctnt in self.content.iter_mut().chain(self.non_content.iter_mut()) {
-            if let (ref mut id, ref mut packed_name, EPubContentType::File(ref mut pb)) = *ctnt {
-                try!(EPubBook::normalise_path(relroot, pb, id, packed_name, "Content, Image or Include", verbose, verb_out));
+            match *ctnt {
+                (ref mut id, ref mut packed_name, EPubContentType::File(ref mut pb)) => {
+                    let relpath = pb.clone();
+                    let root = try!(EPubBook::normalise_path(relroot, pb, id, packed_name, "Content, Image or Include", verbose, verb_out));
+                    if EPubBook::may_reference(&EPubBook::guess_type(packed_name).to_string()) {
+                        referrers.push((relpath, root, pb.clone(), false));
+                    }
+                }
+                (ref mut id, ref mut packed_name, EPubContentType::MarkdownFile(ref mut pb)) => {
+                    let relpath = pb.clone();
+                    let root = try!(EPubBook::normalise_path(relroot, pb, id, packed_name, "Markdown-Content", verbose, verb_out));
+                    packed_name.set_extension("html");
+                    referrers.push((relpath, root, pb.clone(), true));
+                }
+                _ => {}
+            }
+        }
+
+        self.packed_names = self.cover
+            .iter()
+            .chain(self.content.iter())
+            .chain(self.non_content.iter())
+            .flat_map(|&(_, ref packed_name, ref tp)| match *tp {
+                EPubContentType::File(ref pb) |
+                EPubContentType::MarkdownFile(ref pb) => Some((normalise_lexically(pb), packed_name.clone())),
+                _ => None,
+            })
+            .collect();
+
+        // Links are only resolved after all resources have been found, since they can point at ones found later
+        let mut links = vec![];
+        let mut i = 0;
+        while i < referrers.len() {
+            let (relpath, root, abspath, markdown) = referrers[i].clone();
+            i += 1;
+
+            let mut references = vec![];
+            let media_type = EPubBook::guess_type(&abspath).to_string();
+            map_content_references(&try!(EPubBook::read_content(&abspath, markdown)), &media_type, |r, link| {
+                if let Some((path, _)) = local_reference(r) {
+                    if link {
+                        links.push((relpath.clone(), root, abspath.clone(), path));
+                    } else {
+                        references.push(path);
+                    }
+                }
+                None
+            });
+
+            for path in references {
+                let (reference, reference_root) = EPubBook::find_reference(relroot, root, &relpath, &path);
+                if let Some(reference_root) = reference_root {
+                    let reference_abspath = reference_root.resolve(&reference).unwrap();
+                    let key = normalise_lexically(&reference_abspath);
+                    let packed_name = if let Some(packed_name) = self.packed_names.get(&key) {
+                        packed_name.clone()
+                    } else {
+                        let packed_name = reference_root.packed_name(&reference);
+                        if verbose {
+                            let _ = writeln!(verb_out,
+                                             "Found {}{} referenced from {}, packing as {}.",
+                                             reference_root.directory_name(),
+                                             reference.display(),
+                                             relpath.display(),
+                                             packed_name.display());
+                        }
+                        self.packed_names.insert(key, packed_name.clone());
+                        if EPubBook::may_reference(&EPubBook::guess_type(&packed_name).to_string()) {
+                            referrers.push((reference.clone(), reference_root, reference_abspath.clone(), false));
+                        }
+                        self.non_content.push((reference_root.packed_id(&reference), packed_name.clone(), EPubContentType::File(reference_abspath)));
+                        packed_name
+                    };
+
+                    // References are rewritten relative to the referencing file, which may be in a different include directory
+                    self.packed_names.entry(normalise_lexically(&abspath.parent().unwrap_or(Path::new("")).join(path))).or_insert(packed_name);
+                } else if verbose {
+                    let _ = writeln!(verb_out, "Couldn't find {} referenced from {}, leaving as-is.", reference.display(), relpath.display());
+                }
+            }
+        }
+
+        for (relpath, root, abspath, path) in links {
+            let (reference, reference_root) = EPubBook::find_reference(relroot, root, &relpath, &path);
+            match reference_root.and_then(|reference_root| self.packed_names.get(&normalise_lexically(&reference_root.resolve(&reference).unwrap())).cloned()) {
+                Some(packed_name) => {
+                    self.packed_names.entry(normalise_lexically(&abspath.parent().unwrap_or(Path::new("")).join(path))).or_insert(packed_name);
+                }
+                None => {
+                    let _ = writeln!(verb_out, "Warning: {} links to {}, which isn't in the book.", relpath.display(), reference.display());
+                }
             }
         }
 
@@ -269,7 +575,7 @@ impl EPubBook {
     ///     BookElement::Name("Path normalisation demonstration".to_string()),
     ///     BookElement::Cover(PathBuf::from("cover.png")),
     ///     BookElement::Content(PathBuf::from("content/ch01.html")),
-    ///     BookElement::Author("nabijaczleweli".to_string()),
+    ///     BookElement::Author("nabijaczleweli".to_string(), None),
     ///     BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
     ///     BookElement::Language("en-GB".to_string()),
     /// ]).unwrap();
@@ -294,6 +600,18 @@ impl EPubBook {
     ///
     /// This function is equivalent to [`write_zip()`](fn.write_zip.html) with all config arguments defaulted.
     ///
+    /// Which ePub version gets written is governed by `version` and `legacy_ncx`,
+    /// archive entry compression by `compression` and `media_compression`,
+    /// conversion of documents to well-formed XHTML by `normalise_html`, how network content is downloaded by `download`,
+    /// and which hashes it must have by `network_hashes`.
+    ///
+    /// The archive entries' modification times, and the ePub 3 modification date,
+    /// are taken from the `SOURCE_DATE_EPOCH` environment variable, if set, or are the current time otherwise.
+    ///
+    /// Network content is packed under the filename suggested by the server, if any, or the one from its URL otherwise,
+    /// suffixed with the extension for the content type reported by the server, if it has none, and made unique within the book.
+    /// It's listed with the content type reported by the server, if any.
+    ///
     /// Config arguments:
     ///   * `string_toc` – whether to process `Raw` elements for TOC specifiers – default: `false`
     ///
@@ -332,7 +650,7 @@ impl EPubBook {
     /// Sherlock Holmes appeared for the first time in A Study in Scarlet and The Sign of The Four, two novels published in
     /// 1887 and 1890.
     /// "#.to_string()),
-    ///     BookElement::Author("nabijaczleweli".to_string()),
+    ///     BookElement::Author("nabijaczleweli".to_string(), None),
     ///     BookElement::Date(DateTime::parse_from_rfc3339("2018-06-27T12:30:38+02:00").unwrap()),
     ///     BookElement::Language("en-GB".to_string()),
     /// ]).unwrap();
@@ -345,21 +663,35 @@ impl EPubBook {
     /// # }
     /// ```
     pub fn write_zip_ext<W: Write + Seek, V: Write>(&self, string_toc: bool, to: &mut W, verbose: bool, verb_out: &mut V) -> Result<(), Error> {
+        let modified = try!(source_date_epoch()).unwrap_or_else(Utc::now);
+        let downloaded = try!(self.download_network_content(verbose, verb_out));
+        let book = try!(self.with_network_names(&downloaded, verbose, verb_out));
         let mut w = ZipWriter::new(to);
 
-        try!(w.start_file("mimetype", FileOptions::default()).map_err(|_| EPubBook::zip_error("create", "container file")));
+        // The mimetype needs to be the first file, uncompressed, so that it can be read at a fixed offset
+        try!(w.start_file("mimetype", EPubBook::file_options(Compression::Stored, &modified))
+            .map_err(|_| EPubBook::zip_error("create", "container file")));
         try!(w.write_all(MIME_TYPE.as_bytes()).map_err(|_| EPubBook::zip_error("write", "container file")));
 
-        try!(w.start_file("META-INF/container.xml", FileOptions::default()).map_err(|_| EPubBook::zip_error("create", "container file")));
+        try!(w.start_file("META-INF/container.xml", EPubBook::file_options(book.compression, &modified))
+            .map_err(|_| EPubBook::zip_error("create", "container file")));
         try!(w.write_all(CONTAINER.as_bytes()).map_err(|_| EPubBook::zip_error("write", "container file")));
 
-        try!(w.start_file("content.opf", FileOptions::default()).map_err(|_| EPubBook::zip_error("create", "content table")));
-        try!(self.content_table(&mut w, verbose, verb_out));
+        try!(w.start_file("content.opf", EPubBook::file_options(book.compression, &modified)).map_err(|_| EPubBook::zip_error("create", "content table")));
+        try!(book.content_table(&modified, &downloaded, &mut w));
 
-        try!(w.start_file("toc.ncx", FileOptions::default()).map_err(|_| EPubBook::zip_error("create", "table of contents")));
-        try!(self.table_of_contents(string_toc, &mut w, verbose, verb_out));
+        let toc = try!(book.toc_entries(string_toc, &downloaded, verbose, verb_out));
+        if book.has_ncx() {
+            try!(w.start_file("toc.ncx", EPubBook::file_options(book.compression, &modified)).map_err(|_| EPubBook::zip_error("create", "table of contents")));
+            try!(book.table_of_contents(&toc, &mut w));
+        }
+        if book.version == EPubVersion::V3 {
+            try!(w.start_file("nav.xhtml", EPubBook::file_options(book.compression, &modified))
+                .map_err(|_| EPubBook::zip_error("create", "navigation document")));
+            try!(book.navigation_document(&toc, &mut w));
+        }
 
-        try!(self.write_content(&mut w, verbose, verb_out));
+        try!(book.write_content(&modified, &toc, &downloaded, &mut w));
 
         Ok(())
     }
@@ -387,9 +719,9 @@ impl EPubBook {
         }
     }
 
-    fn normalise_path<W: Write>(relroots: &[IncludeDirectory], file: &mut PathBuf, id: &mut String, packed_name: &mut PathBuf, name: &'static str,
-                                verbose: bool, verb_out: &mut W)
-                                -> Result<(), Error> {
+    fn normalise_path<'r, W: Write>(relroots: &'r [IncludeDirectory], file: &mut PathBuf, id: &mut String, packed_name: &mut PathBuf, name: &'static str,
+                                    verbose: bool, verb_out: &mut W)
+                                    -> Result<&'r IncludeDirectory, Error> {
         if let Some(root) = find_file(&file, relroots) {
             if verbose {
                 let _ = writeln!(verb_out, "Normalised {} to {}{0} for {}.", file.display(), root.directory_name(), name);
@@ -397,7 +729,7 @@ impl EPubBook {
             *id = root.packed_id(file);
             *packed_name = root.packed_name(&file);
             *file = root.resolve(&file).unwrap();
-            Ok(())
+            Ok(root)
         } else {
             Err(Error::FileNotFound {
                 who: name,
@@ -406,6 +738,21 @@ impl EPubBook {
         }
     }
 
+    fn file_options(compression: Compression, modified: &DateTime<Utc>) -> FileOptions {
+        FileOptions::default()
+            .compression_method(match compression {
+                Compression::Stored => CompressionMethod::Stored,
+                Compression::Deflated => CompressionMethod::Deflated,
+            })
+            .last_modified_time(ZipDateTime::from_date_and_time(modified.year() as u16,
+                                                                modified.month() as u8,
+                                                                modified.day() as u8,
+                                                                modified.hour() as u8,
+                                                                modified.minute() as u8,
+                                                                modified.second() as u8)
+                .unwrap_or_default())
+    }
+
     fn zip_error(verb: &'static str, what: &'static str) -> Error {
         Error::Io {
             desc: "ePub",
@@ -414,63 +761,136 @@ impl EPubBook {
         }
     }
 
-    fn content_table<W: Write, V: Write>(&self, w: &mut W, verbose: bool, verb_out: &mut V) -> Result<(), Error> {
-        try!(w.write_all(CONTENT_TABLE_HEADER.as_bytes()).map_err(|_| EPubBook::zip_error("write", "content table header")));
-        try!(writeln!(w, "    <dc:title>{}</dc:title>", self.name).map_err(|_| EPubBook::zip_error("write", "content table title line")));
-        try!(writeln!(w, r#"    <dc:creator opf:role="aut">{}</dc:creator>"#, self.author)
-            .map_err(|_| EPubBook::zip_error("write", "content table author line")));
-        try!(writeln!(w,
-                      r#"    <dc:identifier id="uuid" opf:scheme="uuid">{}</dc:identifier>"#,
-                      self.uuid.to_hyphenated_ref())
-            .map_err(|_| EPubBook::zip_error("write", "content table uuid line")));
+    /// Get the markup of a page showing the image packed as `fname`, centred with `<center>` for ePub 2,
+    /// and with CSS for ePub 3, since XHTML5 dropped it.
+    fn image_page(fname: &Path, version: EPubVersion) -> String {
+        match version {
+            EPubVersion::V2 => format!(r#"<center><img src="{}" alt="{0}"></img></center>"#, xml_escape(&fname.to_string_lossy())),
+            EPubVersion::V3 => format!(r#"<div style="text-align: center;"><img src="{}" alt="{0}" /></div>"#, xml_escape(&fname.to_string_lossy())),
+        }
+    }
+
+    /// Get the packed name of the image shown by the image page packed as `page`, if it is one.
+    fn image_page_image(&self, page: &PathBuf) -> Option<&PathBuf> {
+        self.image_pages.get(page).map(|&(ref image, ref url)| url.as_ref().and_then(|u| self.packed_urls.get(u)).unwrap_or(image))
+    }
+
+    fn has_ncx(&self) -> bool {
+        self.version == EPubVersion::V2 || self.legacy_ncx
+    }
+
+    fn content_table<W: Write>(&self, modified: &DateTime<Utc>, downloaded: &BTreeMap<Url, (Vec<u8>, DownloadInfo)>, w: &mut W) -> Result<(), Error> {
+        let v3 = self.version == EPubVersion::V3;
+
+        try!(w.write_all(if v3 {
+                    CONTENT_TABLE_HEADER_3
+                } else {
+                    CONTENT_TABLE_HEADER
+                }
+                .as_bytes())
+            .map_err(|_| EPubBook::zip_error("write", "content table header")));
+        try!(writeln!(w, "    <dc:title>{}</dc:title>", xml_escape(&self.name)).map_err(|_| EPubBook::zip_error("write", "content table title line")));
+        for (i, author) in self.authors.iter().enumerate() {
+            try!(EPubBook::write_creator("creator", i, author, v3, w));
+        }
+        for (i, contributor) in self.contributors.iter().enumerate() {
+            try!(EPubBook::write_creator("contributor", i, contributor, v3, w));
+        }
+        if v3 {
+            try!(writeln!(w, r#"    <dc:identifier id="uuid">urn:uuid:{}</dc:identifier>"#, self.uuid.to_hyphenated_ref())
+                .map_err(|_| EPubBook::zip_error("write", "content table uuid line")));
+        } else {
+            try!(writeln!(w,
+                          r#"    <dc:identifier id="uuid" opf:scheme="uuid">{}</dc:identifier>"#,
+                          self.uuid.to_hyphenated_ref())
+                .map_err(|_| EPubBook::zip_error("write", "content table uuid line")));
+        }
         try!(writeln!(w, r#"    <dc:date>{}</dc:date>"#, self.date.to_rfc3339()).map_err(|_| EPubBook::zip_error("write", "content table date line")));
-        try!(writeln!(w, r#"    <dc:language>{}</dc:language>"#, self.language).map_err(|_| EPubBook::zip_error("write", "content table language line")));
+        try!(writeln!(w, r#"    <dc:language>{}</dc:language>"#, xml_escape(&self.language))
+            .map_err(|_| EPubBook::zip_error("write", "content table language line")));
+        if v3 {
+            try!(writeln!(w,
+                          r#"    <meta property="dcterms:modified">{}</meta>"#,
+                          self.modified.map_or(*modified, |m| m.with_timezone(&Utc)).format("%Y-%m-%dT%H:%M:%SZ"))
+                .map_err(|_| EPubBook::zip_error("write", "content table modification date line")));
+        } else if let Some(m) = self.modified {
+            try!(writeln!(w, r#"    <dc:date opf:event="modification">{}</dc:date>"#, m.to_rfc3339())
+                .map_err(|_| EPubBook::zip_error("write", "content table modification date line")));
+        }
+        try!(self.write_extended_metadata(v3, w));
 
         if let Some(&(ref id, _, _)) = self.cover.as_ref() {
-            try!(writeln!(w, r#"    <meta name="cover" content="{}" />"#, id).map_err(|_| EPubBook::zip_error("write", "content table cover line")));
+            try!(writeln!(w, r#"    <meta name="cover" content="{}" />"#, xml_escape(id))
+                .map_err(|_| EPubBook::zip_error("write", "content table cover line")));
         }
 
         if let Some(desc) = self.description.as_ref() {
+            let mut buf = vec![];
+            try!(self.write_content_type(desc, None, false, None, downloaded, &mut buf));
             try!(writeln!(w, r#"    <dc:description>"#).map_err(|_| EPubBook::zip_error("write", "content table description open line")));
-            try!(EPubBook::write_content_type(desc, &"description", false, w, verbose, verb_out));
+            try!(write!(w, "{}", xml_escape(&String::from_utf8_lossy(&buf))).map_err(|_| EPubBook::zip_error("write", "content table description")));
             try!(writeln!(w, r#"    </dc:description>"#).map_err(|_| EPubBook::zip_error("write", "content table description close line")));
         }
-        try!(writeln!(w, r#"    <dc:language>{}</dc:language>"#, self.language).map_err(|_| EPubBook::zip_error("write", "content table language line")));
+        try!(writeln!(w, r#"    <dc:language>{}</dc:language>"#, xml_escape(&self.language))
+            .map_err(|_| EPubBook::zip_error("write", "content table language line")));
 
         try!(writeln!(w, r#"  </metadata>"#).map_err(|_| EPubBook::zip_error("write", "content table metadata end")));
         try!(writeln!(w, r#"  <manifest>"#).map_err(|_| EPubBook::zip_error("write", "content table manifest start")));
-        try!(writeln!(w, r#"    <item href="toc.ncx" id="toc" media-type="application/x-dtbncx+xml"/>"#)
-            .map_err(|_| EPubBook::zip_error("write", "content table manifest toc line")));
+        if self.has_ncx() {
+            try!(writeln!(w, r#"    <item href="toc.ncx" id="toc" media-type="application/x-dtbncx+xml"/>"#)
+                .map_err(|_| EPubBook::zip_error("write", "content table manifest toc line")));
+        }
+        if v3 {
+            try!(writeln!(w, r#"    <item href="nav.xhtml" id="nav" media-type="application/xhtml+xml" properties="nav" />"#)
+                .map_err(|_| EPubBook::zip_error("write", "content table manifest nav line")));
+        }
 
+        let cover_image = self.cover.as_ref().and_then(|&(_, ref page, _)| self.image_page_image(page)).filter(|_| v3);
         let mut specified_ids = BTreeSet::new();
         for &(ref id, ref fname, _) in self.cover.iter().chain(self.content.iter()).chain(self.non_content.iter()) {
             if !specified_ids.contains(&id[..]) {
                 specified_ids.insert(&id[..]);
                 try!(writeln!(w,
-                              r#"    <item href="{}" id="{}" media-type="{}" />"#,
-                              fname.display(),
-                              id,
-                              EPubBook::guess_type(fname))
+                              r#"    <item href="{}" id="{}" media-type="{}"{} />"#,
+                              xml_escape(&fname.to_string_lossy()),
+                              xml_escape(id),
+                              self.media_type(fname),
+                              if cover_image == Some(fname) {
+                                  r#" properties="cover-image""#
+                              } else {
+                                  ""
+                              })
                     .map_err(|_| EPubBook::zip_error("write", "content table manifest content")));
             }
         }
 
         try!(writeln!(w, r#"  </manifest>"#).map_err(|_| EPubBook::zip_error("write", "content table manifest end")));
-        try!(writeln!(w, r#"  <spine toc="toc">"#).map_err(|_| EPubBook::zip_error("write", "content table spine start")));
+        try!(writeln!(w,
+                      "  <spine{}>",
+                      if self.has_ncx() {
+                          r#" toc="toc""#
+                      } else {
+                          ""
+                      })
+            .map_err(|_| EPubBook::zip_error("write", "content table spine start")));
 
         for &(ref id, _, _) in &self.content {
-            try!(writeln!(w, r#"    <itemref idref="{}" />"#, id).map_err(|_| EPubBook::zip_error("write", "content table spine content")));
+            try!(writeln!(w, r#"    <itemref idref="{}" />"#, xml_escape(id)).map_err(|_| EPubBook::zip_error("write", "content table spine content")));
         }
 
         try!(writeln!(w, r#"  </spine>"#).map_err(|_| EPubBook::zip_error("write", "content table spine end")));
         try!(writeln!(w, r#"  <guide>"#).map_err(|_| EPubBook::zip_error("write", "content table guide start")));
 
         if let Some(&(ref id, ref fname, _)) = self.cover.as_ref() {
-            try!(writeln!(w, r#"    <reference xmlns="http://www.idpf.org/2007/opf" href="{}" title="{}" type="cover" />"#, fname.display(), id)
+            try!(writeln!(w, r#"    <reference xmlns="http://www.idpf.org/2007/opf" href="{}" title="{}" type="cover" />"#,
+                          xml_escape(&fname.to_string_lossy()),
+                          xml_escape(id))
                 .map_err(|_| EPubBook::zip_error("write", "content table guide cover")));
         }
 
-        try!(writeln!(w, r#"    <reference href="toc.ncx" title="Table of Contents" type="toc" />"#)
+        try!(writeln!(w,
+                      r#"    <reference href="{}" title="Table of Contents" type="toc" />"#,
+                      if v3 { "nav.xhtml" } else { "toc.ncx" })
             .map_err(|_| EPubBook::zip_error("write", "content table guide toc")));
         try!(writeln!(w, r#"  </guide>"#).map_err(|_| EPubBook::zip_error("write", "content table guide end")));
         try!(writeln!(w, r#"</package>"#).map_err(|_| EPubBook::zip_error("write", "content table package end")));
@@ -478,105 +898,695 @@ impl EPubBook {
         Ok(())
     }
 
-    fn table_of_contents<W: Write, V: Write>(&self, string_toc: bool, w: &mut W, verbose: bool, verb_out: &mut V) -> Result<(), Error> {
+    /// Write the optional Dublin Core metadata and the series, the latter both as calibre metadata and, for ePub 3, as a collection.
+    fn write_extended_metadata<W: Write>(&self, v3: bool, w: &mut W) -> Result<(), Error> {
+        for &(element, value) in &[("publisher", &self.publisher), ("rights", &self.rights), ("source", &self.source)] {
+            if let Some(ref value) = *value {
+                try!(writeln!(w, "    <dc:{0}>{1}</dc:{0}>", element, xml_escape(value))
+                    .map_err(|_| EPubBook::zip_error("write", "content table metadata line")));
+            }
+        }
+        for &(element, values) in &[("subject", &self.subjects), ("relation", &self.relations)] {
+            for value in values {
+                try!(writeln!(w, "    <dc:{0}>{1}</dc:{0}>", element, xml_escape(value))
+                    .map_err(|_| EPubBook::zip_error("write", "content table metadata line")));
+            }
+        }
+
+        for (i, &(ref scheme, ref identifier)) in self.identifiers.iter().enumerate() {
+            if !v3 {
+                try!(writeln!(w,
+                              r#"    <dc:identifier opf:scheme="{}">{}</dc:identifier>"#,
+                              xml_escape(scheme),
+                              xml_escape(identifier))
+                    .map_err(|_| EPubBook::zip_error("write", "content table identifier line")));
+            } else if scheme == "ISBN" {
+                try!(writeln!(w, r#"    <dc:identifier id="identifier-{}">urn:isbn:{}</dc:identifier>"#, i + 1, xml_escape(identifier))
+                    .map_err(|_| EPubBook::zip_error("write", "content table identifier line")));
+            } else {
+                try!(writeln!(w, r#"    <dc:identifier id="identifier-{}">{}</dc:identifier>"#, i + 1, xml_escape(identifier))
+                    .map_err(|_| EPubBook::zip_error("write", "content table identifier line")));
+                try!(writeln!(w, r##"    <meta refines="#identifier-{}" property="identifier-type">{}</meta>"##, i + 1, xml_escape(scheme))
+                    .map_err(|_| EPubBook::zip_error("write", "content table identifier type line")));
+            }
+        }
+
+        if let Some((ref series, ref index)) = self.series {
+            try!(writeln!(w, r#"    <meta name="calibre:series" content="{}" />"#, xml_escape(series))
+                .map_err(|_| EPubBook::zip_error("write", "content table series line")));
+            if let Some(ref index) = *index {
+                try!(writeln!(w, r#"    <meta name="calibre:series_index" content="{}" />"#, xml_escape(index))
+                    .map_err(|_| EPubBook::zip_error("write", "content table series index line")));
+            }
+            if v3 {
+                try!(writeln!(w, r#"    <meta property="belongs-to-collection" id="series">{}</meta>"#, xml_escape(series))
+                    .map_err(|_| EPubBook::zip_error("write", "content table series line")));
+                try!(writeln!(w, r##"    <meta refines="#series" property="collection-type">series</meta>"##)
+                    .map_err(|_| EPubBook::zip_error("write", "content table series type line")));
+                if let Some(ref index) = *index {
+                    try!(writeln!(w, r##"    <meta refines="#series" property="group-position">{}</meta>"##, xml_escape(index))
+                        .map_err(|_| EPubBook::zip_error("write", "content table series index line")));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write a `dc:creator` or `dc:contributor`, with its role and name to sort by refining it for ePub 3
+    /// or as attributes otherwise.
+    fn write_creator<W: Write>(element: &str, idx: usize, &(ref name, ref file_as, ref role): &EPubCreator, v3: bool, w: &mut W) -> Result<(), Error> {
+        if v3 {
+            let id = format!("{}-{}", element, idx + 1);
+            try!(writeln!(w, r#"    <dc:{0} id="{1}">{2}</dc:{0}>"#, element, id, xml_escape(name))
+                .map_err(|_| EPubBook::zip_error("write", "content table creator line")));
+            try!(writeln!(w, r##"    <meta refines="#{}" property="role" scheme="marc:relators">{}</meta>"##, id, xml_escape(role))
+                .map_err(|_| EPubBook::zip_error("write", "content table creator role line")));
+            if let Some(ref file_as) = *file_as {
+                try!(writeln!(w, r##"    <meta refines="#{}" property="file-as">{}</meta>"##, id, xml_escape(file_as))
+                    .map_err(|_| EPubBook::zip_error("write", "content table creator sort name line")));
+            }
+        } else {
+            try!(writeln!(w,
+                          r#"    <dc:{0} opf:role="{1}"{2}>{3}</dc:{0}>"#,
+                          element,
+                          xml_escape(role),
+                          file_as.as_ref().map(|fa| format!(r#" opf:file-as="{}""#, xml_escape(fa))).unwrap_or_default(),
+                          xml_escape(name))
+                .map_err(|_| EPubBook::zip_error("write", "content table creator line")));
+        }
+        Ok(())
+    }
+
+    /// Get the (title, filename, level) TOC entries, with levels clamped to at most one deeper than the preceding entry's.
+    fn toc_entries<V: Write>(&self, string_toc: bool, downloaded: &BTreeMap<Url, (Vec<u8>, DownloadInfo)>, verbose: bool, verb_out: &mut V)
+                             -> Result<Vec<(String, &Path, usize)>, Error> {
+        let mut toc: Vec<(String, &Path, usize)> = vec![];
+        for &(_, ref fname, ref tp) in &self.content {
+            let title = match *tp {
+                EPubContentType::File(ref pb) => {
+                    find_title_ext(&mut try!(File::open(pb).map_err(|_| {
+                        Error::Io {
+                            desc: "Content",
+                            op: "open",
+                            more: None,
+                        }
+                    })))
+                }
+                EPubContentType::Network(ref u) => find_html_title_ext(&mut &try!(downloaded_content(downloaded, u)).0[..]),
+                EPubContentType::Raw(ref data) if string_toc => find_title_ext(&mut data.as_bytes()),
+                EPubContentType::MarkdownFile(ref pb) => {
+                    find_markdown_title_ext(&mut try!(File::open(pb).map_err(|_| {
+                        Error::Io {
+                            desc: "Markdown-Content",
+                            op: "open",
+                            more: None,
+                        }
+                    })))
+                }
+                EPubContentType::MarkdownRaw(ref data) if string_toc => find_markdown_title_ext(&mut data.as_bytes()),
+                _ => None,
+            };
+
+            if let Some((title, level)) = title {
+                let level = cmp::min(level, toc.last().map_or(1, |&(_, _, prev_level)| prev_level + 1));
+                if verbose {
+                    if level == 1 {
+                        let _ = writeln!(verb_out, r#"Found title "{}" for {}."#, title, fname.display());
+                    } else {
+                        let _ = writeln!(verb_out, r#"Found level {} title "{}" for {}."#, level, title, fname.display());
+                    }
+                }
+                toc.push((title, fname.as_path(), level));
+            }
+        }
+
+        Ok(toc)
+    }
+
+    fn table_of_contents<W: Write>(&self, toc: &[(String, &Path, usize)], w: &mut W) -> Result<(), Error> {
         try!(writeln!(w, r#"<?xml version='1.0' encoding='utf-8'?>"#).map_err(|_| EPubBook::zip_error("write", "toc xml start")));
-        try!(writeln!(w, r#"<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1" xml:lang="{}">"#, self.language)
+        try!(writeln!(w, r#"<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1" xml:lang="{}">"#, xml_escape(&self.language))
             .map_err(|_| EPubBook::zip_error("write", "toc ncx start")));
         try!(writeln!(w, r#"  <head>"#).map_err(|_| EPubBook::zip_error("write", "toc head start")));
-        try!(writeln!(w, r#"    <meta content="{}" name="dtb:uid"/>"#, self.uuid.to_hyphenated_ref()).map_err(|_| EPubBook::zip_error("write", "toc head uuid")));
-        try!(writeln!(w, r#"    <meta content="2" name="dtb:depth"/>"#).map_err(|_| EPubBook::zip_error("write", "toc head depth")));
+        try!(writeln!(w, r#"    <meta content="{}" name="dtb:uid"/>"#, self.uuid.to_hyphenated_ref())
+            .map_err(|_| EPubBook::zip_error("write", "toc head uuid")));
+        try!(writeln!(w,
+                      r#"    <meta content="{}" name="dtb:depth"/>"#,
+                      toc.iter().map(|&(_, _, level)| level).max().unwrap_or(1))
+            .map_err(|_| EPubBook::zip_error("write", "toc head depth")));
         try!(writeln!(w, r#"  </head>"#).map_err(|_| EPubBook::zip_error("write", "toc head end")));
         try!(writeln!(w, r#"  <docTitle>"#).map_err(|_| EPubBook::zip_error("write", "toc doc title start")));
-        try!(writeln!(w, r#"    <text>{}</text>"#, self.name).map_err(|_| EPubBook::zip_error("write", "toc doc title")));
+        try!(writeln!(w, r#"    <text>{}</text>"#, xml_escape(&self.name)).map_err(|_| EPubBook::zip_error("write", "toc doc title")));
         try!(writeln!(w, r#"  </docTitle>"#).map_err(|_| EPubBook::zip_error("write", "toc doc title end")));
         try!(writeln!(w, r#"  <navMap>"#).map_err(|_| EPubBook::zip_error("write", "toc navmap start")));
 
-        {
-            let mut titles = 0;
-            let mut insert_toc = |title, fname: &Path| {
-                titles += 1;
+        for (i, &(ref title, fname, level)) in toc.iter().enumerate() {
+            let indent = "  ".repeat(level + 1);
+            try!(writeln!(w, r#"{}<navPoint id="navpoint-{1}" playOrder="{1}">"#, indent, i + 1)
+                .map_err(|_| EPubBook::zip_error("write", "toc navmap point start")));
+            try!(writeln!(w, r#"{}  <navLabel>"#, indent).map_err(|_| EPubBook::zip_error("write", "toc navmap label start")));
+            try!(writeln!(w, r#"{}    <text>{}</text>"#, indent, xml_escape(title)).map_err(|_| EPubBook::zip_error("write", "toc navmap label text")));
+            try!(writeln!(w, r#"{}  </navLabel>"#, indent).map_err(|_| EPubBook::zip_error("write", "toc navmap label end")));
+            try!(writeln!(w, r#"{}  <content src="{}"/>"#, indent, xml_escape(&fname.to_string_lossy()))
+                .map_err(|_| EPubBook::zip_error("write", "toc navmap point content")));
 
-                try!(writeln!(w, r#"    <navPoint id="{}" playOrder="{}">"#, Uuid::new_v4().to_hyphenated(), titles)
-                    .map_err(|_| EPubBook::zip_error("write", "toc navmap point start")));
-                try!(writeln!(w, r#"      <navLabel>"#).map_err(|_| EPubBook::zip_error("write", "toc navmap label start")));
-                try!(writeln!(w, r#"        <text>{}</text>"#, title).map_err(|_| EPubBook::zip_error("write", "toc navmap label text")));
-                try!(writeln!(w, r#"      </navLabel>"#).map_err(|_| EPubBook::zip_error("write", "toc navmap label end")));
-                try!(writeln!(w, r#"      <content src="{}"/>"#, fname.display()).map_err(|_| EPubBook::zip_error("write", "toc navmap point content")));
-                try!(writeln!(w, r#"    </navPoint>"#).map_err(|_| EPubBook::zip_error("write", "toc navmap point end")));
+            // Close this navPoint, unless the next one is nested in it, as well as all the ones it's nested in, up to the next one's level
+            let next_level = toc.get(i + 1).map_or(1, |&(_, _, level)| level);
+            for closed_level in (next_level..level + 1).rev() {
+                try!(writeln!(w, r#"{}</navPoint>"#, "  ".repeat(closed_level + 1)).map_err(|_| EPubBook::zip_error("write", "toc navmap point end")));
+            }
+        }
 
-                if verbose {
-                    let _ = writeln!(verb_out, r#"Found title "{}" for {}."#, title, fname.display());
-                }
+        try!(writeln!(w, r#"  </navMap>"#).map_err(|_| EPubBook::zip_error("write", "toc navmap end")));
+        try!(writeln!(w, r#"</ncx>"#).map_err(|_| EPubBook::zip_error("write", "toc ncx end")));
 
-                Ok(())
-            };
-            for &(_, ref fname, ref tp) in &self.content {
-                match *tp {
-                    EPubContentType::File(ref pb) => {
-                        if let Some(title) = find_title(&mut try!(File::open(pb).map_err(|_| {
-                            Error::Io {
-                                desc: "Content",
-                                op: "open",
-                                more: None,
-                            }
-                        }))) {
-                            insert_toc(title, fname)?;
-                        }
-                    }
-                    EPubContentType::Raw(ref data) if string_toc => {
-                        if let Some(title) = find_title(&mut data.as_bytes()) {
-                            insert_toc(title, fname)?;
-                        }
-                    }
-                    _ => {}
+        Ok(())
+    }
+
+    fn navigation_document<W: Write>(&self, toc: &[(String, &Path, usize)], w: &mut W) -> Result<(), Error> {
+        try!(writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#).map_err(|_| EPubBook::zip_error("write", "nav xml start")));
+        try!(writeln!(w,
+                      r#"<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" xml:lang="{0}" lang="{0}">"#,
+                      xml_escape(&self.language))
+            .map_err(|_| EPubBook::zip_error("write", "nav html start")));
+        try!(writeln!(w, r#"  <head>"#).map_err(|_| EPubBook::zip_error("write", "nav head start")));
+        try!(writeln!(w, r#"    <title>{}</title>"#, xml_escape(&self.name)).map_err(|_| EPubBook::zip_error("write", "nav head title")));
+        try!(writeln!(w, r#"  </head>"#).map_err(|_| EPubBook::zip_error("write", "nav head end")));
+        try!(writeln!(w, r#"  <body>"#).map_err(|_| EPubBook::zip_error("write", "nav body start")));
+        try!(writeln!(w, r#"    <nav epub:type="toc" id="toc">"#).map_err(|_| EPubBook::zip_error("write", "nav toc start")));
+        try!(writeln!(w, r#"      <h1>Table of Contents</h1>"#).map_err(|_| EPubBook::zip_error("write", "nav toc heading")));
+
+        // The list mustn't be empty, so fall back to pointing at the start of the book
+        let fallback;
+        let toc = if toc.is_empty() && !self.content.is_empty() {
+            fallback = [(self.name.clone(), self.content[0].1.as_path(), 1)];
+            &fallback[..]
+        } else {
+            toc
+        };
+        // Nested lists go inside the list item they're nested under, so that one is only closed after them
+        let mut depth = 0;
+        for (i, &(ref title, fname, level)) in toc.iter().enumerate() {
+            if level > depth {
+                try!(writeln!(w, r#"{}<ol>"#, " ".repeat(4 * level + 2)).map_err(|_| EPubBook::zip_error("write", "nav toc list start")));
+                depth = level;
+            }
+
+            let next_level = toc.get(i + 1).map_or(0, |&(_, _, level)| level);
+            try!(writeln!(w,
+                          r#"{}<li><a href="{}">{}</a>{}"#,
+                          " ".repeat(4 * level + 4),
+                          xml_escape(&fname.to_string_lossy()),
+                          xml_escape(title),
+                          if next_level > level { "" } else { "</li>" })
+                .map_err(|_| EPubBook::zip_error("write", "nav toc list entry")));
+
+            while depth > next_level {
+                try!(writeln!(w, r#"{}</ol>"#, " ".repeat(4 * depth + 2)).map_err(|_| EPubBook::zip_error("write", "nav toc list end")));
+                depth -= 1;
+                if depth > 0 {
+                    try!(writeln!(w, r#"{}</li>"#, " ".repeat(4 * depth + 4)).map_err(|_| EPubBook::zip_error("write", "nav toc list entry end")));
                 }
             }
         }
 
-        try!(writeln!(w, r#"  </navMap>"#).map_err(|_| EPubBook::zip_error("write", "toc navmap end")));
-        try!(writeln!(w, r#"</ncx>"#).map_err(|_| EPubBook::zip_error("write", "toc ncx end")));
+        try!(writeln!(w, r#"    </nav>"#).map_err(|_| EPubBook::zip_error("write", "nav toc end")));
+        try!(writeln!(w, r#"  </body>"#).map_err(|_| EPubBook::zip_error("write", "nav body end")));
+        try!(writeln!(w, r#"</html>"#).map_err(|_| EPubBook::zip_error("write", "nav html end")));
 
         Ok(())
     }
 
-    fn write_content<W: Write + Seek, V: Write>(&self, w: &mut ZipWriter<W>, verbose: bool, verb_out: &mut V) -> Result<(), Error> {
+    fn write_content<W: Write + Seek>(&self, modified: &DateTime<Utc>, toc: &[(String, &Path, usize)], downloaded: &BTreeMap<Url, (Vec<u8>, DownloadInfo)>,
+                                      w: &mut ZipWriter<W>)
+                                      -> Result<(), Error> {
         let mut added_filenames = BTreeSet::new();
         for &(_, ref fname, ref tp) in self.cover.iter().chain(self.content.iter()).chain(self.non_content.iter()) {
             if !added_filenames.contains(fname.to_str().unwrap()) {
                 added_filenames.insert(fname.to_str().unwrap());
-                try!(w.start_file(fname.to_str().unwrap(), FileOptions::default()).map_err(|_| EPubBook::zip_error("create", "table of contents")));
-                try!(EPubBook::write_content_type(tp, &fname.display(), true, w, verbose, verb_out));
+                let compression = if EPubBook::is_media(&self.media_type(fname)) {
+                    self.media_compression
+                } else {
+                    self.compression
+                };
+                try!(w.start_file(fname.to_str().unwrap(), EPubBook::file_options(compression, modified))
+                    .map_err(|_| EPubBook::zip_error("create", "table of contents")));
+
+                let image_page;
+                let tp = match self.image_page_image(fname) {
+                    Some(image) => {
+                        image_page = EPubContentType::Raw(EPubBook::image_page(image, self.version));
+                        &image_page
+                    }
+                    None => tp,
+                };
+                let title = toc.iter().find(|&&(_, toc_fname, _)| toc_fname == fname.as_path()).map_or(&self.name, |&(ref title, _, _)| title);
+                // XHTML5 requires a title, which wrapped strings don't otherwise have
+                let string_title = Some(&title[..]).filter(|_| self.version == EPubVersion::V3);
+                if self.normalise_html && self.media_type(fname) == "application/xhtml+xml" {
+                    let mut buf = vec![];
+                    try!(self.write_content_type(tp, Some(fname), true, string_title, downloaded, &mut buf));
+                    try!(w.write_all(html_to_xhtml(&String::from_utf8_lossy(&buf), title).as_bytes())
+                        .map_err(|_| EPubBook::zip_error("write", "XHTML content")));
+                } else {
+                    try!(self.write_content_type(tp, Some(fname), true, string_title, downloaded, w));
+                }
             }
         }
 
         Ok(())
     }
 
-    fn write_content_type<F: Display, W: Write, V: Write>(whom: &EPubContentType, fname: &F, wrap_string: bool, w: &mut W, verbose: bool, verb_out: &mut V)
-                                                          -> Result<(), Error> {
+    /// References in `File`, `MarkdownFile` and `Network` content are rewritten relative to `packed_name`, if specified.
+    ///
+    /// `Network` content is taken from `downloaded`, which must contain it.
+    ///
+    /// Wrapped strings get `title` as their title, if specified.
+    fn write_content_type<W: Write>(&self, whom: &EPubContentType, packed_name: Option<&PathBuf>, wrap_string: bool, title: Option<&str>,
+                                    downloaded: &BTreeMap<Url, (Vec<u8>, DownloadInfo)>, w: &mut W)
+                                    -> Result<(), Error> {
         match *whom {
             EPubContentType::File(ref pb) => {
-                try!(io::copy(&mut try!(File::open(pb).map_err(|_| EPubBook::zip_error("open", "Content file"))), w)
-                    .map_err(|_| EPubBook::zip_error("write", "Content data")));
+                let mut f = try!(File::open(pb).map_err(|_| EPubBook::zip_error("open", "Content file")));
+                match packed_name.filter(|p| !self.packed_names.is_empty() && EPubBook::may_reference(&self.media_type(p))) {
+                    Some(packed_name) => {
+                        let mut buf = vec![];
+                        try!(f.read_to_end(&mut buf).map_err(|_| EPubBook::zip_error("read", "Content file")));
+                        // Leave files that aren't UTF-8 be, since the references can't be rewritten without changing the encoding
+                        let buf = match String::from_utf8(buf) {
+                            Ok(content) => self.rewrite_references(pb, packed_name, &content).into_bytes(),
+                            Err(e) => e.into_bytes(),
+                        };
+                        try!(w.write_all(&buf).map_err(|_| EPubBook::zip_error("write", "Content data")));
+                    }
+                    None => {
+                        try!(io::copy(&mut f, w).map_err(|_| EPubBook::zip_error("write", "Content data")));
+                    }
+                }
             }
             EPubContentType::Network(ref u) => {
-                if verbose {
-                    let _ = writeln!(verb_out, "Downloading {} to {}.", u, fname);
+                let data = &try!(downloaded_content(downloaded, u)).0;
+                // Leave content that isn't UTF-8 be, like with files
+                match packed_name.filter(|p| EPubBook::may_reference(&self.media_type(p))).and_then(|p| str::from_utf8(data).ok().map(|c| (p, c))) {
+                    Some((packed_name, content)) => {
+                        try!(w.write_all(self.rewrite_network_references(u, packed_name, content).as_bytes())
+                            .map_err(|_| EPubBook::zip_error("write", "network content")))
+                    }
+                    None => try!(w.write_all(data).map_err(|_| EPubBook::zip_error("write", "network content"))),
                 }
-                try!(download_to(w, u));
             }
-            EPubContentType::Raw(ref s) => {
-                if wrap_string {
-                    try!(write_string_content(w, s));
-                } else {
-                    try!(writeln!(w, "{}", s).map_err(|_| EPubBook::zip_error("write", "string content")));
+            EPubContentType::Raw(ref s) => try!(EPubBook::write_string(s, wrap_string, title, w)),
+            EPubContentType::MarkdownFile(ref pb) => {
+                let mut md = String::new();
+                try!(try!(File::open(pb).map_err(|_| EPubBook::zip_error("open", "Markdown-Content file")))
+                    .read_to_string(&mut md)
+                    .map_err(|_| EPubBook::zip_error("read", "Markdown-Content file")));
+                let mut content = render_markdown(&md);
+                if let Some(packed_name) = packed_name {
+                    content = self.rewrite_references(pb, packed_name, &content);
                 }
+                try!(EPubBook::write_string(&content, wrap_string, title, w));
             }
+            EPubContentType::MarkdownRaw(ref s) => try!(EPubBook::write_string(&render_markdown(s), wrap_string, title, w)),
         }
 
         Ok(())
     }
 
+    fn write_string<W: Write>(s: &str, wrap_string: bool, title: Option<&str>, w: &mut W) -> Result<(), Error> {
+        if wrap_string {
+            write_string_content_ext(w, s, title)
+        } else {
+            writeln!(w, "{}", s).map_err(|_| EPubBook::zip_error("write", "string content"))
+        }
+    }
+
+    /// Download all network content, with up to `download.jobs` downloads running at once,
+    /// and then, if `network_images` is set, the images referenced from `Network-Content` pages.
+    ///
+    /// Content pinned in `network_hashes` is verified against its hash.
+    ///
+    /// All failures are reported to the output stream, if there's more than one, and the first one is returned.
+    fn download_network_content<V: Write>(&self, verbose: bool, verb_out: &mut V) -> Result<BTreeMap<Url, (Vec<u8>, DownloadInfo)>, Error> {
+        let (mut downloaded, mut errors) = self.download_urls(self.network_urls(), verbose, verb_out);
+        for (url, &(ref data, _)) in &downloaded {
+            if let Some(expected) = self.network_hashes.get(url) {
+                let actual = sha256_hex(data);
+                if actual == *expected {
+                    if verbose {
+                        let _ = writeln!(verb_out, "Verified {}.", url);
+                    }
+                } else {
+                    errors.insert(url.clone(),
+                                  Error::HashMismatch {
+                                      url: url.to_string(),
+                                      expected: expected.clone(),
+                                      actual: actual,
+                                  });
+                }
+            }
+        }
+        try!(EPubBook::first_download_error(errors, verb_out));
+
+        if self.network_images {
+            let mut images = BTreeMap::new();
+            for &(_, _, ref tp) in &self.content {
+                if let EPubContentType::Network(ref page) = *tp {
+                    for image in network_image_references(page, &String::from_utf8_lossy(&try!(downloaded_content(&downloaded, page)).0)) {
+                        if !downloaded.contains_key(&image) && !images.contains_key(&image) {
+                            if verbose {
+                                let _ = writeln!(verb_out, "Found {} referenced from {}.", image, page);
+                            }
+                            images.insert(image, page.clone());
+                        }
+                    }
+                }
+            }
+
+            let (images_downloaded, errors) = self.download_urls(images.keys().cloned().collect(), verbose, verb_out);
+            for (url, err) in errors {
+                let _ = write!(verb_out, "Warning: couldn't download {} referenced from {}, leaving as-is: ", url, images[&url]);
+                err.print_error(verb_out);
+            }
+            downloaded.extend(images_downloaded);
+        }
+
+        Ok(downloaded)
+    }
+
+    /// Download all network content and get the SHA-256 hashes thereof, as lowercase hex, by URL, optionally logging verbose output.
+    ///
+    /// The hashes aren't checked against `network_hashes`, so that they can be used to update the pins.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate gen_epub_book;
+    /// # extern crate chrono;
+    /// # fn main() {
+    /// # use gen_epub_book::ops::{BookElement, EPubBook};
+    /// # use chrono::DateTime;
+    /// let book = EPubBook::from_elements(vec![
+    ///     BookElement::Name("Hash printing demonstration".to_string()),
+    ///     BookElement::StringContent("Chapter 1 text".to_string()),
+    ///     BookElement::Author("nabijaczleweli".to_string(), None),
+    ///     BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
+    ///     BookElement::Language("en-GB".to_string()),
+    /// ]).unwrap();
+    ///
+    /// assert!(book.hash_network_content(false, &mut vec![]).unwrap().is_empty());
+    /// # }
+    /// ```
+    pub fn hash_network_content<V: Write>(&self, verbose: bool, verb_out: &mut V) -> Result<BTreeMap<Url, String>, Error> {
+        let (downloaded, errors) = self.download_urls(self.network_urls(), verbose, verb_out);
+        try!(EPubBook::first_download_error(errors, verb_out));
+
+        Ok(downloaded.into_iter().map(|(url, (data, _))| (url, sha256_hex(&data))).collect())
+    }
+
+    /// Get the URLs of all network content.
+    fn network_urls(&self) -> BTreeSet<Url> {
+        self.description
+            .iter()
+            .chain(self.cover.iter().chain(self.content.iter()).chain(self.non_content.iter()).map(|&(_, _, ref tp)| tp))
+            .flat_map(|tp| match *tp {
+                EPubContentType::Network(ref u) => Some(u.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Report all download failures to the output stream, if there's more than one, and return the first one.
+    fn first_download_error<V: Write>(errors: BTreeMap<Url, Error>, verb_out: &mut V) -> Result<(), Error> {
+        if errors.len() > 1 {
+            for (url, err) in &errors {
+                let _ = write!(verb_out, "Downloading {} failed: ", url);
+                err.print_error(verb_out);
+            }
+        }
+        match errors.into_iter().next() {
+            Some((_, err)) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Download the specified URLs, with up to `download.jobs` downloads running at once,
+    /// returning the downloaded content and the failures.
+    fn download_urls<V: Write>(&self, urls: BTreeSet<Url>, verbose: bool, verb_out: &mut V)
+                               -> (BTreeMap<Url, (Vec<u8>, DownloadInfo)>, BTreeMap<Url, Error>) {
+        let mut downloaded = BTreeMap::new();
+        let mut errors = BTreeMap::new();
+        if urls.is_empty() {
+            return (downloaded, errors);
+        }
+
+        // Share one client between all downloads, so that connections are reused, but only build it if anything's downloaded over HTTP
+        let client = if !self.download.offline && urls.iter().any(|u| u.scheme() != "file" && u.scheme() != "data") {
+            match self.download.http_client() {
+                Ok(client) => Some(client),
+                Err(err) => {
+                    for url in urls.into_iter().filter(|u| u.scheme() != "file" && u.scheme() != "data") {
+                        errors.insert(url, err.clone());
+                    }
+                    return (downloaded, errors);
+                }
+            }
+        } else {
+            None
+        };
+
+        let jobs = cmp::min(cmp::max(self.download.jobs, 1), urls.len());
+        if verbose {
+            let _ = writeln!(verb_out, "Downloading {} network files, {} at a time.", urls.len(), jobs);
+        }
+
+        // Workers take URLs off the back of the queue until it's empty
+        let queue = Arc::new(Mutex::new(urls.iter().rev().cloned().collect::<Vec<_>>()));
+        let (results_send, results) = mpsc::channel();
+        let workers: Vec<_> = (0..jobs)
+            .map(|_| {
+                let queue = queue.clone();
+                let results_send = results_send.clone();
+                let config = self.download.clone();
+                let client = client.clone();
+                thread::spawn(move || loop {
+                    // Don't hold the lock while downloading, lest the downloads be sequential
+                    let next = queue.lock().unwrap().pop();
+                    let url = match next {
+                        Some(url) => url,
+                        None => break,
+                    };
+
+                    let mut buf = vec![];
+                    let result = download_to_with_client(&mut buf, &url, &config, client.as_ref()).map(|info| (buf, info));
+                    if results_send.send((url, result)).is_err() {
+                        break;
+                    }
+                })
+            })
+            .collect();
+        drop(results_send);
+
+        for (url, result) in results {
+            match result {
+                Ok(data) => {
+                    if verbose {
+                        let _ = writeln!(verb_out, "Downloaded {} ({} bytes).", url, data.0.len());
+                    }
+                    downloaded.insert(url, data);
+                }
+                Err(err) => {
+                    errors.insert(url, err);
+                }
+            }
+        }
+        for worker in workers {
+            let _ = worker.join();
+        }
+
+        // A worker that panicked took the URL it was downloading, and maybe ones still queued, with it
+        for url in urls {
+            if !downloaded.contains_key(&url) && !errors.contains_key(&url) {
+                errors.insert(url,
+                              Error::Io {
+                                  desc: "network content",
+                                  op: "download",
+                                  more: Some("download thread died"),
+                              });
+            }
+        }
+
+        (downloaded, errors)
+    }
+
+    /// Get the book with its network content packed and listed as described in `write_zip_ext()`.
+    fn with_network_names<'s, V: Write>(&'s self, downloaded: &BTreeMap<Url, (Vec<u8>, DownloadInfo)>, verbose: bool, verb_out: &mut V)
+                                        -> Result<Cow<'s, EPubBook>, Error> {
+        if downloaded.is_empty() {
+            return Ok(Cow::Borrowed(self));
+        }
+
+        let mut book = self.clone();
+
+        // Include the images pulled in from network pages
+        let known_urls: BTreeSet<_> = book.description
+            .iter()
+            .chain(book.cover.iter().chain(book.content.iter()).chain(book.non_content.iter()).map(|&(_, _, ref tp)| tp))
+            .flat_map(|tp| match *tp {
+                EPubContentType::Network(ref u) => Some(u.clone()),
+                _ => None,
+            })
+            .collect();
+        for (u, &(_, ref info)) in downloaded {
+            if !known_urls.contains(u) &&
+               server_media_type(info).unwrap_or_else(|| EPubBook::guess_type(&PathBuf::from(url_filename(u))).to_string()).starts_with("image/") {
+                book.non_content.push((xhtml_url_id(u), PathBuf::from(url_filename(u)), EPubContentType::Network(u.clone())));
+            }
+        }
+
+        // Keep clear of the documents and manifest IDs generated by write_zip_ext()
+        let mut taken_ids: BTreeSet<_> = ["toc", "nav"].iter().map(|id| id.to_string()).collect();
+        let mut taken_names: BTreeSet<_> = ["mimetype", "META-INF", "content.opf", "toc.ncx", "nav.xhtml"].iter().map(PathBuf::from).collect();
+        for &(ref id, ref fname, ref tp) in book.cover.iter().chain(book.content.iter()).chain(book.non_content.iter()) {
+            match *tp {
+                EPubContentType::Network(_) => {}
+                _ => {
+                    taken_ids.insert(id.clone());
+                    taken_names.insert(fname.clone());
+                }
+            }
+        }
+
+        let mut packed = BTreeMap::new();
+        for &mut (ref mut id, ref mut fname, ref tp) in book.content.iter_mut().chain(book.non_content.iter_mut()) {
+            if let EPubContentType::Network(ref u) = *tp {
+                let info = &try!(downloaded_content(downloaded, u)).1;
+                let media_type = server_media_type(info);
+
+                let &mut (ref new_id, ref new_fname) = packed.entry(u.clone()).or_insert_with(|| {
+                    let mut name = PathBuf::from(info.filename.clone().unwrap_or_else(|| url_filename(u)));
+                    if let Some(ext) = name.extension().map_or_else(|| info.content_type.as_ref().and_then(|ct| extension_for(ct)), |_| None) {
+                        name.set_extension(ext);
+                    }
+                    let stem = name.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+                    let ext = name.extension().map_or_else(String::new, |e| format!(".{}", e.to_string_lossy()));
+
+                    let new_id = take_unique(&mut taken_ids, |n| if n == 1 { id.clone() } else { format!("{}-{}", id, n) });
+                    let new_fname = take_unique(&mut taken_names, |n| if n == 1 {
+                        name.clone()
+                    } else {
+                        PathBuf::from(format!("{}-{}{}", stem, n, ext))
+                    });
+                    (new_id, new_fname)
+                });
+
+                if verbose && new_fname != fname {
+                    let _ = writeln!(verb_out, "Packing {} as {}.", u, new_fname.display());
+                }
+                if let Some(media_type) = media_type {
+                    book.media_types.insert(new_fname.clone(), media_type);
+                }
+                book.packed_urls.insert(u.clone(), new_fname.clone());
+                *id = new_id.clone();
+                *fname = new_fname.clone();
+            }
+        }
+
+        Ok(Cow::Owned(book))
+    }
+
+    /// Rewrite the references (links included) in the content downloaded from `url` to point at the network content packed in the book,
+    /// relative to the content's `packed_name`, and make the other relative references absolute.
+    fn rewrite_network_references(&self, url: &Url, packed_name: &PathBuf, content: &str) -> String {
+        map_content_references(content, &self.media_type(packed_name), |r, _| {
+            if r.starts_with('#') {
+                return None;
+            }
+
+            let target = match url.join(r) {
+                Ok(target) => target,
+                Err(_) => return None,
+            };
+            let mut resource = target.clone();
+            resource.set_fragment(None);
+            match self.packed_urls.get(&resource) {
+                Some(packed) => Some(format!("{}{}", relative_href(packed_name, packed), target.fragment().map_or_else(String::new, |f| format!("#{}", f)))),
+                None if Url::parse(r).is_err() => Some(target.into_string()),
+                None => None,
+            }
+        })
+    }
+
+    /// Find the file referenced by `path` from the file at `relpath`, first in the referencing file's include directory,
+    /// returning the path of the referenced file relative to the include directory it was found in, if any.
+    fn find_reference<'r>(relroots: &'r [IncludeDirectory], root: &'r IncludeDirectory, relpath: &Path, path: &Path)
+                          -> (PathBuf, Option<&'r IncludeDirectory>) {
+        let reference = normalise_lexically(&relpath.parent().unwrap_or(Path::new("")).join(path));
+        let reference_root = if root.resolve(&reference).is_some() {
+            Some(root)
+        } else {
+            find_file(&reference, relroots)
+        };
+        (reference, reference_root)
+    }
+
+    /// Rewrite the references (links included) in the content of the file at `abspath` to point at the files packed in the book,
+    /// relative to the content's `packed_name`.
+    fn rewrite_references(&self, abspath: &Path, packed_name: &PathBuf, content: &str) -> String {
+        let dir = abspath.parent().unwrap_or(Path::new(""));
+        map_content_references(content, &self.media_type(packed_name), |r, _| {
+            local_reference(r).and_then(|(path, suffix)| {
+                self.packed_names.get(&normalise_lexically(&dir.join(path))).map(|target| format!("{}{}", relative_href(packed_name, target), suffix))
+            })
+        })
+    }
+
+    fn read_content(path: &Path, markdown: bool) -> Result<String, Error> {
+        let mut buf = vec![];
+        try!(try!(File::open(path).map_err(|_| {
+                Error::Io {
+                    desc: "Content",
+                    op: "open",
+                    more: None,
+                }
+            }))
+            .read_to_end(&mut buf)
+            .map_err(|_| {
+                Error::Io {
+                    desc: "Content",
+                    op: "read",
+                    more: None,
+                }
+            }));
+
+        let content = String::from_utf8_lossy(&buf);
+        Ok(if markdown {
+            render_markdown(&content)
+        } else {
+            content.into_owned()
+        })
+    }
+
+    /// Whether files of the specified media type can reference other files, i.e. are (X)HTML or CSS.
+    fn may_reference(tp: &str) -> bool {
+        tp == "application/xhtml+xml" || tp == "text/css"
+    }
+
+    fn is_media(tp: &str) -> bool {
+        let (top, sub) = tp.split_at(tp.find('/').unwrap_or(tp.len()));
+        match top {
+            "image" => !sub.starts_with("/svg"),
+            "audio" | "video" | "font" => true,
+            "application" => sub.starts_with("/font") || sub.ends_with("opentype"),
+            _ => false,
+        }
+    }
+
+    fn media_type(&self, fname: &PathBuf) -> String {
+        self.media_types.get(fname).cloned().unwrap_or_else(|| EPubBook::guess_type(fname).to_string())
+    }
+
     fn guess_type(fname: &PathBuf) -> Mime {
         lazy_static! {
             static ref TEXT_PLAIN: Mime = "text/plain".parse().unwrap();
@@ -591,3 +1601,147 @@ impl EPubBook {
         })
     }
 }
+
+
+impl Default for EPubVersion {
+    fn default() -> EPubVersion {
+        EPubVersion::V2
+    }
+}
+
+impl FromStr for EPubVersion {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<EPubVersion, Error> {
+        match s {
+            "2" => Ok(EPubVersion::V2),
+            "3" => Ok(EPubVersion::V3),
+            _ => {
+                Err(Error::Parse {
+                    tp: "ePub version",
+                    wher: "book",
+                    more: Some("neither 2 nor 3"),
+                })
+            }
+        }
+    }
+}
+
+impl fmt::Display for EPubVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EPubVersion::V2 => write!(f, "2"),
+            EPubVersion::V3 => write!(f, "3"),
+        }
+    }
+}
+
+/// Resolve `.`s and `..`s in the path without touching the filesystem.
+fn normalise_lexically(p: &Path) -> PathBuf {
+    let mut ret = PathBuf::new();
+    for component in p.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if ret.file_name().is_some() => {
+                ret.pop();
+            }
+            component => ret.push(component.as_os_str()),
+        }
+    }
+    ret
+}
+
+/// Split a reference to a local file into its percent-decoded path and its query and/or fragment.
+///
+/// Returns `None` for URLs, absolute paths and same-document references.
+fn local_reference(r: &str) -> Option<(PathBuf, &str)> {
+    if r.starts_with('/') || r.find(':').map_or(false, |colon| r.find(|c| c == '/' || c == '?' || c == '#').map_or(true, |sep| colon < sep)) {
+        return None;
+    }
+
+    let split = r.find(|c| c == '?' || c == '#').unwrap_or(r.len());
+    let path = percent_decode(r[..split].as_bytes()).decode_utf8_lossy();
+    if path.is_empty() {
+        None
+    } else {
+        Some((PathBuf::from(path.into_owned()), &r[split..]))
+    }
+}
+
+/// Get the media type the server reported for downloaded content, unless it's the catch-all `application/octet-stream`,
+/// with `text/html` as `application/xhtml+xml`, like `EPubBook::guess_type()`.
+fn server_media_type(info: &DownloadInfo) -> Option<String> {
+    info.content_type.as_ref().filter(|ct| *ct != "application/octet-stream").map(|ct| if ct == "text/html" {
+        "application/xhtml+xml".to_string()
+    } else {
+        ct.clone()
+    })
+}
+
+/// Get the downloaded content of the specified URL, or an error if it wasn't downloaded.
+fn downloaded_content<'d>(downloaded: &'d BTreeMap<Url, (Vec<u8>, DownloadInfo)>, url: &Url) -> Result<&'d (Vec<u8>, DownloadInfo), Error> {
+    downloaded.get(url).ok_or_else(|| {
+        Error::Io {
+            desc: "network content",
+            op: "find",
+            more: Some("not downloaded"),
+        }
+    })
+}
+
+/// Map the references in content of the specified media type with `util::map_css_references()` for CSS,
+/// or `util::map_references()` otherwise.
+fn map_content_references<F: FnMut(&str, bool) -> Option<String>>(content: &str, media_type: &str, f: F) -> String {
+    if media_type == "text/css" {
+        map_css_references(content, f)
+    } else {
+        map_references(content, f)
+    }
+}
+
+/// Get the remote images referenced from the network page at `page`, i.e. the HTTP(S) non-link references
+/// that don't look like anything but images.
+fn network_image_references(page: &Url, content: &str) -> Vec<Url> {
+    let mut images = vec![];
+    map_references(content, |r, link| {
+        if !link {
+            if let Ok(mut image) = page.join(r) {
+                image.set_fragment(None);
+                if (image.scheme() == "http" || image.scheme() == "https" || (image.scheme() == "file" && page.scheme() == "file")) &&
+                   guess_mime_type_opt(url_filename(&image)).map_or(true, |tp| tp.to_string().starts_with("image/")) {
+                    images.push(image);
+                }
+            }
+        }
+        None
+    });
+    images
+}
+
+/// Get the extension for files of the specified media type, preferring the subtype itself, as with `image/png` and `.png`.
+fn extension_for(media_type: &str) -> Option<&'static str> {
+    get_mime_extensions_str(media_type).and_then(|exts| {
+        let subtype = &media_type[media_type.find('/').map_or(0, |i| i + 1)..];
+        exts.iter().find(|&&ext| ext == subtype).or_else(|| exts.first()).cloned()
+    })
+}
+
+/// Get the first of `candidate(1)`, `candidate(2)`, &c. that isn't `taken`, and take it.
+fn take_unique<T: Ord + Clone, F: Fn(usize) -> T>(taken: &mut BTreeSet<T>, candidate: F) -> T {
+    let unique = (1..).map(candidate).find(|c| !taken.contains(c)).unwrap();
+    taken.insert(unique.clone());
+    unique
+}
+
+/// Get a percent-encoded relative URL pointing at the `to` packed name from a file with the `from` packed name.
+fn relative_href(from: &Path, to: &Path) -> String {
+    let from = from.to_string_lossy();
+    let to = to.to_string_lossy();
+
+    let mut from_dirs: Vec<_> = from.split('/').collect();
+    from_dirs.pop();
+    let to: Vec<_> = to.split('/').collect();
+    let common = from_dirs.iter().zip(&to[..to.len() - 1]).take_while(|&(f, t)| f == t).count();
+
+    utf8_percent_encode(&("../".repeat(from_dirs.len() - common) + &to[common..].join("/")), DEFAULT_ENCODE_SET).to_string()
+}
@@ -0,0 +1,220 @@
+use self::super::super::util::MIME_TYPE;
+use std::collections::{BTreeMap, BTreeSet};
+use url::percent_encoding::percent_decode;
+use zip::{CompressionMethod, ZipArchive};
+use xml::reader::{EventReader, XmlEvent};
+use std::io::{Read, Seek, Write};
+use self::super::super::Error;
+
+
+/// Check whether the ePub in the specified stream is structurally valid.
+///
+/// The rules checked are the ones `EPubBook::write_zip()` is responsible for, i.e.:
+///
+///   * `mimetype` is the first entry, is uncompressed, has no extra field and contains `application/epub+zip`,
+///   * `META-INF/container.xml` exists and points at an existing package document,
+///   * every manifest `item` is present in the archive,
+///   * every spine `itemref` (and the spine's `toc`) refers to a manifest `item`,
+///   * IDs are unique within each document,
+///   * all XHTML documents, the package document and the NCX are well-formed XML, and
+///   * all NCX and navigation document targets exist.
+///
+/// Returns the first rule broken as `Error::InvalidEPub`.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate gen_epub_book;
+/// # extern crate chrono;
+/// # fn main() {
+/// # use gen_epub_book::ops::{BookElement, EPubBook, check_epub};
+/// # use chrono::DateTime;
+/// # use std::io::Cursor;
+/// let book = EPubBook::from_elements(vec![
+///     BookElement::Name("Simple ePub demonstration".to_string()),
+///     BookElement::StringContent("<!-- ePub title: \"Chapter 1\" -->Chapter 1 text".to_string()),
+///     BookElement::Author("nabijaczleweli".to_string(), None),
+///     BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
+///     BookElement::Language("en-GB".to_string()),
+/// ]).unwrap();
+///
+/// let mut epub = Cursor::new(vec![]);
+/// book.write_zip(&mut epub, false, &mut vec![]).unwrap();
+/// assert_eq!(check_epub(&mut epub, false, &mut vec![]), Ok(()));
+/// # }
+/// ```
+pub fn check_epub<R: Read + Seek, W: Write>(from: &mut R, verbose: bool, verb_out: &mut W) -> Result<(), Error> {
+    let mut zip = try!(ZipArchive::new(from).map_err(|_| invalid("ePub", "not a ZIP archive", None)));
+    try!(check_mimetype(&mut zip));
+
+    let container = try!(parse_document(&mut zip, "META-INF/container.xml"));
+    let package_path = try!(container.elements
+        .iter()
+        .filter(|&&(ref name, _)| name == "rootfile")
+        .flat_map(|&(_, ref attrs)| attrs.get("full-path"))
+        .next()
+        .ok_or_else(|| invalid("META-INF/container.xml", "has no rootfile", None)));
+    let package_path = try!(resolve_href("", package_path).ok_or_else(|| invalid("META-INF/container.xml", "has external rootfile", None))).0;
+    if verbose {
+        let _ = writeln!(verb_out, "Found package document {}.", package_path);
+    }
+
+    let package = try!(parse_document(&mut zip, &package_path));
+    let mut manifest = BTreeMap::new();
+    for &(ref name, ref attrs) in &package.elements {
+        if name == "item" {
+            let id = try!(attrs.get("id").ok_or_else(|| invalid(&package_path, "has manifest item without id", None)));
+            let href = try!(attrs.get("href").ok_or_else(|| invalid(&package_path, "has manifest item without href", Some(id.clone()))));
+            if let Some((href, _)) = resolve_href(&package_path, href) {
+                try!(check_exists(&mut zip, &package_path, &href));
+                manifest.insert(id.clone(),
+                                (href,
+                                 attrs.get("media-type").map(|s| &s[..]).unwrap_or(""),
+                                 attrs.get("properties").map_or(false, |p| p.split_whitespace().any(|p| p == "nav"))));
+            }
+        }
+    }
+    for &(ref name, ref attrs) in &package.elements {
+        let idref = match &name[..] {
+            "spine" => attrs.get("toc"),
+            "itemref" => attrs.get("idref"),
+            _ => None,
+        };
+        if let Some(idref) = idref {
+            if !manifest.contains_key(idref) {
+                return Err(invalid(&package_path, "has unresolvable spine reference", Some(idref.clone())));
+            }
+        }
+    }
+    if verbose {
+        let _ = writeln!(verb_out, "Found {} manifest items.", manifest.len());
+    }
+
+    let mut ids = BTreeMap::new();
+    let mut targets = vec![];
+    for &(ref href, media_type, nav) in manifest.values() {
+        let (target_element, target_attribute) = match media_type {
+            "application/xhtml+xml" => ("a", "href"),
+            "application/x-dtbncx+xml" => ("content", "src"),
+            _ => continue,
+        };
+
+        let document = try!(parse_document(&mut zip, href));
+        if nav || media_type == "application/x-dtbncx+xml" {
+            for &(ref name, ref attrs) in &document.elements {
+                if name == target_element {
+                    if let Some(target) = attrs.get(target_attribute).and_then(|t| resolve_href(href, t)) {
+                        targets.push((href, target));
+                    }
+                }
+            }
+        }
+        ids.insert(href, document.ids);
+
+        if verbose {
+            let _ = writeln!(verb_out, "Checked {}.", href);
+        }
+    }
+
+    for (document, (target, fragment)) in targets {
+        try!(check_exists(&mut zip, document, &target));
+        if let Some(fragment) = fragment {
+            if ids.get(&target).map_or(false, |ids| !ids.contains(&fragment)) {
+                return Err(invalid(document, "references missing fragment", Some(format!("{}#{}", target, fragment))));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+
+struct Document {
+    elements: Vec<(String, BTreeMap<String, String>)>,
+    ids: BTreeSet<String>,
+}
+
+fn invalid(file: &str, what: &'static str, more: Option<String>) -> Error {
+    Error::InvalidEPub {
+        file: file.to_string(),
+        what: what,
+        more: more,
+    }
+}
+
+fn check_mimetype<R: Read + Seek>(zip: &mut ZipArchive<R>) -> Result<(), Error> {
+    let mut mimetype = try!(zip.by_index(0).map_err(|_| invalid("ePub", "empty", None)));
+    if mimetype.name() != "mimetype" {
+        return Err(invalid("mimetype", "not the first entry", None));
+    }
+    if mimetype.compression() != CompressionMethod::Stored {
+        return Err(invalid("mimetype", "compressed", None));
+    }
+    if mimetype.data_start() != mimetype.header_start() + 30 + "mimetype".len() as u64 {
+        return Err(invalid("mimetype", "has an extra field", None));
+    }
+
+    let mut content = String::new();
+    try!(mimetype.read_to_string(&mut content).map_err(|_| invalid("mimetype", "unreadable", None)));
+    if content != MIME_TYPE {
+        return Err(invalid("mimetype", "has wrong content", Some(content)));
+    }
+
+    Ok(())
+}
+
+fn check_exists<R: Read + Seek>(zip: &mut ZipArchive<R>, document: &str, file: &str) -> Result<(), Error> {
+    zip.by_name(file).map(|_| ()).map_err(|_| invalid(document, "references missing file", Some(file.to_string())))
+}
+
+fn parse_document<R: Read + Seek>(zip: &mut ZipArchive<R>, file: &str) -> Result<Document, Error> {
+    let mut document = Document {
+        elements: vec![],
+        ids: BTreeSet::new(),
+    };
+
+    for event in EventReader::new(try!(zip.by_name(file).map_err(|_| invalid(file, "missing", None)))) {
+        if let XmlEvent::StartElement { name, attributes, .. } = try!(event.map_err(|e| invalid(file, "not well-formed", Some(e.to_string())))) {
+            let attributes: BTreeMap<_, _> = attributes.into_iter().filter(|a| a.name.prefix.is_none()).map(|a| (a.name.local_name, a.value)).collect();
+            if let Some(id) = attributes.get("id") {
+                if !document.ids.insert(id.clone()) {
+                    return Err(invalid(file, "has duplicate ID", Some(id.clone())));
+                }
+            }
+            document.elements.push((name.local_name, attributes));
+        }
+    }
+
+    Ok(document)
+}
+
+/// Resolve an `href` relative to the specified archive file into an archive path and optional fragment.
+///
+/// Returns `None` for absolute URLs.
+fn resolve_href(relative_to: &str, href: &str) -> Option<(String, Option<String>)> {
+    if href.find(':').map_or(false, |colon| href.find(|c| c == '/' || c == '?' || c == '#').map_or(true, |sep| colon < sep)) {
+        return None;
+    }
+
+    let (path, fragment) = match href.find('#') {
+        Some(i) => (&href[..i], Some(href[i + 1..].to_string())),
+        None => (href, None),
+    };
+    if path.is_empty() {
+        return Some((relative_to.to_string(), fragment));
+    }
+
+    let mut segments: Vec<_> = relative_to.split('/').collect();
+    segments.pop();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." if segments.last().map_or(false, |&s| s != "..") => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    Some((percent_decode(segments.join("/").as_bytes()).decode_utf8_lossy().into_owned(), fragment))
+}
@@ -1,16 +1,71 @@
 use url::Url;
-use std::fmt;
+use std::{cmp, fmt};
+use uuid::Uuid;
 use std::path::PathBuf;
+use self::super::super::util::edit_distance;
 use self::super::super::Error;
 use chrono::{DateTime, FixedOffset};
 
 
+/// Declare `ELEMENT_NAMES` and `element_name()` from a single list of variants and their descriptor names
+macro_rules! element_names {
+    ($($variant:ident => $name:expr,)*) => {
+        static ELEMENT_NAMES: &'static [&'static str] = &[$($name),*];
+
+        fn element_name(elem: &BookElement) -> &'static str {
+            match *elem {
+                $(BookElement::$variant(..) => $name,)*
+            }
+        }
+    }
+}
+
+element_names! {
+    Name => "Name",
+    Content => "Content",
+    NetworkContent => "Network-Content",
+    StringContent => "String-Content",
+    MarkdownContent => "Markdown-Content",
+    StringMarkdownContent => "String-Markdown-Content",
+    ImageContent => "Image-Content",
+    NetworkImageContent => "Network-Image-Content",
+    Cover => "Cover",
+    NetworkCover => "Network-Cover",
+    Include => "Include",
+    NetworkInclude => "Network-Include",
+    Description => "Description",
+    StringDescription => "String-Description",
+    NetworkDescription => "Network-Description",
+    Author => "Author",
+    Contributor => "Contributor",
+    Date => "Date",
+    Language => "Language",
+    Uuid => "UUID",
+    Publisher => "Publisher",
+    Rights => "Rights",
+    Subject => "Subject",
+    Source => "Source",
+    Relation => "Relation",
+    Isbn => "ISBN",
+    Identifier => "Identifier",
+    Modified => "Modified",
+    Series => "Series",
+    SeriesIndex => "Series-Index",
+}
+
+
 /// A single element of the e-book
 ///
 /// Parse a single line with `BookElement::parse()` or the whole descriptor with `ops::parse_descriptor()`.
 ///
 /// Use `Display` to desugar back to description form.
 ///
+/// The values of the `Network-*` elements can be suffixed with ` sha256=HASH`,
+/// where `HASH` is the 64-hexdigit SHA-256 hash the downloaded content must have,
+/// which is kept, lowercased, alongside the URL.
+///
+/// Besides HTTP(S), they accept `file:` and `data:` URLs, see `util::download_to_ext()`.
+///
 /// # Examples
 ///
 /// ```
@@ -35,16 +90,48 @@ pub enum BookElement {
     /// which will allow users on e-book readers to jump directly to the content
     /// represented by the document containing this entry.
     ///
+    /// The comment can also specify the content's TOC level, see `ops::find_title_ext()`.
+    ///
     /// Required: no<br />
     /// Value: relative path to (X)HTML chunk<br />
     /// Amount: any
     Content(PathBuf),
+    /// URL of (X)HTML page to put in the e-book
+    ///
+    /// The TOC name is taken from the `<!-- ePub title: "TOC_NAME" -->` comment, like for `Content`,
+    /// or, failing that, from the page's `<title>`.
+    ///
+    /// References to other network content in the e-book are rewritten to point at it,
+    /// and other relative references are made absolute.
+    /// Images referenced from the page can also be downloaded and included, see `EPubBook::network_images`.
+    ///
+    /// Required: no<br />
+    /// Type: file URL, optionally followed by ` sha256=HASH`<br />
+    /// Amount: any
+    NetworkContent(Url, Option<String>),
     /// (X)HTML string to use as content
     ///
     /// Required: no<br />
     /// Value: (X)HTML string<br />
     /// Amount: any
     StringContent(String),
+    /// Markdown content to put in the e-book
+    ///
+    /// The content is rendered from CommonMark to XHTML when writing the book.
+    ///
+    /// The TOC name is taken from the `<!-- ePub title: "TOC_NAME" -->` comment, like for `Content`,
+    /// or, failing that, from the first heading.
+    ///
+    /// Required: no<br />
+    /// Value: relative path to Markdown file<br />
+    /// Amount: any
+    MarkdownContent(PathBuf),
+    /// Markdown string to use as content
+    ///
+    /// Required: no<br />
+    /// Value: Markdown string<br />
+    /// Amount: any
+    StringMarkdownContent(String),
     /// Relative path to image to include in e-book
     ///
     /// Required: no<br />
@@ -54,9 +141,9 @@ pub enum BookElement {
     /// URL of image to include in e-book
     ///
     /// Required: no<br />
-    /// Type: file URL<br />
+    /// Type: file URL, optionally followed by ` sha256=HASH`<br />
     /// Amount: any
-    NetworkImageContent(Url),
+    NetworkImageContent(Url, Option<String>),
     /// Relative path to image to use as e-book cover
     ///
     /// Required: no<br />
@@ -67,10 +154,10 @@ pub enum BookElement {
     /// URL of image to use as e-book cover
     ///
     /// Required: no<br />
-    /// Type: file URL<br />
+    /// Type: file URL, optionally followed by ` sha256=HASH`<br />
     /// Amount: 0-1<br />
     /// Remarks: exclusive with Cover
-    NetworkCover(Url),
+    NetworkCover(Url, Option<String>),
     /// Auxilliary file to include in e-book
     ///
     /// This is useful for, e.g., CSS.
@@ -84,9 +171,9 @@ pub enum BookElement {
     /// This is useful for, e.g., fonts.
     ///
     /// Required: no<br />
-    /// Type: file URL<br />
+    /// Type: file URL, optionally followed by ` sha256=HASH`<br />
     /// Amount: any
-    NetworkInclude(Url),
+    NetworkInclude(Url, Option<String>),
     /// Relative path to file containing the book's description
     ///
     /// Required: no<br />
@@ -104,16 +191,27 @@ pub enum BookElement {
     /// URL of auxilliary file containing the book's description
     ///
     /// Required: no<br />
-    /// Type: file URL<br />
+    /// Type: file URL, optionally followed by ` sha256=HASH`<br />
     /// Amount: 0-1<br />
     /// Remarks: exclusive with Description and String-Description
-    NetworkDescription(Url),
-    /// E-book's author
+    NetworkDescription(Url, Option<String>),
+    /// One of the e-book's authors, and the name to sort them by, if specified
+    ///
+    /// The name to sort by is specified by suffixing the value with ` file-as=SORT_NAME`.
     ///
     /// Required: yes<br />
-    /// Type: plaintext string<br />
-    /// Amount: 1
-    Author(String),
+    /// Type: plaintext string, optionally followed by ` file-as=SORT_NAME`<br />
+    /// Amount: 1+
+    Author(String, Option<String>),
+    /// Someone else who contributed to the e-book, with their role, like `trl` for translators or `ill` for illustrators
+    ///
+    /// The role is a [MARC relator code](https://www.loc.gov/marc/relators/relaterm.html),
+    /// and the name to sort by is specified like for `Author`.
+    ///
+    /// Required: no<br />
+    /// Type: MARC relator code, plaintext string, optionally followed by ` file-as=SORT_NAME`<br />
+    /// Amount: any
+    Contributor(String, String, Option<String>),
     /// E-book's authoring/publishing date
     ///
     /// Required: yes<br />
@@ -126,6 +224,78 @@ pub enum BookElement {
     /// Type: [BCP47](https://tools.ietf.org/html/bcp47)-compliant language code<br />
     /// Amount: 1
     Language(String),
+    /// E-book's UUID
+    ///
+    /// If not specified, one is derived from the rest of the book's elements,
+    /// so that the same descriptor always produces the same book.
+    ///
+    /// Required: no<br />
+    /// Type: [RFC4122](https://tools.ietf.org/html/rfc4122)-compliant UUID<br />
+    /// Amount: 0-1
+    Uuid(Uuid),
+    /// E-book's publisher
+    ///
+    /// Required: no<br />
+    /// Type: plaintext string<br />
+    /// Amount: 0-1
+    Publisher(String),
+    /// E-book's copyright and licensing statement
+    ///
+    /// Required: no<br />
+    /// Type: plaintext string<br />
+    /// Amount: 0-1
+    Rights(String),
+    /// Topic of the e-book, like a genre or keyword
+    ///
+    /// Required: no<br />
+    /// Type: plaintext string<br />
+    /// Amount: any
+    Subject(String),
+    /// Work the e-book is derived from, like the printed book's ISBN
+    ///
+    /// Required: no<br />
+    /// Type: plaintext string<br />
+    /// Amount: 0-1
+    Source(String),
+    /// Work the e-book is related to
+    ///
+    /// Required: no<br />
+    /// Type: plaintext string<br />
+    /// Amount: any
+    Relation(String),
+    /// E-book's ISBN, without hyphens or spaces
+    ///
+    /// Required: no<br />
+    /// Type: ISBN-10 or ISBN-13 with a correct check digit<br />
+    /// Amount: 0-1
+    Isbn(String),
+    /// Additional identifier of the e-book, with its scheme, like `DOI 10.1000/182`
+    ///
+    /// Required: no<br />
+    /// Type: scheme name, plaintext string<br />
+    /// Amount: any
+    Identifier(String, String),
+    /// E-book's last modification date
+    ///
+    /// If not specified, the time of writing the book is used, see `EPubBook::write_zip_ext()`.
+    ///
+    /// Required: no<br />
+    /// Type: [RFC3339](https://tools.ietf.org/html/rfc3339)-compliant date<br />
+    /// Amount: 0-1
+    Modified(DateTime<FixedOffset>),
+    /// Series the e-book belongs to
+    ///
+    /// Required: no<br />
+    /// Type: plaintext string<br />
+    /// Amount: 0-1
+    Series(String),
+    /// E-book's position in its series
+    ///
+    /// Required: no<br />
+    /// Type: decimal number<br />
+    /// Amount: 0-1<br />
+    /// Remarks: requires Series
+    SeriesIndex(String),
 }
 
 impl BookElement {
@@ -136,7 +306,8 @@ impl BookElement {
     ///
     /// If the line isn't a descripting line or the line uses an unknown key, `Ok(None)` is returned.
     ///
-    /// `Err` will only be returned when parsing a `DateTime` or a `Url` fails.
+    /// `Err` will only be returned when parsing a `DateTime`, a `Url`, a SHA-256 hash, a contributor, an ISBN, an identifier,
+    /// a series index, or a `Uuid` fails.
     ///
     /// If `free_date` is `true`, in addition to the default RFC3339,
     /// RFC2822 and Unix-timestamp+tt:zz are accepted as correct `DateTime` formats.
@@ -153,6 +324,8 @@ impl BookElement {
     /// assert!(BookElement::parse("Date: 1486564218", ":", true).is_err());
     /// assert!(BookElement::parse("Network-Image-Content: http/i.imgur.com/ViQ2WED.jpg",
     ///                            ":", false).is_err());
+    /// assert!(BookElement::parse("Network-Image-Content: http://i.imgur.com/ViQ2WED.jpg sha256=00",
+    ///                            ":", false).is_err());
     /// ```
     ///
     /// Not a description/unrecognised key:
@@ -195,31 +368,64 @@ impl BookElement {
                 if i == line.len() - 1 {
                     Ok(None)
                 } else {
-                    let ctnt = line[i + separator.len()..].trim();
-                    match line[0..i].trim() {
-                        "Name" => Ok(Some(BookElement::Name(ctnt.to_string()))),
-                        "Content" => Ok(Some(BookElement::Content(PathBuf::from(ctnt)))),
-                        "String-Content" => Ok(Some(BookElement::StringContent(ctnt.to_string()))),
-                        "Image-Content" => Ok(Some(BookElement::ImageContent(PathBuf::from(ctnt)))),
-                        "Network-Image-Content" => Ok(Some(BookElement::NetworkImageContent(try!(BookElement::parse_url(ctnt))))),
-                        "Cover" => Ok(Some(BookElement::Cover(PathBuf::from(ctnt)))),
-                        "Network-Cover" => Ok(Some(BookElement::NetworkCover(try!(BookElement::parse_url(ctnt))))),
-                        "Include" => Ok(Some(BookElement::Include(PathBuf::from(ctnt)))),
-                        "Network-Include" => Ok(Some(BookElement::NetworkInclude(try!(BookElement::parse_url(ctnt))))),
-                        "Description" => Ok(Some(BookElement::Description(PathBuf::from(ctnt)))),
-                        "String-Description" => Ok(Some(BookElement::StringDescription(ctnt.to_string()))),
-                        "Network-Description" => Ok(Some(BookElement::NetworkDescription(try!(BookElement::parse_url(ctnt))))),
-                        "Author" => Ok(Some(BookElement::Author(ctnt.to_string()))),
-                        "Date" => Ok(Some(BookElement::Date(try!(BookElement::parse_datetime(ctnt, free_date))))),
-                        "Language" => Ok(Some(BookElement::Language(ctnt.to_string()))),
-                        _ => Ok(None),
-                    }
+                    BookElement::parse_pair(line[0..i].trim(), line[i + separator.len()..].trim(), free_date)
                 }
             }
             None => Ok(None),
         }
     }
 
+    /// Get a book element from an already-split descriptor key and value, like `parse()`.
+    ///
+    /// Unlike `parse()`, the value is used as-is, without stripping any whitespace.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use gen_epub_book::ops::BookElement;
+    /// assert_eq!(BookElement::parse_pair("String-Content", "  <p>Indented</p>", false),
+    ///            Ok(Some(BookElement::StringContent("  <p>Indented</p>".to_string()))));
+    /// assert_eq!(BookElement::parse_pair("Nmae", "nabijaczleweli", false), Ok(None));
+    /// ```
+    pub fn parse_pair(key: &str, ctnt: &str, free_date: bool) -> Result<Option<BookElement>, Error> {
+        match key {
+            "Name" => Ok(Some(BookElement::Name(ctnt.to_string()))),
+            "Content" => Ok(Some(BookElement::Content(PathBuf::from(ctnt)))),
+            "Network-Content" => BookElement::parse_network(ctnt).map(|(u, h)| Some(BookElement::NetworkContent(u, h))),
+            "String-Content" => Ok(Some(BookElement::StringContent(ctnt.to_string()))),
+            "Markdown-Content" => Ok(Some(BookElement::MarkdownContent(PathBuf::from(ctnt)))),
+            "String-Markdown-Content" => Ok(Some(BookElement::StringMarkdownContent(ctnt.to_string()))),
+            "Image-Content" => Ok(Some(BookElement::ImageContent(PathBuf::from(ctnt)))),
+            "Network-Image-Content" => BookElement::parse_network(ctnt).map(|(u, h)| Some(BookElement::NetworkImageContent(u, h))),
+            "Cover" => Ok(Some(BookElement::Cover(PathBuf::from(ctnt)))),
+            "Network-Cover" => BookElement::parse_network(ctnt).map(|(u, h)| Some(BookElement::NetworkCover(u, h))),
+            "Include" => Ok(Some(BookElement::Include(PathBuf::from(ctnt)))),
+            "Network-Include" => BookElement::parse_network(ctnt).map(|(u, h)| Some(BookElement::NetworkInclude(u, h))),
+            "Description" => Ok(Some(BookElement::Description(PathBuf::from(ctnt)))),
+            "String-Description" => Ok(Some(BookElement::StringDescription(ctnt.to_string()))),
+            "Network-Description" => BookElement::parse_network(ctnt).map(|(u, h)| Some(BookElement::NetworkDescription(u, h))),
+            "Author" => {
+                let (name, file_as) = BookElement::parse_person(ctnt);
+                Ok(Some(BookElement::Author(name, file_as)))
+            }
+            "Contributor" => BookElement::parse_contributor(ctnt).map(|(r, n, f)| Some(BookElement::Contributor(r, n, f))),
+            "Date" => Ok(Some(BookElement::Date(try!(BookElement::parse_datetime(ctnt, free_date))))),
+            "Language" => Ok(Some(BookElement::Language(ctnt.to_string()))),
+            "UUID" => Ok(Some(BookElement::Uuid(try!(BookElement::parse_uuid(ctnt))))),
+            "Publisher" => Ok(Some(BookElement::Publisher(ctnt.to_string()))),
+            "Rights" => Ok(Some(BookElement::Rights(ctnt.to_string()))),
+            "Subject" => Ok(Some(BookElement::Subject(ctnt.to_string()))),
+            "Source" => Ok(Some(BookElement::Source(ctnt.to_string()))),
+            "Relation" => Ok(Some(BookElement::Relation(ctnt.to_string()))),
+            "ISBN" => Ok(Some(BookElement::Isbn(try!(BookElement::parse_isbn(ctnt))))),
+            "Identifier" => BookElement::parse_identifier(ctnt).map(|(s, i)| Some(BookElement::Identifier(s, i))),
+            "Modified" => Ok(Some(BookElement::Modified(try!(BookElement::parse_datetime(ctnt, free_date))))),
+            "Series" => Ok(Some(BookElement::Series(ctnt.to_string()))),
+            "Series-Index" => Ok(Some(BookElement::SeriesIndex(try!(BookElement::parse_series_index(ctnt))))),
+            _ => Ok(None),
+        }
+    }
+
     /// Get the descriptor name of this element.
     ///
     /// # Examples
@@ -234,28 +440,47 @@ impl BookElement {
     /// assert_eq!(BookElement::Name("nabijaczleweli".to_string()).name(), "Name");
     /// assert_eq!(BookElement::Content(PathBuf::from("content/ch01.html")).name(), "Content");
     /// assert_eq!(BookElement::NetworkImageContent(
-    ///                Url::parse("http://i.imgur.com/ViQ2WED.jpg").unwrap()).name(),
+    ///                Url::parse("http://i.imgur.com/ViQ2WED.jpg").unwrap(), None).name(),
     ///            "Network-Image-Content");
     /// # }
     /// ```
     pub fn name(&self) -> &'static str {
-        match *self {
-            BookElement::Name(_) => "Name",
-            BookElement::Content(_) => "Content",
-            BookElement::StringContent(_) => "String-Content",
-            BookElement::ImageContent(_) => "Image-Content",
-            BookElement::NetworkImageContent(_) => "Network-Image-Content",
-            BookElement::Cover(_) => "Cover",
-            BookElement::NetworkCover(_) => "Network-Cover",
-            BookElement::Include(_) => "Include",
-            BookElement::NetworkInclude(_) => "Network-Include",
-            BookElement::Description(_) => "Description",
-            BookElement::StringDescription(_) => "String-Description",
-            BookElement::NetworkDescription(_) => "Network-Description",
-            BookElement::Author(_) => "Author",
-            BookElement::Date(_) => "Date",
-            BookElement::Language(_) => "Language",
-        }
+        element_name(self)
+    }
+
+    /// Get the descriptor names of all elements, in the order they're declared in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use gen_epub_book::ops::BookElement;
+    /// assert_eq!(BookElement::names()[0], "Name");
+    /// assert!(BookElement::names().contains(&"Series-Index"));
+    /// ```
+    pub fn names() -> &'static [&'static str] {
+        ELEMENT_NAMES
+    }
+
+    /// Find the element name most similar to the specified unknown key, if any is similar enough.
+    ///
+    /// Case is ignored, and at most a third of the key's characters (but at least one) may need editing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use gen_epub_book::ops::BookElement;
+    /// assert_eq!(BookElement::similar_name("Contnet"), Some("Content"));
+    /// assert_eq!(BookElement::similar_name("language"), Some("Language"));
+    /// assert_eq!(BookElement::similar_name("Title"), None);
+    /// ```
+    pub fn similar_name(key: &str) -> Option<&'static str> {
+        let key = key.to_lowercase();
+        let max_distance = cmp::max(1, key.chars().count() / 3);
+        ELEMENT_NAMES.iter()
+            .map(|name| (edit_distance(&key, &name.to_lowercase()), *name))
+            .filter(|&(distance, _)| distance <= max_distance)
+            .min_by_key(|&(distance, _)| distance)
+            .map(|(_, name)| name)
     }
 }
 
@@ -266,24 +491,113 @@ impl fmt::Display for BookElement {
         match *self {
             BookElement::Name(ref s) |
             BookElement::StringContent(ref s) |
+            BookElement::StringMarkdownContent(ref s) |
             BookElement::StringDescription(ref s) |
-            BookElement::Author(ref s) |
-            BookElement::Language(ref s) => write!(f, "{}", s),
+            BookElement::Language(ref s) |
+            BookElement::Publisher(ref s) |
+            BookElement::Rights(ref s) |
+            BookElement::Subject(ref s) |
+            BookElement::Source(ref s) |
+            BookElement::Relation(ref s) |
+            BookElement::Isbn(ref s) |
+            BookElement::Series(ref s) |
+            BookElement::SeriesIndex(ref s) => write!(f, "{}", s),
+            BookElement::Identifier(ref s, ref i) => write!(f, "{} {}", s, i),
             BookElement::Content(ref pb) |
+            BookElement::MarkdownContent(ref pb) |
             BookElement::ImageContent(ref pb) |
             BookElement::Cover(ref pb) |
             BookElement::Include(ref pb) |
             BookElement::Description(ref pb) => write!(f, "{}", pb.display()),
-            BookElement::NetworkImageContent(ref u) |
-            BookElement::NetworkCover(ref u) |
-            BookElement::NetworkInclude(ref u) |
-            BookElement::NetworkDescription(ref u) => write!(f, "{}", u.as_str()),
-            BookElement::Date(ref d) => write!(f, "{}", d.to_rfc3339()),
+            BookElement::NetworkContent(ref u, ref h) |
+            BookElement::NetworkImageContent(ref u, ref h) |
+            BookElement::NetworkCover(ref u, ref h) |
+            BookElement::NetworkInclude(ref u, ref h) |
+            BookElement::NetworkDescription(ref u, ref h) => {
+                try!(write!(f, "{}", u.as_str()));
+                match *h {
+                    Some(ref h) => write!(f, " sha256={}", h),
+                    None => Ok(()),
+                }
+            }
+            BookElement::Author(ref n, ref fa) => BookElement::write_person(f, n, fa),
+            BookElement::Contributor(ref r, ref n, ref fa) => {
+                try!(write!(f, "{} ", r));
+                BookElement::write_person(f, n, fa)
+            }
+            BookElement::Date(ref d) |
+            BookElement::Modified(ref d) => write!(f, "{}", d.to_rfc3339()),
+            BookElement::Uuid(ref u) => write!(f, "{}", u.to_hyphenated_ref()),
         }
     }
 }
 
 impl BookElement {
+    fn parse_person(data: &str) -> (String, Option<String>) {
+        if let Some(i) = data.rfind("file-as=") {
+            if data[..i].ends_with(char::is_whitespace) {
+                let file_as = data[i + "file-as=".len()..].trim();
+                return (data[..i].trim().to_string(), if file_as.is_empty() { None } else { Some(file_as.to_string()) });
+            }
+        }
+
+        (data.to_string(), None)
+    }
+
+    fn parse_contributor(data: &str) -> Result<(String, String, Option<String>), Error> {
+        let (role, person) = match data.find(char::is_whitespace) {
+            Some(i) => (&data[..i], data[i..].trim()),
+            None => (data, ""),
+        };
+        if role.len() != 3 || !role.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(Error::Parse {
+                tp: "MARC relator code",
+                wher: "book element",
+                more: Some("not three letters"),
+            });
+        }
+        if person.is_empty() {
+            return Err(Error::Parse {
+                tp: "contributor",
+                wher: "book element",
+                more: Some("no name"),
+            });
+        }
+
+        let (name, file_as) = BookElement::parse_person(person);
+        Ok((role.to_lowercase(), name, file_as))
+    }
+
+    fn write_person(f: &mut fmt::Formatter, name: &str, file_as: &Option<String>) -> fmt::Result {
+        try!(write!(f, "{}", name));
+        match *file_as {
+            Some(ref fa) => write!(f, " file-as={}", fa),
+            None => Ok(()),
+        }
+    }
+
+    fn parse_network(data: &str) -> Result<(Url, Option<String>), Error> {
+        if let Some(i) = data.rfind(char::is_whitespace) {
+            if data[i + 1..].starts_with("sha256=") {
+                return Ok((try!(BookElement::parse_url(data[..i].trim())), Some(try!(BookElement::parse_sha256(&data[i + 1 + "sha256=".len()..])))));
+            }
+        }
+
+        Ok((try!(BookElement::parse_url(data)), None))
+    }
+
+    fn parse_sha256(data: &str) -> Result<String, Error> {
+        if data.len() == 64 && data.chars().all(|c| c.is_digit(16)) {
+            Ok(data.to_lowercase())
+        } else {
+            Err(Error::Parse {
+                tp: "SHA-256 hash",
+                wher: "book element",
+                more: Some("not 64 hexadecimal digits"),
+            })
+        }
+    }
+
     fn parse_url(data: &str) -> Result<Url, Error> {
         Url::parse(data).map_err(|_| {
             Error::Parse {
@@ -294,6 +608,65 @@ impl BookElement {
         })
     }
 
+    fn parse_isbn(data: &str) -> Result<String, Error> {
+        let isbn: String = data.chars().filter(|&c| c != '-' && !c.is_whitespace()).collect::<String>().to_uppercase();
+        let digits: Option<Vec<u32>> = isbn.chars().enumerate().map(|(i, c)| if i == 9 && c == 'X' { Some(10) } else { c.to_digit(10) }).collect();
+        let valid = match digits {
+            Some(ref digits) if digits.len() == 10 => digits.iter().enumerate().map(|(i, d)| (10 - i as u32) * d).sum::<u32>() % 11 == 0,
+            Some(ref digits) if digits.len() == 13 && !isbn.contains('X') => {
+                digits.iter().enumerate().map(|(i, d)| if i % 2 == 0 { *d } else { 3 * d }).sum::<u32>() % 10 == 0
+            }
+            _ => false,
+        };
+        if valid {
+            Ok(isbn)
+        } else {
+            Err(Error::Parse {
+                tp: "ISBN",
+                wher: "book element",
+                more: Some("not a valid ISBN-10 or ISBN-13"),
+            })
+        }
+    }
+
+    fn parse_identifier(data: &str) -> Result<(String, String), Error> {
+        match data.find(char::is_whitespace) {
+            Some(i) => Ok((data[..i].to_string(), data[i..].trim().to_string())),
+            None => {
+                Err(Error::Parse {
+                    tp: "identifier",
+                    wher: "book element",
+                    more: Some("no scheme"),
+                })
+            }
+        }
+    }
+
+    fn parse_series_index(data: &str) -> Result<String, Error> {
+        let mut parts = data.splitn(2, '.');
+        let whole = parts.next().unwrap();
+        let fraction = parts.next().unwrap_or("0");
+        if !whole.is_empty() && !fraction.is_empty() && whole.chars().chain(fraction.chars()).all(|c| c.is_digit(10)) {
+            Ok(data.to_string())
+        } else {
+            Err(Error::Parse {
+                tp: "series index",
+                wher: "book element",
+                more: Some("not a decimal number"),
+            })
+        }
+    }
+
+    fn parse_uuid(data: &str) -> Result<Uuid, Error> {
+        Uuid::parse_str(data).map_err(|_| {
+            Error::Parse {
+                tp: "UUID",
+                wher: "book element",
+                more: None,
+            }
+        })
+    }
+
     fn parse_datetime(data: &str, free_date: bool) -> Result<DateTime<FixedOffset>, Error> {
         let dt = DateTime::parse_from_rfc3339(data);
         if free_date {
@@ -1,23 +1,36 @@
 //! Main functions doing actual work.
 //!
-//! First, use `parse_descriptor()` to get an element list from a descriptor file,
+//! First, use `parse_descriptor_ext()` to get an element list from a descriptor file,
 //! then construct an `EPubBook::from_elements()`, validate and absolutise paths via
 //! `EPubBook::normalise_paths()` and write the book with `EPubBook::write_zip()`.
+//!
+//! An already written book can be verified with `check_epub()`.
 
 
 mod book;
+mod check;
 mod element;
 mod include_dir;
 
-use regex::Regex;
-use std::path::Path;
+use pulldown_cmark::{Parser, Event, Tag};
+use kuchiki::traits::TendrilSink;
+use kuchiki::parse_html;
+use regex::{Captures, Regex};
+use self::super::util::source_date_epoch;
+use chrono::Utc;
+use natord;
+use glob::{Pattern, glob};
+use std::path::{Component, PathBuf, Path};
+use std::collections::{BTreeSet, BTreeMap};
 use self::super::Error;
-use std::iter::FromIterator;
 use std::io::{BufReader, BufRead, Read};
+use std::fs::{self, File};
+use std::{env, iter};
 
+pub use self::check::check_epub;
 pub use self::element::BookElement;
 pub use self::include_dir::IncludeDirectory;
-pub use self::book::{EPubContentType, EPubData, EPubBook};
+pub use self::book::{EPubContentType, EPubVersion, Compression, EPubData, EPubBook};
 
 
 /// Parse the whole descriptor  with a specified
@@ -26,10 +39,8 @@ pub use self::book::{EPubContentType, EPubData, EPubBook};
 /// [rigidness](https://nabijaczleweli.xyz/content/gen-epub-book/programmer.html#features-free-date-format),
 /// stopping at the first encountered error.
 ///
-/// Uses `BookElement::parse()`, so it inherits all errors from there,
-/// adding only the error from splitting lines.
-///
-/// Filters out non-describing lines.
+/// Equivalent to `parse_descriptor_ext()` with the default `DescriptorConfig`, but without expanding glob patterns,
+/// so that it doesn't touch the filesystem.
 ///
 /// # Examples
 ///
@@ -57,27 +68,197 @@ pub use self::book::{EPubContentType, EPubData, EPubBook};
 ///         BookElement::Cover(PathBuf::from("cover.png")),
 ///         BookElement::ImageContent(PathBuf::from("simple/chapter_image.png")),
 ///         BookElement::Content(PathBuf::from("simple/ctnt.html")),
-///         BookElement::Author("nabijaczleweli".to_string()),
+///         BookElement::Author("nabijaczleweli".to_string(), None),
 ///         BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
 ///         BookElement::Language("en-GB".to_string())]));
 /// # }
 /// ```
-pub fn parse_descriptor<R: Read>(desc: &'static str, from: &mut R, separator: &str, free_date: bool) -> Result<Vec<BookElement>, Error> {
-    let elems: Vec<Option<BookElement>> = try!(Result::from_iter(BufReader::new(from)
-        .lines()
-        .map(|r| {
-            r.map_err(|_| {
-                Error::Io {
-                    desc: desc,
-                    op: "read",
-                    more: Some("line split"),
+pub fn parse_descriptor<R: Read>(desc: &str, from: &mut R, separator: &str, free_date: bool) -> Result<Vec<BookElement>, Error> {
+    let config = DescriptorConfig::default();
+    let mut state = DescriptorState::new(&config);
+    state.expand_globs = false;
+    parse_descriptor_impl(desc, from, separator, free_date, &mut state)
+}
+
+/// How to parse a descriptor with `parse_descriptor_ext()`.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DescriptorConfig {
+    /// Whether to reject malformed lines and undefined variables
+    ///
+    /// If `true`, blank lines aside, lines without the separator, without a key or with an empty value (once variables are expanded) are `Error::Parse`s,
+    /// lines whose key isn't an element's name are `Error::UnknownElement`s, both wrapped in `Error::Descriptor`,
+    /// and undefined variables are `Error::UndefinedVariable`s.
+    ///
+    /// Default: `false`
+    pub strict: bool,
+    /// Directories to resolve `Descriptor-Include`s and glob patterns in, in order
+    ///
+    /// Default: none
+    pub include_order: Vec<IncludeDirectory>,
+    /// Additional (name, value) variables, which take precedence over `Set` ones
+    ///
+    /// Default: none
+    pub defines: Vec<(String, String)>,
+    /// Whether to also take variables from the environment, after all others
+    ///
+    /// Default: `false`
+    pub environment: bool,
+    /// Path to the descriptor being parsed, if it's a file, so that it including itself is caught as a cycle
+    ///
+    /// Default: `None`
+    pub path: Option<PathBuf>,
+}
+
+/// The state shared between a descriptor and the ones it includes.
+struct DescriptorState<'c> {
+    /// How to parse the descriptors.
+    config: &'c DescriptorConfig,
+    /// The variables `Set` so far.
+    variables: BTreeMap<String, String>,
+    /// The names and paths of the descriptors being included.
+    chain: Vec<(String, PathBuf)>,
+    /// Whether to expand glob patterns, which `parse_descriptor()` doesn't.
+    expand_globs: bool,
+}
+
+impl<'c> DescriptorState<'c> {
+    fn new(config: &'c DescriptorConfig) -> DescriptorState<'c> {
+        DescriptorState {
+            config: config,
+            variables: BTreeMap::new(),
+            chain: vec![],
+            expand_globs: true,
+        }
+    }
+}
+
+fn parse_descriptor_impl<R: Read>(desc: &str, from: &mut R, separator: &str, free_date: bool, state: &mut DescriptorState) -> Result<Vec<BookElement>, Error> {
+    let read_error = |_| {
+        Error::Io {
+            desc: "descriptor",
+            op: "read",
+            more: Some("line split"),
+        }
+    };
+
+    let mut elems = vec![];
+    let mut lines = BufReader::new(from).lines().enumerate();
+    while let Some((i, line)) = lines.next() {
+        let line = try!(line.map_err(read_error));
+        if line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        let parsed = match heredoc_terminator(&line, separator) {
+            Some((key, terminator)) => {
+                let mut value = vec![];
+                loop {
+                    match lines.next() {
+                        Some((_, l)) => {
+                            let l = try!(l.map_err(read_error));
+                            if l.trim() == terminator {
+                                break;
+                            }
+                            value.push(l);
+                        }
+                        None => {
+                            return Err(descriptor_error(desc,
+                                                        i + 1,
+                                                        &line,
+                                                        separator,
+                                                        Error::Parse {
+                                                            tp: "heredoc",
+                                                            wher: "descriptor",
+                                                            more: Some("no terminator"),
+                                                        }))
+                        }
+                    }
                 }
-            })
-        })
-        .map(|r| r.and_then(|l| BookElement::parse(&l, separator, free_date)))
-        .collect::<Vec<_>>()));
+                if state.config.strict {
+                    try!(check_strict(&line, separator).map_err(|err| descriptor_error(desc, i + 1, &line, separator, err)));
+                }
+                parse_element(key, &value.join("\n"), separator, free_date, state)
+            }
+            None => parse_line(&line, separator, free_date, state),
+        };
 
-    Ok(elems.into_iter().flat_map(|o| o).collect())
+        match parsed {
+            Ok(parsed) => elems.extend(parsed),
+            Err(err) => return Err(descriptor_error(desc, i + 1, &line, separator, err)),
+        }
+    }
+
+    Ok(elems)
+}
+
+/// Parse the whole descriptor with a specified
+/// [separator](https://nabijaczleweli.xyz/content/gen-epub-book/programmer.html#features-custom-separator),
+/// [rigidness](https://nabijaczleweli.xyz/content/gen-epub-book/programmer.html#features-free-date-format)
+/// and additional configuration, stopping at the first encountered error.
+///
+/// Uses `BookElement::parse_pair()`, so it inherits all errors from there, wrapped in `Error::Descriptor`,
+/// which specifies the descriptor's name and the line's number, key and value,
+/// adding only the error from splitting lines.
+///
+/// Lines starting with `#` are comments, and are skipped.
+///
+/// A value of `<<TERMINATOR`, where `TERMINATOR` consists of alphanumerics, `-`s and `_`s, starts a heredoc:
+/// the element's value is then all following lines, verbatim, up to the one consisting of just `TERMINATOR`;
+/// a missing terminator is an `Error::Parse`.
+///
+/// `${NAME}`s in values are replaced with the value of the variable `NAME`, taken from, in order:
+/// the config's `defines`, the last preceding `Set: NAME=value` line (in this or an included descriptor),
+/// the built-in `DATE` (the current RFC3339 datetime) and `YEAR`, both from `util::source_date_epoch()` if set,
+/// and, if the config's `environment` is set, the environment. Undefined variables are left as-is, unless `strict`.
+/// `$${NAME}` stands for a literal `${NAME}`.
+///
+/// A `Descriptor-Include: PATH` line is replaced with the elements of the descriptor at `PATH`,
+/// resolved in the first include directory that has it (or an `Error::FileNotFound`), parsed the same way.
+/// Errors in the included descriptor are wrapped in an `Error::Descriptor` for the including line,
+/// and including a descriptor that's already being included is an `Error::IncludeCycle`.
+///
+/// Filters out non-describing lines, unless `strict`.
+///
+/// Glob patterns (paths containing `*`, `?`, or `[`) in `Content`, `Markdown-Content`, `Image-Content` and `Include` elements
+/// are matched against all the include directories, then replaced by the elements for all the files thus found,
+/// in natural order (so `ch2.html` comes before `ch10.html`), unless a file with that very path exists in one of them.
+///
+/// A glob pattern that doesn't match any files is an `Error::FileNotFound`.
+///
+/// # Examples
+///
+/// ```
+/// # use gen_epub_book::ops::{IncludeDirectory, BookElement, DescriptorConfig, parse_descriptor_ext};
+/// # use std::fs::{self, File};
+/// # use std::env::temp_dir;
+/// # use std::path::PathBuf;
+/// # let book = temp_dir().join("gen-epub-book.rs-doctest").join("ops-parse-descriptor-ext-0");
+/// # fs::create_dir_all(book.join("chapters")).unwrap();
+/// # for f in &["ch1.html", "ch2.html", "ch10.html", "notes.txt"] {
+/// #   File::create(book.join("chapters").join(f)).unwrap();
+/// # }
+/// let config = DescriptorConfig {
+///     include_order: vec![IncludeDirectory::Unnamed {
+///         dir: ("".to_string(), book.clone()),
+///     }],
+///     ..DescriptorConfig::default()
+/// };
+/// assert_eq!(parse_descriptor_ext("string input", &mut &b"\
+///         Name: Globbed ePub demonstration\n\
+///         Content: chapters/ch*.html\n"[..], ":", false, &config),
+///     Ok(vec![
+///         BookElement::Name("Globbed ePub demonstration".to_string()),
+///         BookElement::Content(PathBuf::from("chapters/ch1.html")),
+///         BookElement::Content(PathBuf::from("chapters/ch2.html")),
+///         BookElement::Content(PathBuf::from("chapters/ch10.html"))]));
+/// ```
+pub fn parse_descriptor_ext<R: Read>(desc: &str, from: &mut R, separator: &str, free_date: bool, config: &DescriptorConfig)
+                                     -> Result<Vec<BookElement>, Error> {
+    let mut state = DescriptorState::new(config);
+    if let Some(ref path) = config.path {
+        state.chain.push((desc.to_string(), fs::canonicalize(path).unwrap_or_else(|_| path.clone())));
+    }
+    parse_descriptor_impl(desc, from, separator, free_date, &mut state)
 }
 
 /// Find an ePub title line in the specified input stream.
@@ -95,8 +276,28 @@ pub fn parse_descriptor<R: Read>(desc: &'static str, from: &mut R, separator: &s
 ///            Some("TTL".to_string()));
 /// ```
 pub fn find_title<R: Read>(i: &mut R) -> Option<String> {
+    find_title_ext(i).map(|(title, _)| title)
+}
+
+/// Find an ePub title line in the specified input stream, along with the TOC level it specifies.
+///
+/// The title line contains `<!-- ePub title: "TOC_NAME" -->` or `<!-- ePub title: "TOC_NAME" level: LEVEL -->`,
+/// where `TOC_NAME` is any string not containing the `"` character, and `LEVEL` is a positive integer, defaulting to 1.
+///
+/// Content with a level deeper than the content preceding it is nested under it in the table of contents.
+///
+/// # Examples
+///
+/// ```
+/// # use gen_epub_book::ops::find_title_ext;
+/// assert_eq!(find_title_ext(&mut &br#"L1\nL <!-- ePub title: "TTL" -->2\nL3"#[..]),
+///            Some(("TTL".to_string(), 1)));
+/// assert_eq!(find_title_ext(&mut &br#"L1\nL <!-- ePub title: "TTL" level: 3 -->2\nL3"#[..]),
+///            Some(("TTL".to_string(), 3)));
+/// ```
+pub fn find_title_ext<R: Read>(i: &mut R) -> Option<(String, usize)> {
     lazy_static! {
-        static ref TITLE_RGX: Regex = Regex::new(r#"<!-- ePub title: "([^"]+)" -->"#).unwrap();
+        static ref TITLE_RGX: Regex = Regex::new(r#"<!-- ePub title: "([^"]+)"(?: level: ([1-9][0-9]*))? -->"#).unwrap();
     }
 
     BufReader::new(i)
@@ -106,7 +307,114 @@ pub fn find_title<R: Read>(i: &mut R) -> Option<String> {
         } else {
             false
         })
-        .map(|l| TITLE_RGX.captures(&l.unwrap()).unwrap().get(1).unwrap().as_str().to_string())
+        .map(|l| {
+            let l = l.unwrap();
+            let captures = TITLE_RGX.captures(&l).unwrap();
+            (captures.get(1).unwrap().as_str().to_string(), captures.get(2).and_then(|lvl| lvl.as_str().parse().ok()).unwrap_or(1))
+        })
+}
+
+/// Find an ePub title line in the specified Markdown input stream, or, failing that, its first heading.
+///
+/// The title line is the same as for `find_title()`.
+///
+/// See `find_markdown_title_ext()` for the TOC level.
+///
+/// # Examples
+///
+/// ```
+/// # use gen_epub_book::ops::find_markdown_title;
+/// assert_eq!(find_markdown_title(&mut &b"Intro\n\n## Chapter *1*\n\n# Chapter 2"[..]),
+///            Some("Chapter 1".to_string()));
+/// assert_eq!(find_markdown_title(&mut &b"<!-- ePub title: \"TTL\" -->\n\n# Chapter 1"[..]),
+///            Some("TTL".to_string()));
+/// ```
+pub fn find_markdown_title<R: Read>(i: &mut R) -> Option<String> {
+    find_markdown_title_ext(i).map(|(title, _)| title)
+}
+
+/// Find an ePub title line in the specified Markdown input stream, along with the TOC level it specifies,
+/// or, failing that, its first heading, at level 1.
+///
+/// The title line is the same as for `find_title_ext()`.
+///
+/// # Examples
+///
+/// ```
+/// # use gen_epub_book::ops::find_markdown_title_ext;
+/// assert_eq!(find_markdown_title_ext(&mut &b"Intro\n\n## Chapter *1*\n\n# Chapter 2"[..]),
+///            Some(("Chapter 1".to_string(), 1)));
+/// assert_eq!(find_markdown_title_ext(&mut &b"<!-- ePub title: \"TTL\" level: 2 -->\n\n# Chapter 1"[..]),
+///            Some(("TTL".to_string(), 2)));
+/// ```
+pub fn find_markdown_title_ext<R: Read>(i: &mut R) -> Option<(String, usize)> {
+    let mut md = String::new();
+    if i.read_to_string(&mut md).is_err() {
+        return None;
+    }
+
+    find_title_ext(&mut md.as_bytes()).or_else(|| {
+        let mut heading = None;
+        for ev in Parser::new(&md) {
+            match ev {
+                Event::Start(Tag::Header(_)) => heading = Some(String::new()),
+                Event::Text(ref t) if heading.is_some() => heading.as_mut().unwrap().push_str(t),
+                Event::End(Tag::Header(_)) => return heading.map(|h| (h.trim().to_string(), 1)),
+                _ => {}
+            }
+        }
+        None
+    })
+}
+
+/// Find an ePub title line in the specified (X)HTML input stream, or, failing that, its `<title>`.
+///
+/// The title line is the same as for `find_title()`.
+///
+/// See `find_html_title_ext()` for the TOC level.
+///
+/// # Examples
+///
+/// ```
+/// # use gen_epub_book::ops::find_html_title;
+/// assert_eq!(find_html_title(&mut &b"<html><head><title>Chapter  1 &amp;\n 2</title></head></html>"[..]),
+///            Some("Chapter 1 & 2".to_string()));
+/// assert_eq!(find_html_title(&mut &b"<!-- ePub title: \"TTL\" --><title>Chapter 1</title>"[..]),
+///            Some("TTL".to_string()));
+/// ```
+pub fn find_html_title<R: Read>(i: &mut R) -> Option<String> {
+    find_html_title_ext(i).map(|(title, _)| title)
+}
+
+/// Find an ePub title line in the specified (X)HTML input stream, along with the TOC level it specifies,
+/// or, failing that, its `<title>`, with whitespace collapsed, at level 1.
+///
+/// The title line is the same as for `find_title_ext()`.
+///
+/// # Examples
+///
+/// ```
+/// # use gen_epub_book::ops::find_html_title_ext;
+/// assert_eq!(find_html_title_ext(&mut &b"<title>Chapter 1</title><h1>Chapter 2</h1>"[..]),
+///            Some(("Chapter 1".to_string(), 1)));
+/// assert_eq!(find_html_title_ext(&mut &b"<!-- ePub title: \"TTL\" level: 2 --><title>Chapter 1</title>"[..]),
+///            Some(("TTL".to_string(), 2)));
+/// ```
+pub fn find_html_title_ext<R: Read>(i: &mut R) -> Option<(String, usize)> {
+    let mut html = String::new();
+    if i.read_to_string(&mut html).is_err() {
+        return None;
+    }
+
+    find_title_ext(&mut html.as_bytes()).or_else(|| {
+        parse_html()
+            .one(html)
+            .select_first("title")
+            .ok()
+            .map(|title| title.text_contents().split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|title| !title.is_empty())
+            .map(|title| (title, 1))
+    })
 }
 
 /// Find an appropriate `IncludeDirectory` for the specified file, or `None` otherwise
@@ -177,3 +485,271 @@ pub fn find_file<P: AsRef<Path>>(file: P, include_order: &[IncludeDirectory]) ->
     let file = file.as_ref();
     include_order.iter().find(|i| i.resolve(file).is_some())
 }
+
+/// Check whether the specified string can be used as a variable's name, i.e. is a non-empty string of alphanumerics and `_`s.
+///
+/// # Examples
+///
+/// ```
+/// # use gen_epub_book::ops::is_variable_name;
+/// assert!(is_variable_name("VERSION"));
+/// assert!(is_variable_name("chapter_2"));
+/// assert!(!is_variable_name("RELEASE-DATE"));
+/// assert!(!is_variable_name(""));
+/// ```
+pub fn is_variable_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+
+/// Parse a single, heredoc-joined, descriptor line into the elements it stands for.
+fn parse_line(line: &str, separator: &str, free_date: bool, state: &mut DescriptorState) -> Result<Vec<BookElement>, Error> {
+    if state.config.strict {
+        try!(check_strict(line, separator));
+    }
+
+    match split_line(line, separator) {
+        Some((key, value)) => parse_element(key, value, separator, free_date, state),
+        None => Ok(vec![]),
+    }
+}
+
+/// Parse the element with the specified key and value, taking the value verbatim.
+fn parse_element(key: &str, value: &str, separator: &str, free_date: bool, state: &mut DescriptorState) -> Result<Vec<BookElement>, Error> {
+    if key == "Set" {
+        let (name, value) = try!(parse_definition(value));
+        let value = try!(expand_variables(value, state));
+        state.variables.insert(name.to_string(), value);
+        return Ok(vec![]);
+    }
+
+    // Checked after expansion, since a variable can be empty
+    let value = try!(expand_variables(value, state));
+    if value.trim().is_empty() {
+        if state.config.strict {
+            Err(Error::Parse {
+                tp: "element",
+                wher: "descriptor",
+                more: Some("empty value"),
+            })
+        } else {
+            Ok(vec![])
+        }
+    } else if key == "Descriptor-Include" {
+        include_descriptor(&value, separator, free_date, state)
+    } else {
+        match try!(BookElement::parse_pair(key, &value, free_date)) {
+            Some(elem) if state.expand_globs => expand_glob(elem, &state.config.include_order),
+            Some(elem) => Ok(vec![elem]),
+            None => Ok(vec![]),
+        }
+    }
+}
+
+/// Split the specified descriptor line into its trimmed key and value, if it has the separator.
+fn split_line<'l>(line: &'l str, separator: &str) -> Option<(&'l str, &'l str)> {
+    let line = line.trim();
+    line.find(separator).map(|i| (line[..i].trim(), line[i + separator.len()..].trim()))
+}
+
+/// Split the value of a `Set` line into the variable's name and value.
+fn parse_definition(value: &str) -> Result<(&str, &str), Error> {
+    match value.find('=') {
+        Some(i) if is_variable_name(value[..i].trim()) => Ok((value[..i].trim(), value[i + 1..].trim())),
+        _ => {
+            Err(Error::Parse {
+                tp: "variable definition",
+                wher: "descriptor",
+                more: Some("not NAME=value"),
+            })
+        }
+    }
+}
+
+/// Replace all `${NAME}`s in the specified value with the variables' values, and all `$${NAME}`s with `${NAME}`.
+///
+/// Undefined variables are left as-is, unless `strict`, in which case they're an `Error::UndefinedVariable`.
+fn expand_variables(value: &str, state: &DescriptorState) -> Result<String, Error> {
+    lazy_static! {
+        static ref VARIABLE: Regex = Regex::new(r"\$(\$?)\{([a-zA-Z0-9_]+)\}").unwrap();
+    }
+
+    let mut error = None;
+    let expanded = VARIABLE.replace_all(value, |var: &Captures| {
+            if !var[1].is_empty() {
+                return var[0][1..].to_string();
+            }
+
+            match variable(&var[2], state) {
+                Ok(Some(value)) => value,
+                Ok(None) => {
+                    if state.config.strict && error.is_none() {
+                        error = Some(Error::UndefinedVariable { name: var[2].to_string() });
+                    }
+                    var[0].to_string()
+                }
+                Err(err) => {
+                    error = error.take().or(Some(err));
+                    var[0].to_string()
+                }
+            }
+        })
+        .into_owned();
+
+    match error {
+        Some(err) => Err(err),
+        None => Ok(expanded),
+    }
+}
+
+/// Get the value of the specified variable, looking at, in order: `-d`efines, `Set`s, built-ins and, if enabled, the environment.
+fn variable(name: &str, state: &DescriptorState) -> Result<Option<String>, Error> {
+    if let Some(&(_, ref value)) = state.config.defines.iter().rev().find(|&&(ref n, _)| n == name) {
+        return Ok(Some(value.clone()));
+    }
+    if let Some(value) = state.variables.get(name) {
+        return Ok(Some(value.clone()));
+    }
+
+    match name {
+        "DATE" => Ok(Some(try!(source_date_epoch()).unwrap_or_else(Utc::now).format("%Y-%m-%dT%H:%M:%S+00:00").to_string())),
+        "YEAR" => Ok(Some(try!(source_date_epoch()).unwrap_or_else(Utc::now).format("%Y").to_string())),
+        _ if state.config.environment => Ok(env::var(name).ok()),
+        _ => Ok(None),
+    }
+}
+
+/// Parse the descriptor at the specified path, resolved in the include directories, unless it's already in the include chain.
+fn include_descriptor(path: &str, separator: &str, free_date: bool, state: &mut DescriptorState) -> Result<Vec<BookElement>, Error> {
+    let file = try!(state.config.include_order.iter().filter_map(|i| i.resolve(path)).next().ok_or_else(|| {
+        Error::FileNotFound {
+            who: "Descriptor-Include",
+            path: PathBuf::from(path),
+        }
+    }));
+    let file = fs::canonicalize(&file).unwrap_or(file);
+
+    if let Some(i) = state.chain.iter().position(|&(_, ref f)| *f == file) {
+        return Err(Error::IncludeCycle { chain: state.chain[i..].iter().map(|&(ref n, _)| n.clone()).chain(iter::once(path.to_string())).collect() });
+    }
+
+    let mut from = try!(File::open(&file).map_err(|_| {
+        Error::Io {
+            desc: "included descriptor",
+            op: "open",
+            more: None,
+        }
+    }));
+    state.chain.push((path.to_string(), file));
+    let elems = parse_descriptor_impl(path, &mut from, separator, free_date, state);
+    state.chain.pop();
+    elems
+}
+
+/// Get the key and terminator of the heredoc started by the specified descriptor line, if it starts one.
+fn heredoc_terminator<'l>(line: &'l str, separator: &str) -> Option<(&'l str, &'l str)> {
+    split_line(line, separator).and_then(|(key, value)| if value.starts_with("<<") && value.len() > 2 &&
+                                                           value[2..].chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+        Some((key, &value[2..]))
+    } else {
+        None
+    })
+}
+
+/// Check that the specified descriptor line is blank or has a known key.
+fn check_strict(line: &str, separator: &str) -> Result<(), Error> {
+    let malformed = |more| {
+        Err(Error::Parse {
+            tp: "element",
+            wher: "descriptor",
+            more: Some(more),
+        })
+    };
+    match split_line(line, separator) {
+        Some(("", _)) => malformed("no key"),
+        Some((key, _)) if !BookElement::names().contains(&key) && key != "Descriptor-Include" && key != "Set" => {
+            Err(Error::UnknownElement {
+                name: key.to_string(),
+                suggestion: BookElement::similar_name(key),
+            })
+        }
+        Some(_) => Ok(()),
+        None if line.trim().is_empty() => Ok(()),
+        None => malformed("no separator"),
+    }
+}
+
+/// Wrap an error in the specified descriptor line in an `Error::Descriptor`, pointing at the line's key, for key errors, or value.
+fn descriptor_error(desc: &str, line_number: usize, line: &str, separator: &str, error: Error) -> Error {
+    let trimmed = line.trim();
+    let start = line.len() - line.trim_start().len();
+    let (key, value, value_start) = match trimmed.find(separator) {
+        Some(i) => {
+            let rest = &trimmed[i + separator.len()..];
+            (trimmed[..i].trim(), rest.trim(), start + i + separator.len() + (rest.len() - rest.trim_start().len()))
+        }
+        None => ("", trimmed, start),
+    };
+
+    Error::Descriptor {
+        descriptor: desc.to_string(),
+        line: line_number,
+        column: line[..if error.is_key_error() { start } else { value_start }].chars().count() + 1,
+        text: line.to_string(),
+        key: key.to_string(),
+        value: value.to_string(),
+        error: Box::new(error),
+    }
+}
+
+/// Replace the element with the ones for the files matched by its path, if it's a glob pattern, as described in `parse_descriptor_ext()`.
+fn expand_glob(elem: BookElement, include_order: &[IncludeDirectory]) -> Result<Vec<BookElement>, Error> {
+    let (pattern, construct): (String, fn(PathBuf) -> BookElement) = match elem {
+        BookElement::Content(ref p) => (p.to_string_lossy().into_owned(), BookElement::Content),
+        BookElement::MarkdownContent(ref p) => (p.to_string_lossy().into_owned(), BookElement::MarkdownContent),
+        BookElement::ImageContent(ref p) => (p.to_string_lossy().into_owned(), BookElement::ImageContent),
+        BookElement::Include(ref p) => (p.to_string_lossy().into_owned(), BookElement::Include),
+        _ => return Ok(vec![elem]),
+    };
+    // Paths that merely look like patterns, like "notes[1].html", are kept if they exist
+    if !pattern.contains(|c| c == '*' || c == '?' || c == '[') || find_file(&pattern, include_order).is_some() {
+        return Ok(vec![elem]);
+    }
+
+    let mut found = BTreeSet::new();
+    for dir in include_order {
+        let root = match *dir {
+            IncludeDirectory::Named { ref dir, .. } |
+            IncludeDirectory::Unnamed { ref dir } => &dir.1,
+        };
+
+        let paths = try!(glob(&format!("{}/{}", Pattern::escape(&root.to_string_lossy()), pattern)).map_err(|e| {
+            Error::Parse {
+                tp: "glob",
+                wher: elem.name(),
+                more: Some(e.msg),
+            }
+        }));
+        // glob() drops "."s from the paths it returns, so a plain strip_prefix() won't do
+        let root_len = root.components().filter(|&c| c != Component::CurDir).count();
+        for path in paths.flat_map(|p| p).filter(|p| p.is_file()) {
+            found.insert(path.components()
+                .filter(|&c| c != Component::CurDir)
+                .skip(root_len)
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+                .join("/"));
+        }
+    }
+
+    if found.is_empty() {
+        return Err(Error::FileNotFound {
+            who: elem.name(),
+            path: PathBuf::from(pattern),
+        });
+    }
+
+    let mut found: Vec<_> = found.into_iter().collect();
+    found.sort_by(|lhs, rhs| natord::compare(lhs, rhs));
+    Ok(found.into_iter().map(|f| construct(PathBuf::from(f))).collect())
+}
@@ -11,9 +11,12 @@
 //! ```
 
 
-use self::super::ops::IncludeDirectory;
-use clap::{AppSettings, Arg};
+use self::super::ops::{IncludeDirectory, EPubVersion, is_variable_name};
+use self::super::util::DownloadConfig;
+use clap::{AppSettings, SubCommand, Arg};
+use std::time::Duration;
 use std::path::PathBuf;
+use url::Url;
 use std::iter;
 use std::fs;
 
@@ -47,6 +50,42 @@ pub struct Options {
     ///
     /// Default: false
     pub free_date: bool,
+    /// Whether to reject unknown keys and malformed lines in the descriptor instead of ignoring them.
+    ///
+    /// Default: false
+    pub strict: bool,
+    /// Additional (name, value) variables for the descriptor, overriding its `Set` lines.
+    ///
+    /// Default: empty
+    pub defines: Vec<(String, String)>,
+    /// The ePub version to write.
+    ///
+    /// Default: `EPubVersion::V2`
+    pub epub_version: EPubVersion,
+    /// Whether to also write the NCX table of contents for ePub 3 books.
+    ///
+    /// Default: true
+    pub legacy_ncx: bool,
+    /// Whether to convert (X)HTML content to well-formed XHTML.
+    ///
+    /// Default: false
+    pub normalise_html: bool,
+    /// How to download network content.
+    ///
+    /// Default: no cache directory, online, 4 downloads at once, 30 second timeout, no retries, no extra headers, no proxy.
+    pub download: DownloadConfig,
+    /// Whether to also include images referenced from network pages.
+    ///
+    /// Default: false
+    pub network_images: bool,
+    /// Whether to print the SHA-256 hashes of network content instead of assembling an ePub.
+    ///
+    /// Default: false
+    pub print_hashes: bool,
+    /// The ePub to check instead of assembling one, or `None` to assemble.
+    ///
+    /// Default: `None`
+    pub check_file: Option<(String, PathBuf)>,
 }
 
 impl Options {
@@ -54,18 +93,54 @@ impl Options {
     pub fn parse() -> Options {
         let matches = app_from_crate!("\n")
             .setting(AppSettings::ColoredHelp)
+            .setting(AppSettings::SubcommandsNegateReqs)
+            .setting(AppSettings::DisableHelpSubcommand)
             .arg(Arg::from_usage("<SOURCE> 'File to assemble ePub from'").validator(Options::source_file_validator))
-            .arg(Arg::from_usage("<TARGET> 'File to write'"))
+            .arg(Arg::from_usage("<TARGET> 'File to write'").required_unless("print-hashes"))
             .arg(Arg::from_usage("-v --verbose 'Print more information'"))
             .arg(Arg::from_usage("-D --free-date 'Parse more datetime formats'"))
+            .arg(Arg::from_usage("--strict 'Reject unknown keys and malformed lines in the descriptor'"))
+            .arg(Arg::from_usage("-d --define [DEFINE]... 'Additional descriptor variable. Format: NAME=value'")
+                .number_of_values(1)
+                .validator(Options::define_validator)
+                .required(false))
             .arg(Arg::from_usage("-S --separator [SEPARATOR] 'Custom separator'").default_value(":").validator(Options::separator_validator).required(false))
             .arg(Arg::from_usage("-I --include [INC_DIR]... 'Additional include directory. Format: [name=]path'")
                 .validator(Options::include_dir_validator)
                 .required(false))
+            .arg(Arg::from_usage("-E --epub-version [VERSION] 'ePub version to write'").possible_values(&["2", "3"]).default_value("2").required(false))
+            .arg(Arg::from_usage("--no-ncx 'Don't write the NCX table of contents for ePub 3'"))
+            .arg(Arg::from_usage("-X --xhtml 'Convert (X)HTML content to well-formed XHTML'"))
+            .arg(Arg::from_usage("--cache-dir [CACHE_DIR] 'Directory to cache network content in'").required(false))
+            .arg(Arg::from_usage("--offline 'Only use cached network content'").requires("cache-dir"))
+            .arg(Arg::from_usage("-j --jobs [JOBS] 'Amount of network content to download at once'")
+                .default_value("4")
+                .validator(Options::jobs_validator)
+                .required(false))
+            .arg(Arg::from_usage("--timeout [SECONDS] 'Seconds to wait for a server, 0 for no limit'")
+                .default_value("30")
+                .validator(Options::timeout_validator)
+                .required(false))
+            .arg(Arg::from_usage("--retries [RETRIES] 'Amount of times to retry failed downloads'")
+                .default_value("0")
+                .validator(Options::retries_validator)
+                .required(false))
+            .arg(Arg::from_usage("-H --header [HEADER]... 'Additional HTTP header to send. Format: Name: value'")
+                .number_of_values(1)
+                .validator(Options::header_validator)
+                .required(false))
+            .arg(Arg::from_usage("--user-agent [USER_AGENT] 'HTTP user agent to send'").required(false))
+            .arg(Arg::from_usage("--proxy [PROXY] 'Proxy URL to download network content through'").validator(Options::proxy_validator).required(false))
+            .arg(Arg::from_usage("--network-images 'Also include images referenced from network pages'"))
+            .arg(Arg::from_usage("--print-hashes 'Print the hashes of network content instead of assembling an ePub'"))
+            .subcommand(SubCommand::with_name("check")
+                .about("Check an ePub's structural validity")
+                .arg(Arg::from_usage("<EPUB> 'ePub file to check'").validator(Options::epub_file_validator)))
             .get_matches();
 
-        let source = Options::optional_fname_arg(matches.value_of("SOURCE").unwrap());
-        let target = Options::optional_fname_arg(matches.value_of("TARGET").unwrap());
+        let check = matches.subcommand_matches("check").map(|m| m.value_of("EPUB").unwrap());
+        let source = matches.value_of("SOURCE").and_then(Options::optional_fname_arg);
+        let target = matches.value_of("TARGET").and_then(Options::optional_fname_arg);
         let source_root = match source.and_then(|src| src.rfind('/').or_else(|| src.rfind('\\'))) {
             Some(s) => IncludeDirectory::Unnamed { dir: (source.unwrap()[..s + 1].to_string(), PathBuf::from(&source.unwrap()[..s])) },
             None => IncludeDirectory::Unnamed { dir: ("".to_string(), PathBuf::from(".")) },
@@ -79,6 +154,27 @@ impl Options {
             verbose: matches.is_present("verbose"),
             separator: matches.value_of("separator").unwrap_or(":").to_string(),
             free_date: matches.is_present("free-date"),
+            strict: matches.is_present("strict"),
+            defines: matches.values_of("define").into_iter().flat_map(|v| v.map(Options::split_define).map(Option::unwrap)).collect(),
+            epub_version: matches.value_of("epub-version").unwrap_or("2").parse().unwrap(),
+            legacy_ncx: !matches.is_present("no-ncx"),
+            normalise_html: matches.is_present("xhtml"),
+            download: DownloadConfig {
+                cache_dir: matches.value_of("cache-dir").map(PathBuf::from),
+                offline: matches.is_present("offline"),
+                jobs: matches.value_of("jobs").unwrap_or("4").parse().unwrap(),
+                timeout: match matches.value_of("timeout").unwrap_or("30").parse().unwrap() {
+                    0 => None,
+                    s => Some(Duration::from_secs(s)),
+                },
+                retries: matches.value_of("retries").unwrap_or("0").parse().unwrap(),
+                headers: matches.values_of("header").into_iter().flat_map(|v| v.map(Options::split_header).map(Option::unwrap)).collect(),
+                user_agent: matches.value_of("user-agent").map(str::to_string),
+                proxy: matches.value_of("proxy").map(|p| Url::parse(p).unwrap()),
+            },
+            network_images: matches.is_present("network-images"),
+            print_hashes: matches.is_present("print-hashes"),
+            check_file: check.map(|c| (c.to_string(), PathBuf::from(c))),
         }
     }
 
@@ -94,6 +190,14 @@ impl Options {
         }
     }
 
+    fn epub_file_validator(s: String) -> Result<(), String> {
+        fs::canonicalize(&s).map_err(|_| format!("ePub file \"{}\" not found", s)).and_then(|f| if f.is_file() {
+            Ok(())
+        } else {
+            Err(format!("ePub file \"{}\" not actualy a file", s))
+        })
+    }
+
     fn separator_validator(s: String) -> Result<(), String> {
         if s.is_empty() {
             Err("Separator empty".to_string())
@@ -102,6 +206,39 @@ impl Options {
         }
     }
 
+    fn jobs_validator(s: String) -> Result<(), String> {
+        match s.parse::<usize>() {
+            Ok(0) | Err(_) => Err(format!("Job count \"{}\" not a positive integer", s)),
+            Ok(_) => Ok(()),
+        }
+    }
+
+    fn timeout_validator(s: String) -> Result<(), String> {
+        s.parse::<u64>().map(|_| ()).map_err(|_| format!("Timeout \"{}\" not a non-negative integer", s))
+    }
+
+    fn retries_validator(s: String) -> Result<(), String> {
+        s.parse::<u32>().map(|_| ()).map_err(|_| format!("Retry count \"{}\" not a non-negative integer", s))
+    }
+
+    fn header_validator(s: String) -> Result<(), String> {
+        match Options::split_header(&s) {
+            Some((ref name, _)) if !name.is_empty() => Ok(()),
+            _ => Err(format!("Header \"{}\" not in \"Name: value\" format", s)),
+        }
+    }
+
+    fn define_validator(s: String) -> Result<(), String> {
+        match Options::split_define(&s) {
+            Some(_) => Ok(()),
+            None => Err(format!("Define \"{}\" not in \"NAME=value\" format", s)),
+        }
+    }
+
+    fn proxy_validator(s: String) -> Result<(), String> {
+        Url::parse(&s).map(|_| ()).map_err(|e| format!("Proxy \"{}\" not a valid URL: {}", s, e))
+    }
+
     fn include_dir_validator(s: String) -> Result<(), String> {
         s.parse::<IncludeDirectory>().map(|_| ()).map_err(|e| {
             let mut out = Vec::new();
@@ -110,6 +247,14 @@ impl Options {
         })
     }
 
+    fn split_header(s: &str) -> Option<(String, String)> {
+        s.find(':').map(|i| (s[..i].trim().to_string(), s[i + 1..].trim().to_string()))
+    }
+
+    fn split_define(s: &str) -> Option<(String, String)> {
+        s.find('=').map(|i| (s[..i].trim().to_string(), s[i + 1..].to_string())).filter(|&(ref name, _)| is_variable_name(name))
+    }
+
     fn optional_fname_arg(s: &str) -> Option<&str> {
         if s == "-" { None } else { Some(s) }
     }
@@ -1,11 +1,26 @@
 //! Module containing various utility functions.
 
 
+use chrono::{DateTime, TimeZone, Utc};
+use reqwest::header::{CONTENT_DISPOSITION, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, USER_AGENT, HeaderMap, HeaderName,
+                      HeaderValue};
+use reqwest::{Client, Proxy, StatusCode};
 use std::path::{PathBuf, Path};
-use std::io::{self, Write};
+use std::io::{self, Read, Write};
 use self::super::Error;
+use std::fs::{self, File};
+use std::time::Duration;
+use std::{cmp, env};
+use std::thread;
+use uuid::Uuid;
+use kuchiki::{NodeData, NodeRef, parse_html};
+use kuchiki::traits::TendrilSink;
+use pulldown_cmark::{Parser, html};
+use regex::{Captures, Regex};
+use sha2::{Digest, Sha256};
+use base64;
+use url::percent_encoding::percent_decode;
 use url::Url;
-use reqwest;
 
 
 /// Contents of the the container file
@@ -25,6 +40,188 @@ pub static MIME_TYPE: &'static str = "application/epub+zip";
 /// Mimetype specifier resides in `/content.opf`.
 pub static CONTENT_TABLE_HEADER: &'static str = include_str!("../assets/content.opf.header");
 
+/// Constant header for the root file/content table of ePub 3 books.
+///
+/// Same as `CONTENT_TABLE_HEADER`, but declares package version 3.0.
+pub static CONTENT_TABLE_HEADER_3: &'static str = include_str!("../assets/content-3.opf.header");
+
+
+/// How to download network content with `download_to_ext()`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DownloadConfig {
+    /// Directory to cache downloaded content in, if any
+    ///
+    /// Cached content is revalidated with its `ETag` and `Last-Modified` headers.
+    ///
+    /// Default: `None`
+    pub cache_dir: Option<PathBuf>,
+    /// Whether to only use cached content, failing for content that isn't
+    ///
+    /// Default: `false`
+    pub offline: bool,
+    /// How many network files to download at once when writing a book
+    ///
+    /// Default: `4`
+    pub jobs: usize,
+    /// How long to wait for a server before giving up, if at all
+    ///
+    /// Default: 30 seconds
+    pub timeout: Option<Duration>,
+    /// How many more times to try downloading after failed attempts
+    ///
+    /// Attempts fail on connection errors, server errors and "Too Many Requests" responses,
+    /// and are retried after 1, 2, 4, &c. seconds.
+    ///
+    /// Default: `0`
+    pub retries: u32,
+    /// Additional (name, value) headers to send with each request, e.g. for authentication
+    ///
+    /// Default: none
+    pub headers: Vec<(String, String)>,
+    /// User agent to identify as, if any
+    ///
+    /// Default: `None`
+    pub user_agent: Option<String>,
+    /// Proxy to send all requests through, if any
+    ///
+    /// Default: `None`
+    pub proxy: Option<Url>,
+}
+
+impl DownloadConfig {
+    /// Get the path under which the content of the specified URL is cached, if there's a cache directory.
+    ///
+    /// The file is named after the URL's v5 UUID in the URL namespace,
+    /// and is accompanied by one with the `headers` extension with its validators, content type and disposition.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate gen_epub_book;
+    /// # extern crate url;
+    /// # fn main() {
+    /// # use self::gen_epub_book::util::DownloadConfig;
+    /// # use std::path::{PathBuf, Path};
+    /// # use self::url::Url;
+    /// let config = DownloadConfig {
+    ///     cache_dir: Some(PathBuf::from("cache")),
+    ///     ..DownloadConfig::default()
+    /// };
+    /// assert_eq!(config.cache_path(&Url::parse("https://example.com/cover.png").unwrap()),
+    ///            Some(Path::new("cache").join("eb29906f72dd5d74ac08aea196c0136c")));
+    /// assert_eq!(DownloadConfig::default().cache_path(&Url::parse("https://example.com/cover.png").unwrap()), None);
+    /// # }
+    /// ```
+    pub fn cache_path(&self, url: &Url) -> Option<PathBuf> {
+        self.cache_dir.as_ref().map(|d| d.join(Uuid::new_v5(&Uuid::NAMESPACE_URL, url.as_str().as_bytes()).to_simple().to_string()))
+    }
+
+    /// Build an HTTP client sending the configured headers and user agent, through the configured proxy, with the configured timeout.
+    ///
+    /// Returns `Error::Parse` if any of them is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use gen_epub_book::util::DownloadConfig;
+    /// assert!(DownloadConfig::default().http_client().is_ok());
+    /// assert!(DownloadConfig {
+    ///     headers: vec![("Bad header".to_string(), "value".to_string())],
+    ///     ..DownloadConfig::default()
+    /// }.http_client().is_err());
+    /// ```
+    pub fn http_client(&self) -> Result<Client, Error> {
+        fn invalid(more: &'static str) -> Error {
+            Error::Parse {
+                tp: "HTTP client configuration",
+                wher: "network content",
+                more: Some(more),
+            }
+        }
+
+        let mut headers = HeaderMap::new();
+        if let Some(ref user_agent) = self.user_agent {
+            headers.insert(USER_AGENT, try!(HeaderValue::from_str(user_agent).map_err(|_| invalid("invalid user agent"))));
+        }
+        for (name, value) in &self.headers {
+            headers.append(try!(HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid("invalid header name"))),
+                           try!(HeaderValue::from_str(value).map_err(|_| invalid("invalid header value"))));
+        }
+
+        let mut client = Client::builder().default_headers(headers).timeout(self.timeout);
+        if let Some(ref proxy) = self.proxy {
+            client = client.proxy(try!(Proxy::all(proxy.as_str()).map_err(|_| invalid("invalid proxy"))));
+        }
+        client.build().map_err(|_| invalid("unsupported"))
+    }
+}
+
+impl Default for DownloadConfig {
+    fn default() -> DownloadConfig {
+        DownloadConfig {
+            cache_dir: None,
+            offline: false,
+            jobs: 4,
+            timeout: Some(Duration::from_secs(30)),
+            retries: 0,
+            headers: vec![],
+            user_agent: None,
+            proxy: None,
+        }
+    }
+}
+
+/// What the server said about content downloaded with `download_to_ext()`.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DownloadInfo {
+    /// The content's lowercase media type, without parameters, if specified
+    pub content_type: Option<String>,
+    /// The filename the server suggested for the content, without any directories, if any
+    pub filename: Option<String>,
+}
+
+impl DownloadInfo {
+    /// Extract the download information from the values of the `Content-Type` and `Content-Disposition` headers.
+    ///
+    /// An extended (`filename*`) filename takes precedence over a plain one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use gen_epub_book::util::DownloadInfo;
+    /// assert_eq!(DownloadInfo::from_headers(Some("image/PNG; charset=binary"), Some(r#"inline; filename="../cover.png""#)),
+    ///            DownloadInfo {
+    ///                content_type: Some("image/png".to_string()),
+    ///                filename: Some("cover.png".to_string()),
+    ///            });
+    /// assert_eq!(DownloadInfo::from_headers(None, Some("attachment; filename=a.jpg; filename*=UTF-8''%C5%BC.jpg")).filename,
+    ///            Some("ż.jpg".to_string()));
+    /// assert_eq!(DownloadInfo::from_headers(None, None), DownloadInfo::default());
+    /// ```
+    pub fn from_headers(content_type: Option<&str>, content_disposition: Option<&str>) -> DownloadInfo {
+        let mut filename = None;
+        for param in content_disposition.into_iter().flat_map(|cd| cd.split(';').skip(1)) {
+            let (name, value) = match param.find('=') {
+                Some(i) => (param[..i].trim().to_lowercase(), param[i + 1..].trim().trim_matches('"')),
+                None => continue,
+            };
+            if name == "filename*" {
+                let value = &value[value.rfind('\'').map_or(0, |i| i + 1)..];
+                filename = Some(percent_decode(value.as_bytes()).decode_utf8_lossy().into_owned());
+                break;
+            } else if name == "filename" {
+                filename = Some(value.to_string());
+            }
+        }
+
+        DownloadInfo {
+            content_type: content_type.map(|ct| ct.split(';').next().unwrap().trim().to_lowercase()).filter(|ct| !ct.is_empty()),
+            filename: filename.map(|f| f[f.rfind(|c| c == '/' || c == '\\').map_or(0, |i| i + 1)..].to_string())
+                .filter(|f| !f.is_empty() && f != "." && f != ".."),
+        }
+    }
+}
+
 
 /// Uppercase the first character of the supplied string.
 ///
@@ -44,6 +241,54 @@ pub fn uppercase_first(s: &str) -> String {
     }
 }
 
+/// Calculate the Levenshtein distance between the supplied strings, in characters.
+///
+/// # Examples
+///
+/// ```
+/// # use gen_epub_book::util::edit_distance;
+/// assert_eq!(edit_distance("Contnet", "Content"), 2);
+/// assert_eq!(edit_distance("Autor", "Author"), 1);
+/// ```
+pub fn edit_distance(lhs: &str, rhs: &str) -> usize {
+    let rhs: Vec<char> = rhs.chars().collect();
+    let mut row: Vec<usize> = (0..rhs.len() + 1).collect();
+    for (i, l) in lhs.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &r) in rhs.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = cmp::min(cmp::min(above, row[j]) + 1, diagonal + if l == r { 0 } else { 1 });
+            diagonal = above;
+        }
+    }
+    row[rhs.len()]
+}
+
+/// Escape the characters with special meaning in XML so that the string can be safely used as text or in an attribute value.
+///
+/// # Examples
+///
+/// ```
+/// # use gen_epub_book::util::xml_escape;
+/// assert_eq!(&xml_escape(r#"Tom & "Jerry" <Redux>"#), "Tom &amp; &quot;Jerry&quot; &lt;Redux&gt;");
+/// assert_eq!(&xml_escape("Plain text"), "Plain text");
+/// ```
+pub fn xml_escape(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => ret.push_str("&amp;"),
+            '<' => ret.push_str("&lt;"),
+            '>' => ret.push_str("&gt;"),
+            '"' => ret.push_str("&quot;"),
+            '\'' => ret.push_str("&apos;"),
+            c => ret.push(c),
+        }
+    }
+    ret
+}
+
 /// Get the (X)HTML ID from a path.
 ///
 /// Replaces slashes with `-`s, `.`s with `_` and removes all `../`s, `./`s and the extension.
@@ -78,6 +323,32 @@ pub fn book_filename<P: AsRef<Path>>(p: P) -> PathBuf {
         .into()
 }
 
+/// Get the filename to pack the content of a URL as, absent a better one from the server.
+///
+/// This is the last non-empty path segment, or the host, for URLs without any, or `"network-data"`, for URLs without either.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate gen_epub_book;
+/// # extern crate url;
+/// # fn main() {
+/// # use gen_epub_book::util::url_filename;
+/// # use url::Url;
+/// assert_eq!(url_filename(&Url::parse("https://example.com/images/cover.png?size=large").unwrap()), "cover.png");
+/// assert_eq!(url_filename(&Url::parse("https://example.com/chapters/").unwrap()), "chapters");
+/// assert_eq!(url_filename(&Url::parse("https://example.com").unwrap()), "example.com");
+/// assert_eq!(url_filename(&Url::parse("data:text/plain,content").unwrap()), "network-data");
+/// # }
+/// ```
+pub fn url_filename(url: &Url) -> String {
+    url.path_segments()
+        .and_then(|s| s.filter(|s| !s.is_empty()).last())
+        .or_else(|| url.host_str())
+        .unwrap_or("network-data")
+        .to_string()
+}
+
 /// Get the (X)HTML ID from a URL.
 ///
 /// # Examples
@@ -94,11 +365,15 @@ pub fn book_filename<P: AsRef<Path>>(p: P) -> PathBuf {
 /// # }
 /// ```
 pub fn xhtml_url_id(url: &Url) -> String {
-    url.path_segments().unwrap().last().unwrap().replace('.', "_")
+    url_filename(url).replace('.', "_")
 }
 
 /// Write the string content in an acceptable form.
 ///
+/// The content is (X)HTML and is inserted verbatim, so any plaintext within needs to already be `xml_escape()`d.
+///
+/// This function is equivalent to [`write_string_content_ext()`](fn.write_string_content_ext.html) without a title.
+///
 /// # Examples
 ///
 /// ```
@@ -115,6 +390,31 @@ pub fn xhtml_url_id(url: &Url) -> String {
 /// "#);
 /// ```
 pub fn write_string_content<W: Write>(to: &mut W, ctnt: &str) -> Result<(), Error> {
+    write_string_content_ext(to, ctnt, None)
+}
+
+/// Write the string content in an acceptable form, with the specified title, if any.
+///
+/// A title is required for XHTML5, and thence ePub 3, content documents.
+///
+/// # Examples
+///
+/// ```
+/// # use gen_epub_book::util::write_string_content_ext;
+/// let mut buf = vec![];
+/// assert_eq!(write_string_content_ext(&mut buf, "<i>String content</i>", Some("Title & such")), Ok(()));
+/// assert_eq!(&buf.iter().map(|&i| i as char).collect::<String>(),
+/// r#"<html xmlns="http://www.w3.org/1999/xhtml">
+///   <head>
+///     <title>Title &amp; such</title>
+///   </head>
+///   <body>
+///     <i>String content</i>
+///   </body>
+/// </html>
+/// "#);
+/// ```
+pub fn write_string_content_ext<W: Write>(to: &mut W, ctnt: &str, title: Option<&str>) -> Result<(), Error> {
     fn e(more: &'static str) -> Error {
         Error::Io {
             desc: "string content",
@@ -124,7 +424,14 @@ pub fn write_string_content<W: Write>(to: &mut W, ctnt: &str) -> Result<(), Erro
     }
 
     try!(writeln!(to, r#"<html xmlns="http://www.w3.org/1999/xhtml">"#).map_err(|_| e("string content html start")));
-    try!(writeln!(to, r#"  <head></head>"#).map_err(|_| e("string content head start end")));
+    match title {
+        Some(title) => {
+            try!(writeln!(to, r#"  <head>"#).map_err(|_| e("string content head start")));
+            try!(writeln!(to, r#"    <title>{}</title>"#, xml_escape(title)).map_err(|_| e("string content title")));
+            try!(writeln!(to, r#"  </head>"#).map_err(|_| e("string content head end")));
+        }
+        None => try!(writeln!(to, r#"  <head></head>"#).map_err(|_| e("string content head start end"))),
+    }
     try!(writeln!(to, r#"  <body>"#).map_err(|_| e("string content body start")));
     try!(writeln!(to, r#"    {}"#, ctnt).map_err(|_| e("string content string")));
     try!(writeln!(to, r#"  </body>"#).map_err(|_| e("string content body end")));
@@ -133,8 +440,188 @@ pub fn write_string_content<W: Write>(to: &mut W, ctnt: &str) -> Result<(), Erro
     Ok(())
 }
 
+/// Render CommonMark to an XHTML chunk.
+///
+/// The result can then be inserted into a document with `write_string_content()`.
+///
+/// # Examples
+///
+/// ```
+/// # use gen_epub_book::util::render_markdown;
+/// assert_eq!(render_markdown("# Chapter 1\n\nTom & *Jerry*  \nRedux"),
+///            "<h1>Chapter 1</h1>\n<p>Tom &amp; <em>Jerry</em><br />\nRedux</p>\n");
+/// ```
+pub fn render_markdown(md: &str) -> String {
+    let mut out = String::new();
+    html::push_html(&mut out, Parser::new(md));
+    out
+}
+
+/// Convert (possibly tag-soup) HTML into a well-formed XHTML document.
+///
+/// The result has an XML declaration, the XHTML namespace, all elements closed (void ones self-closed), and
+/// the specified title, if the document doesn't have one already.
+///
+/// # Examples
+///
+/// ```
+/// # use gen_epub_book::util::html_to_xhtml;
+/// assert_eq!(html_to_xhtml("<p>Tom & Jerry<br><p class=redux>Redux", "Chapter 1"),
+///            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+///             <html xmlns=\"http://www.w3.org/1999/xhtml\"><head><title>Chapter 1</title></head>\
+///             <body><p>Tom &amp; Jerry<br /></p><p class=\"redux\">Redux</p></body></html>\n");
+/// ```
+pub fn html_to_xhtml(html: &str, title: &str) -> String {
+    let document = parse_html().one(html);
+    let has_title = document.descendants().any(|n| n.as_element().map_or(false, |e| &*e.name.local == "title"));
+
+    let mut out = r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_string();
+    out.push('\n');
+    write_xhtml_node(&document, XHTML_NAMESPACE, has_title, title, &mut out);
+    out.push('\n');
+    out
+}
+
+/// Replace references to other files in the specified (X)HTML content with what the specified function returns for them.
+///
+/// References are `src` and `href` attribute values and CSS `url()`s in `<style>` elements and `style` attributes.
+/// The function gets the reference verbatim and whether it's a hyperlink (i.e. the `href` of an `a` or `area` element)
+/// rather than a resource the content depends on. References for which it returns `None` are left as-is.
+///
+/// Use `map_css_references()` for CSS files.
+///
+/// # Examples
+///
+/// ```
+/// # use gen_epub_book::util::map_references;
+/// let content = r#"<link href="style.css" /><a href='ch02.html#fn3'><img src="img/fig1.png" style="background: url(bg.png)" /></a>"#;
+/// let mut found = vec![];
+/// assert_eq!(map_references(content, |r, link| {
+///                found.push((r.to_string(), link));
+///                if link { None } else { Some(r.replace('/', "-")) }
+///            }),
+///            r#"<link href="style.css" /><a href='ch02.html#fn3'><img src="img-fig1.png" style="background: url(bg.png)" /></a>"#);
+/// assert_eq!(found,
+///            vec![("style.css".to_string(), false),
+///                 ("ch02.html#fn3".to_string(), true),
+///                 ("img/fig1.png".to_string(), false),
+///                 ("bg.png".to_string(), false)]);
+/// ```
+pub fn map_references<F: FnMut(&str, bool) -> Option<String>>(content: &str, mut f: F) -> String {
+    lazy_static! {
+        static ref TAG: Regex = Regex::new(r#"<([a-zA-Z][a-zA-Z0-9:-]*)((?:[^>"']|"[^"]*"|'[^']*')*)>"#).unwrap();
+        static ref ATTRIBUTE: Regex = Regex::new(r#"(\s(src|href|xlink:href|style)\s*=\s*)(?:"([^"]*)"|'([^']*)')"#).unwrap();
+        static ref STYLE: Regex = Regex::new(r#"(?is)(<style(?:\s[^>]*)?>)(.*?)(</style\s*>)"#).unwrap();
+    }
+
+    let content = TAG.replace_all(content, |tag: &Captures| {
+        let link = ["a", "area"].iter().any(|l| tag[1].eq_ignore_ascii_case(l));
+        let attributes = ATTRIBUTE.replace_all(&tag[2], |attr: &Captures| {
+            let (quote, value) = match attr.get(3) {
+                Some(value) => ("\"", value.as_str()),
+                None => ("'", &attr[4]),
+            };
+            let reference = if &attr[2] == "style" {
+                Some(map_css_references(value, &mut f)).filter(|style| style != value)
+            } else {
+                f(value, link)
+            };
+            match reference {
+                Some(reference) => format!("{}{}{}{1}", &attr[1], quote, reference),
+                None => attr[0].to_string(),
+            }
+        });
+        format!("<{}{}>", &tag[1], attributes)
+    });
+
+    STYLE.replace_all(&content, |style: &Captures| format!("{}{}{}", &style[1], map_css_references(&style[2], &mut f), &style[3]))
+        .into_owned()
+}
+
+/// Replace references to other files, i.e. `url()`s, in the specified CSS content with what the specified function returns for them.
+///
+/// The function gets the reference verbatim and `false`, since CSS has no hyperlinks, like with `map_references()`.
+/// References for which it returns `None` are left as-is.
+///
+/// # Examples
+///
+/// ```
+/// # use gen_epub_book::util::map_css_references;
+/// assert_eq!(map_css_references(r#"body { background: url("img/bg.png") } p { background: url(p.png) }"#,
+///                               |r, _| if r == "p.png" { None } else { Some(r.replace('/', "-")) }),
+///            r#"body { background: url("img-bg.png") } p { background: url(p.png) }"#);
+/// ```
+pub fn map_css_references<F: FnMut(&str, bool) -> Option<String>>(content: &str, mut f: F) -> String {
+    lazy_static! {
+        static ref CSS_URL: Regex = Regex::new(r#"url\(\s*(?:"([^"]*)"|'([^']*)'|([^)"'\s]+))\s*\)"#).unwrap();
+    }
+
+    CSS_URL.replace_all(content, |url: &Captures| {
+            let (quote, reference) = match (url.get(1), url.get(2)) {
+                (Some(reference), _) => ("\"", reference.as_str()),
+                (None, Some(reference)) => ("'", reference.as_str()),
+                (None, None) => ("", &url[3]),
+            };
+            match f(reference, false) {
+                Some(reference) => format!("url({}{}{0})", quote, reference),
+                None => url[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// Get the SHA-256 hash of the specified data, as lowercase hex.
+///
+/// # Examples
+///
+/// ```
+/// # use gen_epub_book::util::sha256_hex;
+/// assert_eq!(sha256_hex(b"abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+/// ```
+pub fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// Get the time specified in the
+/// [`SOURCE_DATE_EPOCH`](https://reproducible-builds.org/specs/source-date-epoch/) environment variable, if any.
+///
+/// Returns an error if it's set to something other than a Unix timestamp.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate gen_epub_book;
+/// # extern crate chrono;
+/// # fn main() {
+/// # use gen_epub_book::util::source_date_epoch;
+/// # use chrono::{TimeZone, Utc};
+/// # use std::env;
+/// env::set_var("SOURCE_DATE_EPOCH", "1486564218");
+/// assert_eq!(source_date_epoch(), Ok(Some(Utc.timestamp(1486564218, 0))));
+/// # }
+/// ```
+pub fn source_date_epoch() -> Result<Option<DateTime<Utc>>, Error> {
+    match env::var("SOURCE_DATE_EPOCH") {
+        Ok(epoch) => {
+            epoch.trim()
+                .parse()
+                .ok()
+                .and_then(|epoch| Utc.timestamp_opt(epoch, 0).single())
+                .map(Some)
+                .ok_or(Error::Parse {
+                    tp: "Unix timestamp",
+                    wher: "SOURCE_DATE_EPOCH",
+                    more: None,
+                })
+        }
+        Err(_) => Ok(None),
+    }
+}
+
 /// Download the contents of the specified URL to the specified output stream.
 ///
+/// This function is equivalent to [`download_to_ext()`](fn.download_to_ext.html) with the default `DownloadConfig`.
+///
 /// # Examples
 ///
 /// ```
@@ -144,35 +631,361 @@ pub fn write_string_content<W: Write>(to: &mut W, ctnt: &str) -> Result<(), Erro
 /// # use self::gen_epub_book::util::download_to;
 /// # use self::url::Url;
 /// let mut buf = vec![];
-/// assert_eq!(download_to(&mut buf,
-///                        &Url::parse("https://www.uuidgenerator.net/api/version4").unwrap()),
-///            Ok(()));
+/// assert!(download_to(&mut buf,
+///                     &Url::parse("https://www.uuidgenerator.net/api/version4").unwrap()).is_ok());
 /// assert_eq!(buf.len(), "90772bc3-c7fd-4d3a-b88b-57d3122d3712\r\n".len());
 /// # }
 /// ```
-pub fn download_to<W: Write>(w: &mut W, what: &Url) -> Result<(), Error> {
-    let mut resp = try!(reqwest::get(what.as_str()).map_err(|_| {
+pub fn download_to<W: Write>(w: &mut W, what: &Url) -> Result<DownloadInfo, Error> {
+    download_to_ext(w, what, &DownloadConfig::default())
+}
+
+/// Download the contents of the specified URL to the specified output stream, as configured,
+/// returning what the server said about them.
+///
+/// `file:` URLs are read from the local filesystem and `data:` URLs are decoded in place, see `decode_data_url()`;
+/// neither is cached nor needs to be in offline mode.
+///
+/// With a cache directory, the content is saved there, alongside what the server said about it,
+/// and only downloaded again if it changed since,
+/// and, in offline mode, taken only from there, returning `Error::NotCached` if it isn't.
+/// A download only replaces its cached copy once it completes.
+///
+/// Failed attempts are retried as many times as configured, with exponential backoff.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate gen_epub_book;
+/// # extern crate url;
+/// # fn main() {
+/// # use self::gen_epub_book::util::{DownloadConfig, download_to_ext};
+/// # use self::gen_epub_book::Error;
+/// # use std::env::temp_dir;
+/// # use self::url::Url;
+/// let config = DownloadConfig {
+///     cache_dir: Some(temp_dir().join("gen-epub-book.rs-doctest").join("util-download-to-ext-0")),
+///     offline: true,
+///     ..DownloadConfig::default()
+/// };
+/// assert_eq!(download_to_ext(&mut vec![], &Url::parse("https://example.com/uncached.png").unwrap(), &config),
+///            Err(Error::NotCached { url: "https://example.com/uncached.png".to_string() }));
+/// # }
+/// ```
+pub fn download_to_ext<W: Write>(w: &mut W, what: &Url, config: &DownloadConfig) -> Result<DownloadInfo, Error> {
+    download_to_with_client(w, what, config, None)
+}
+
+/// Download the contents of the specified URL to the specified output stream, as configured, with the specified HTTP client,
+/// returning what the server said about them.
+///
+/// This function is equivalent to [`download_to_ext()`](fn.download_to_ext.html),
+/// but, given a client, from `DownloadConfig::http_client()`, it's used instead of building one for each download,
+/// so that connections can be reused across downloads.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate gen_epub_book;
+/// # extern crate url;
+/// # fn main() {
+/// # use self::gen_epub_book::util::{DownloadConfig, download_to_with_client};
+/// # use self::url::Url;
+/// let config = DownloadConfig::default();
+/// let client = config.http_client().unwrap();
+/// let mut buf = vec![];
+/// assert!(download_to_with_client(&mut buf, &Url::parse("data:,Chapter%201").unwrap(), &config, Some(&client)).is_ok());
+/// assert_eq!(buf, b"Chapter 1");
+/// # }
+/// ```
+pub fn download_to_with_client<W: Write>(w: &mut W, what: &Url, config: &DownloadConfig, client: Option<&Client>) -> Result<DownloadInfo, Error> {
+    fn cache_error(op: &'static str) -> Error {
         Error::Io {
-            desc: "network content",
-            op: "request",
+            desc: "download cache",
+            op: op,
             more: None,
         }
-    }));
+    }
 
-    if !resp.status().is_success() {
-        Err(Error::Io {
-            desc: "network content",
-            op: "inspect",
-            more: resp.status().canonical_reason(),
-        })
-    } else {
-        try!(io::copy(&mut resp, w).map_err(|_| {
-            Error::Io {
+    match what.scheme() {
+        "file" => {
+            let path = try!(what.to_file_path().map_err(|_| {
+                Error::Parse {
+                    tp: "file URL",
+                    wher: "network content",
+                    more: Some("not a local path"),
+                }
+            }));
+            let mut file = try!(File::open(&path).map_err(|_| {
+                Error::FileNotFound {
+                    who: "network content",
+                    path: path.clone(),
+                }
+            }));
+            try!(io::copy(&mut file, w).map_err(|_| {
+                Error::Io {
+                    desc: "network content",
+                    op: "read",
+                    more: None,
+                }
+            }));
+            return Ok(DownloadInfo::default());
+        }
+        "data" => {
+            let (data, media_type) = try!(decode_data_url(what));
+            try!(w.write_all(&data).map_err(|_| {
+                Error::Io {
+                    desc: "network content",
+                    op: "write",
+                    more: None,
+                }
+            }));
+            return Ok(DownloadInfo {
+                content_type: Some(media_type),
+                filename: None,
+            });
+        }
+        _ => {}
+    }
+
+    let cached = config.cache_path(what);
+    let is_cached = cached.as_ref().map_or(false, |c| c.is_file());
+    if config.offline && !is_cached {
+        return Err(Error::NotCached { url: what.to_string() });
+    }
+
+    let mut saved_headers = String::new();
+    if is_cached {
+        let _ = File::open(cached.as_ref().unwrap().with_extension("headers")).and_then(|mut f| f.read_to_string(&mut saved_headers));
+    }
+
+    if !config.offline {
+        let built;
+        let client = match client {
+            Some(client) => client,
+            None => {
+                built = try!(config.http_client());
+                &built
+            }
+        };
+
+        let mut attempt = 0;
+        let mut resp = loop {
+            let mut req = client.get(what.as_str());
+            if let Some(etag) = saved_header(&saved_headers, "ETag") {
+                req = req.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = saved_header(&saved_headers, "Last-Modified") {
+                req = req.header(IF_MODIFIED_SINCE, last_modified);
+            }
+
+            let resp = req.send();
+            let failed = resp.as_ref().map(|r| r.status().is_server_error() || r.status() == StatusCode::TOO_MANY_REQUESTS).unwrap_or(true);
+            if !failed || attempt >= config.retries {
+                break try!(resp.map_err(|_| {
+                    Error::Io {
+                        desc: "network content",
+                        op: "request",
+                        more: None,
+                    }
+                }));
+            }
+
+            thread::sleep(Duration::from_secs(1 << cmp::min(attempt, 6)));
+            attempt += 1;
+        };
+
+        if resp.status() == StatusCode::NOT_MODIFIED && is_cached {
+            // Fall through to reading the cache
+        } else if !resp.status().is_success() {
+            return Err(Error::Io {
                 desc: "network content",
-                op: "read",
-                more: None,
+                op: "inspect",
+                more: resp.status().canonical_reason(),
+            });
+        } else if let Some(ref cached) = cached {
+            try!(fs::create_dir_all(cached.parent().unwrap()).map_err(|_| cache_error("create")));
+
+            // Only move the download into the cache once it's complete, lest a failed one leave a truncated file there
+            let partial = cached.with_extension("partial");
+            let partial_headers = cached.with_extension("headers-partial");
+            let mut write_cache = || -> Result<(), Error> {
+                try!(io::copy(&mut resp, &mut try!(File::create(&partial).map_err(|_| cache_error("create")))).map_err(|_| {
+                    Error::Io {
+                        desc: "network content",
+                        op: "read",
+                        more: None,
+                    }
+                }));
+
+                saved_headers.clear();
+                for &(name, ref header) in &[("ETag", ETAG),
+                                             ("Last-Modified", LAST_MODIFIED),
+                                             ("Content-Type", CONTENT_TYPE),
+                                             ("Content-Disposition", CONTENT_DISPOSITION)] {
+                    if let Some(value) = resp.headers().get(header).and_then(|v| v.to_str().ok()) {
+                        saved_headers.push_str(&format!("{}: {}\n", name, value));
+                    }
+                }
+                try!(fs::write(&partial_headers, &saved_headers).map_err(|_| cache_error("write")));
+
+                let _ = fs::remove_file(cached.with_extension("headers"));
+                try!(fs::rename(&partial, cached).map_err(|_| cache_error("rename")));
+                fs::rename(&partial_headers, cached.with_extension("headers")).map_err(|_| cache_error("rename"))
+            };
+            if let Err(err) = write_cache() {
+                let _ = fs::remove_file(&partial);
+                let _ = fs::remove_file(&partial_headers);
+                return Err(err);
             }
-        }));
-        Ok(())
+        } else {
+            try!(io::copy(&mut resp, w).map_err(|_| {
+                Error::Io {
+                    desc: "network content",
+                    op: "read",
+                    more: None,
+                }
+            }));
+            return Ok(DownloadInfo::from_headers(resp.headers().get(CONTENT_TYPE).and_then(|v| v.to_str().ok()),
+                                                 resp.headers().get(CONTENT_DISPOSITION).and_then(|v| v.to_str().ok())));
+        }
+    }
+
+    try!(io::copy(&mut try!(File::open(cached.unwrap()).map_err(|_| cache_error("open"))), w).map_err(|_| cache_error("read")));
+    Ok(DownloadInfo::from_headers(saved_header(&saved_headers, "Content-Type"), saved_header(&saved_headers, "Content-Disposition")))
+}
+
+/// Decode the payload of the specified [`data:` URL](https://tools.ietf.org/html/rfc2397)
+/// and get its lowercase media type, without parameters.
+///
+/// The media type defaults to `text/plain`.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate gen_epub_book;
+/// # extern crate url;
+/// # fn main() {
+/// # use self::gen_epub_book::util::decode_data_url;
+/// # use self::url::Url;
+/// assert_eq!(decode_data_url(&Url::parse("data:image/SVG+xml;charset=utf-8,%3Csvg%2F%3E").unwrap()),
+///            Ok((b"<svg/>".to_vec(), "image/svg+xml".to_string())));
+/// assert_eq!(decode_data_url(&Url::parse("data:;base64,SGVsbG8sIFdvcmxkIQ==").unwrap()),
+///            Ok((b"Hello, World!".to_vec(), "text/plain".to_string())));
+/// assert!(decode_data_url(&Url::parse("data:text/plain").unwrap()).is_err());
+/// # }
+/// ```
+pub fn decode_data_url(url: &Url) -> Result<(Vec<u8>, String), Error> {
+    fn invalid(more: &'static str) -> Error {
+        Error::Parse {
+            tp: "data URL",
+            wher: "network content",
+            more: Some(more),
+        }
+    }
+
+    let url = &url.as_str()[url.scheme().len() + 1..];
+    let url = &url[..url.find('#').unwrap_or(url.len())];
+    let comma = try!(url.find(',').ok_or_else(|| invalid("no data")));
+
+    let mut header: Vec<_> = url[..comma].split(';').collect();
+    let base64 = header.len() > 1 && header.last().unwrap().trim().eq_ignore_ascii_case("base64");
+    if base64 {
+        header.pop();
+    }
+    let media_type = match header[0].trim() {
+        "" => "text/plain".to_string(),
+        media_type => percent_decode(media_type.as_bytes()).decode_utf8_lossy().to_lowercase(),
+    };
+
+    let data: Vec<_> = percent_decode(&url.as_bytes()[comma + 1..]).collect();
+    if base64 {
+        let data: Vec<_> = data.into_iter().filter(|b| !b.is_ascii_whitespace()).collect();
+        Ok((try!(base64::decode(&data).map_err(|_| invalid("invalid base64"))), media_type))
+    } else {
+        Ok((data, media_type))
+    }
+}
+
+/// Get the value of a header saved in a download cache `headers` file.
+fn saved_header<'s>(saved_headers: &'s str, name: &str) -> Option<&'s str> {
+    saved_headers.lines().find(|l| l.starts_with(name) && l[name.len()..].starts_with(": ")).map(|l| &l[name.len() + 2..])
+}
+
+
+static XHTML_NAMESPACE: &'static str = "http://www.w3.org/1999/xhtml";
+
+fn write_xhtml_node(node: &NodeRef, parent_namespace: &str, has_title: bool, title: &str, out: &mut String) {
+    lazy_static! {
+        static ref XML_NAME: Regex = Regex::new(r"^[a-zA-Z_][a-zA-Z0-9_.-]*$").unwrap();
+    }
+    static VOID_ELEMENTS: &'static [&'static str] = &["area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+                                                      "track", "wbr"];
+
+    match *node.data() {
+        NodeData::Element(ref element) => {
+            let name = &*element.name.local;
+            let namespace = &*element.name.ns;
+
+            out.push('<');
+            out.push_str(name);
+            if namespace != parent_namespace || name == "html" {
+                out.push_str(&format!(r#" xmlns="{}""#, xml_escape(namespace)));
+                if name == "svg" {
+                    out.push_str(r#" xmlns:xlink="http://www.w3.org/1999/xlink""#);
+                }
+            }
+            for (attr_name, attr) in &element.attributes.borrow().map {
+                let prefix = match &*attr_name.ns {
+                    "" => "",
+                    "http://www.w3.org/1999/xlink" => "xlink:",
+                    "http://www.w3.org/XML/1998/namespace" => "xml:",
+                    _ => continue,
+                };
+                if XML_NAME.is_match(&attr_name.local) && &*attr_name.local != "xmlns" {
+                    out.push_str(&format!(r#" {}{}="{}""#, prefix, &*attr_name.local, xml_escape(&attr.value)));
+                }
+            }
+
+            let inject_title = name == "head" && !has_title;
+            let empty_title = name == "title" && node.text_contents().trim().is_empty();
+            if node.first_child().is_none() && !inject_title && !empty_title &&
+               (namespace != XHTML_NAMESPACE || VOID_ELEMENTS.contains(&name)) {
+                out.push_str(" />");
+            } else {
+                out.push('>');
+                if inject_title {
+                    out.push_str(&format!("<title>{}</title>", xml_escape(title)));
+                }
+                if empty_title {
+                    out.push_str(&xml_escape(title));
+                } else {
+                    for child in node.children() {
+                        write_xhtml_node(&child, namespace, has_title, title, out);
+                    }
+                }
+                out.push_str(&format!("</{}>", name));
+            }
+        }
+        NodeData::Text(ref text) => out.push_str(&xml_escape(&text.borrow())),
+        NodeData::Comment(ref comment) => {
+            // XML comments can contain neither "--" nor a trailing "-", and a single pass leaves "--" in runs of three or more
+            let mut comment = comment.borrow().to_string();
+            while comment.contains("--") {
+                comment = comment.replace("--", "- -");
+            }
+            if comment.ends_with('-') {
+                comment.push(' ');
+            }
+            out.push_str(&format!("<!--{}-->", comment));
+        }
+        NodeData::Document(_) |
+        NodeData::DocumentFragment => {
+            for child in node.children() {
+                write_xhtml_node(&child, parent_namespace, has_title, title, out);
+            }
+        }
+        NodeData::ProcessingInstruction(_) |
+        NodeData::Doctype(_) => {}
     }
 }
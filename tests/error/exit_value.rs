@@ -74,3 +74,76 @@ fn wrong_element_amount() {
 fn required_element_missing() {
     assert_eq!(Error::RequiredElementMissing("").exit_value(), 6);
 }
+
+#[test]
+fn invalid_epub() {
+    assert_eq!(Error::InvalidEPub {
+                       file: "".to_string(),
+                       what: "",
+                       more: None,
+                   }
+                   .exit_value(),
+               7);
+    assert_eq!(Error::InvalidEPub {
+                       file: "".to_string(),
+                       what: "",
+                       more: Some("".to_string()),
+                   }
+                   .exit_value(),
+               7);
+}
+
+#[test]
+fn not_cached() {
+    assert_eq!(Error::NotCached { url: "https://example.com/cover.png".to_string() }.exit_value(), 8);
+}
+
+#[test]
+fn hash_mismatch() {
+    assert_eq!(Error::HashMismatch {
+                       url: "https://example.com/cover.png".to_string(),
+                       expected: "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad".to_string(),
+                       actual: "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_string(),
+                   }
+                   .exit_value(),
+               9);
+}
+
+#[test]
+fn unknown_element() {
+    assert_eq!(Error::UnknownElement {
+                       name: "Contnet".to_string(),
+                       suggestion: Some("Content"),
+                   }
+                   .exit_value(),
+               2);
+}
+
+#[test]
+fn undefined_variable() {
+    assert_eq!(Error::UndefinedVariable { name: "VERSION".to_string() }.exit_value(), 2);
+}
+
+#[test]
+fn include_cycle() {
+    assert_eq!(Error::IncludeCycle { chain: vec!["a.epupp".to_string(), "a.epupp".to_string()] }.exit_value(), 10);
+}
+
+#[test]
+fn descriptor() {
+    assert_eq!(Error::Descriptor {
+                       descriptor: "book.epupp".to_string(),
+                       line: 1,
+                       column: 7,
+                       text: "Date: yesterday".to_string(),
+                       key: "Date".to_string(),
+                       value: "yesterday".to_string(),
+                       error: Box::new(Error::Parse {
+                           tp: "datetime",
+                           wher: "book element",
+                           more: Some("not RFC3339"),
+                       }),
+                   }
+                   .exit_value(),
+               2);
+}
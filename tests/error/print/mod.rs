@@ -50,3 +50,181 @@ fn required_element_missing() {
     assert_eq!(out.iter().map(|&i| i as char).collect::<String>(),
                "Required element Name not specified.\n".to_string());
 }
+
+#[test]
+fn invalid_epub() {
+    let mut out = Vec::new();
+    Error::InvalidEPub {
+            file: "mimetype".to_string(),
+            what: "not the first entry",
+            more: None,
+        }
+        .print_error(&mut out);
+    assert_eq!(out.iter().map(|&i| i as char).collect::<String>(),
+               "Invalid ePub: mimetype not the first entry.\n".to_string());
+
+    let mut out = Vec::new();
+    Error::InvalidEPub {
+            file: "content.opf".to_string(),
+            what: "references missing file",
+            more: Some("ctnt.html".to_string()),
+        }
+        .print_error(&mut out);
+    assert_eq!(out.iter().map(|&i| i as char).collect::<String>(),
+               "Invalid ePub: content.opf references missing file: ctnt.html.\n".to_string());
+}
+
+#[test]
+fn not_cached() {
+    let mut out = Vec::new();
+    Error::NotCached { url: "https://example.com/cover.png".to_string() }.print_error(&mut out);
+    assert_eq!(out.iter().map(|&i| i as char).collect::<String>(),
+               "https://example.com/cover.png not cached, but offline.\n".to_string());
+}
+
+#[test]
+fn hash_mismatch() {
+    let mut out = Vec::new();
+    Error::HashMismatch {
+            url: "https://example.com/cover.png".to_string(),
+            expected: "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad".to_string(),
+            actual: "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_string(),
+        }
+        .print_error(&mut out);
+    assert_eq!(out.iter().map(|&i| i as char).collect::<String>(),
+               "https://example.com/cover.png has SHA-256 hash e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855, \
+                but ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad was expected.\n"
+                   .to_string());
+}
+
+#[test]
+fn unknown_element() {
+    let mut out = Vec::new();
+    Error::UnknownElement {
+            name: "Contnet".to_string(),
+            suggestion: Some("Content"),
+        }
+        .print_error(&mut out);
+    assert_eq!(out.iter().map(|&i| i as char).collect::<String>(),
+               "Unknown element Contnet, did you mean Content?\n".to_string());
+}
+
+#[test]
+fn unknown_element_no_suggestion() {
+    let mut out = Vec::new();
+    Error::UnknownElement {
+            name: "Title".to_string(),
+            suggestion: None,
+        }
+        .print_error(&mut out);
+    assert_eq!(out.iter().map(|&i| i as char).collect::<String>(), "Unknown element Title.\n".to_string());
+}
+
+#[test]
+fn descriptor() {
+    let mut out = Vec::new();
+    Error::Descriptor {
+            descriptor: "book.epupp".to_string(),
+            line: 12,
+            column: 9,
+            text: "Date :  yesterday  ".to_string(),
+            key: "Date".to_string(),
+            value: "yesterday".to_string(),
+            error: Box::new(Error::Parse {
+                tp: "datetime",
+                wher: "book element",
+                more: Some("not RFC3339"),
+            }),
+        }
+        .print_error(&mut out);
+    assert_eq!(out.iter().map(|&i| i as char).collect::<String>(),
+               "book.epupp:12: Failed to parse datetime for book element: not RFC3339.\nDate :  yesterday  \n        ^~~~~~~~~\n".to_string());
+}
+
+#[test]
+fn descriptor_tabs() {
+    let mut out = Vec::new();
+    Error::Descriptor {
+            descriptor: "stdin".to_string(),
+            line: 3,
+            column: 9,
+            text: "\tCover:\ti.png".to_string(),
+            key: "Cover".to_string(),
+            value: "i.png".to_string(),
+            error: Box::new(Error::FileNotFound {
+                who: "Cover",
+                path: "i.png".into(),
+            }),
+        }
+        .print_error(&mut out);
+    assert_eq!(out.iter().map(|&i| i as char).collect::<String>(),
+               "stdin:3: File i.png for Cover not found.\n\tCover:\ti.png\n\t      \t^~~~~\n".to_string());
+}
+
+#[test]
+fn descriptor_unknown_key() {
+    let mut out = Vec::new();
+    Error::Descriptor {
+            descriptor: "book.epupp".to_string(),
+            line: 4,
+            column: 3,
+            text: "  Contnet: ch3.html".to_string(),
+            key: "Contnet".to_string(),
+            value: "ch3.html".to_string(),
+            error: Box::new(Error::UnknownElement {
+                name: "Contnet".to_string(),
+                suggestion: Some("Content"),
+            }),
+        }
+        .print_error(&mut out);
+    assert_eq!(out.iter().map(|&i| i as char).collect::<String>(),
+               "book.epupp:4: Unknown element Contnet, did you mean Content?\n  Contnet: ch3.html\n  ^~~~~~~\n".to_string());
+}
+
+#[test]
+fn undefined_variable() {
+    let mut out = Vec::new();
+    Error::UndefinedVariable { name: "VERSION".to_string() }.print_error(&mut out);
+    assert_eq!(out.iter().map(|&i| i as char).collect::<String>(), "Variable VERSION not defined.\n".to_string());
+}
+
+#[test]
+fn include_cycle() {
+    let mut out = Vec::new();
+    Error::IncludeCycle { chain: vec!["a.epupp".to_string(), "b.epupp".to_string(), "a.epupp".to_string()] }.print_error(&mut out);
+    assert_eq!(out.iter().map(|&i| i as char).collect::<String>(),
+               "Descriptor include cycle: a.epupp -> b.epupp -> a.epupp.\n".to_string());
+}
+
+#[test]
+fn descriptor_included() {
+    let mut out = Vec::new();
+    Error::Descriptor {
+            descriptor: "book.epupp".to_string(),
+            line: 3,
+            column: 21,
+            text: "Descriptor-Include: common.epupp".to_string(),
+            key: "Descriptor-Include".to_string(),
+            value: "common.epupp".to_string(),
+            error: Box::new(Error::Descriptor {
+                descriptor: "common.epupp".to_string(),
+                line: 2,
+                column: 7,
+                text: "Date: yesterday".to_string(),
+                key: "Date".to_string(),
+                value: "yesterday".to_string(),
+                error: Box::new(Error::Parse {
+                    tp: "datetime",
+                    wher: "book element",
+                    more: Some("not RFC3339"),
+                }),
+            }),
+        }
+        .print_error(&mut out);
+    assert_eq!(out.iter().map(|&i| i as char).collect::<String>(),
+               "In descriptor included from book.epupp:3:\n\
+                common.epupp:2: Failed to parse datetime for book element: not RFC3339.\n\
+                Date: yesterday\n      \
+                ^~~~~~~~~\n"
+                   .to_string());
+}
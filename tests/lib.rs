@@ -1,6 +1,8 @@
 extern crate gen_epub_book;
 extern crate chrono;
 extern crate url;
+extern crate uuid;
+extern crate zip;
 
 mod error;
 mod util;
@@ -7,13 +7,13 @@ use url::Url;
 #[test]
 fn no_cover() {
     let book = EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
-                                            BookElement::Author("nabijaczleweli".to_string()),
+                                            BookElement::Author("nabijaczleweli".to_string(), None),
                                             BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
                                             BookElement::Language("en-GB".to_string())])
         .unwrap();
 
     assert_eq!(book.name, "Simple ePub demonstration".to_string());
-    assert_eq!(book.author, "nabijaczleweli".to_string());
+    assert_eq!(book.authors, vec![("nabijaczleweli".to_string(), None, "aut".to_string())]);
     assert_eq!(book.date, DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap());
     assert_eq!(book.language, "en-GB".to_string());
     assert_eq!(book.cover, None);
@@ -22,14 +22,14 @@ fn no_cover() {
 #[test]
 fn file_cover() {
     let book = EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
-                                            BookElement::Author("nabijaczleweli".to_string()),
+                                            BookElement::Author("nabijaczleweli".to_string(), None),
                                             BookElement::Cover(PathBuf::from("examples/cover.png")),
                                             BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
                                             BookElement::Language("en-GB".to_string())])
         .unwrap();
 
     assert_eq!(book.name, "Simple ePub demonstration".to_string());
-    assert_eq!(book.author, "nabijaczleweli".to_string());
+    assert_eq!(book.authors, vec![("nabijaczleweli".to_string(), None, "aut".to_string())]);
     assert_eq!(book.date, DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap());
     assert_eq!(book.language, "en-GB".to_string());
     assert_eq!(book.cover,
@@ -41,14 +41,14 @@ fn file_cover() {
 #[test]
 fn network_cover() {
     let book = EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
-                                            BookElement::Author("nabijaczleweli".to_string()),
-                                            BookElement::NetworkCover(Url::parse("http://i.imgur.com/ViQ2WED.jpg").unwrap()),
+                                            BookElement::Author("nabijaczleweli".to_string(), None),
+                                            BookElement::NetworkCover(Url::parse("http://i.imgur.com/ViQ2WED.jpg").unwrap(), None),
                                             BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
                                             BookElement::Language("en-GB".to_string())])
         .unwrap();
 
     assert_eq!(book.name, "Simple ePub demonstration".to_string());
-    assert_eq!(book.author, "nabijaczleweli".to_string());
+    assert_eq!(book.authors, vec![("nabijaczleweli".to_string(), None, "aut".to_string())]);
     assert_eq!(book.date, DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap());
     assert_eq!(book.language, "en-GB".to_string());
     assert_eq!(book.cover,
@@ -56,3 +56,77 @@ fn network_cover() {
                      PathBuf::from("network-cover-data-2.html"),
                      EPubContentType::Raw(r#"<center><img src="ViQ2WED.jpg" alt="ViQ2WED.jpg"></img></center>"#.to_string()))));
 }
+
+#[test]
+fn file_cover_escaped() {
+    let book = EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
+                                            BookElement::Author("nabijaczleweli".to_string(), None),
+                                            BookElement::Cover(PathBuf::from("covers/\"Tom\" & Jerry.png")),
+                                            BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
+                                            BookElement::Language("en-GB".to_string())])
+        .unwrap();
+
+    assert_eq!(book.cover,
+               Some(("cover-content-2".to_string(),
+                     PathBuf::from("cover-data-2.html"),
+                     EPubContentType::Raw(r#"<center><img src="covers-&quot;Tom&quot; &amp; Jerry.png" alt="covers-&quot;Tom&quot; &amp; Jerry.png"></img></center>"#
+                         .to_string()))));
+}
+
+#[test]
+fn network_hashes() {
+    let book = EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
+                                            BookElement::Author("nabijaczleweli".to_string(), None),
+                                            BookElement::NetworkCover(Url::parse("http://i.imgur.com/ViQ2WED.jpg").unwrap(),
+                                                                      Some("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad".to_string())),
+                                            BookElement::NetworkInclude(Url::parse("https://example.com/style.css").unwrap(), None),
+                                            BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
+                                            BookElement::Language("en-GB".to_string())])
+        .unwrap();
+
+    assert_eq!(book.network_hashes.into_iter().collect::<Vec<_>>(),
+               vec![(Url::parse("http://i.imgur.com/ViQ2WED.jpg").unwrap(), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad".to_string())]);
+}
+
+#[test]
+fn creators() {
+    let book = EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
+                                            BookElement::Author("nabijaczleweli".to_string(), None),
+                                            BookElement::Contributor("trl".to_string(), "Anthea Bell".to_string(), Some("Bell, Anthea".to_string())),
+                                            BookElement::Author("Terry Pratchett".to_string(), Some("Pratchett, Terry".to_string())),
+                                            BookElement::Contributor("ill".to_string(), "nicohman".to_string(), None),
+                                            BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
+                                            BookElement::Language("en-GB".to_string())])
+        .unwrap();
+
+    assert_eq!(book.authors,
+               vec![("nabijaczleweli".to_string(), None, "aut".to_string()),
+                    ("Terry Pratchett".to_string(), Some("Pratchett, Terry".to_string()), "aut".to_string())]);
+    assert_eq!(book.contributors,
+               vec![("Anthea Bell".to_string(), Some("Bell, Anthea".to_string()), "trl".to_string()),
+                    ("nicohman".to_string(), None, "ill".to_string())]);
+}
+
+#[test]
+fn metadata() {
+    let book = EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
+                                            BookElement::Author("nabijaczleweli".to_string(), None),
+                                            BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
+                                            BookElement::Language("en-GB".to_string()),
+                                            BookElement::Subject("Fantasy".to_string()),
+                                            BookElement::Identifier("DOI".to_string(), "10.1000/182".to_string()),
+                                            BookElement::Publisher("Tom & Jerry Ltd.".to_string()),
+                                            BookElement::Subject("Humour".to_string()),
+                                            BookElement::Isbn("9780306406157".to_string()),
+                                            BookElement::SeriesIndex("2.5".to_string()),
+                                            BookElement::Series("Discworld".to_string())])
+        .unwrap();
+
+    assert_eq!(book.publisher, Some("Tom & Jerry Ltd.".to_string()));
+    assert_eq!(book.rights, None);
+    assert_eq!(book.subjects, vec!["Fantasy".to_string(), "Humour".to_string()]);
+    assert_eq!(book.identifiers,
+               vec![("DOI".to_string(), "10.1000/182".to_string()), ("ISBN".to_string(), "9780306406157".to_string())]);
+    assert_eq!(book.modified, None);
+    assert_eq!(book.series, Some(("Discworld".to_string(), Some("2.5".to_string()))));
+}
@@ -8,7 +8,7 @@ use url::Url;
 #[test]
 fn two_file() {
     assert_eq!(EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
-                                            BookElement::Author("nabijaczleweli".to_string()),
+                                            BookElement::Author("nabijaczleweli".to_string(), None),
                                             BookElement::Cover(PathBuf::from("examples/cover.png")),
                                             BookElement::Cover(PathBuf::from("examples/cover.png")),
                                             BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
@@ -24,9 +24,9 @@ fn two_file() {
 #[test]
 fn two_network() {
     assert_eq!(EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
-                                            BookElement::Author("nabijaczleweli".to_string()),
-                                            BookElement::NetworkCover(Url::parse("http://i.imgur.com/ViQ2WED.jpg").unwrap()),
-                                            BookElement::NetworkCover(Url::parse("http://i.imgur.com/ViQ2WED.jpg").unwrap()),
+                                            BookElement::Author("nabijaczleweli".to_string(), None),
+                                            BookElement::NetworkCover(Url::parse("http://i.imgur.com/ViQ2WED.jpg").unwrap(), None),
+                                            BookElement::NetworkCover(Url::parse("http://i.imgur.com/ViQ2WED.jpg").unwrap(), None),
                                             BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
                                             BookElement::Language("en-GB".to_string())]),
                Err(Error::WrongElementAmount {
@@ -40,9 +40,9 @@ fn two_network() {
 #[test]
 fn mixed_file_first() {
     assert_eq!(EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
-                                            BookElement::Author("nabijaczleweli".to_string()),
+                                            BookElement::Author("nabijaczleweli".to_string(), None),
                                             BookElement::Cover(PathBuf::from("examples/cover.png")),
-                                            BookElement::NetworkCover(Url::parse("http://i.imgur.com/ViQ2WED.jpg").unwrap()),
+                                            BookElement::NetworkCover(Url::parse("http://i.imgur.com/ViQ2WED.jpg").unwrap(), None),
                                             BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
                                             BookElement::Language("en-GB".to_string())]),
                Err(Error::WrongElementAmount {
@@ -56,8 +56,8 @@ fn mixed_file_first() {
 #[test]
 fn mixed_network_first() {
     assert_eq!(EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
-                                            BookElement::Author("nabijaczleweli".to_string()),
-                                            BookElement::NetworkCover(Url::parse("http://i.imgur.com/ViQ2WED.jpg").unwrap()),
+                                            BookElement::Author("nabijaczleweli".to_string(), None),
+                                            BookElement::NetworkCover(Url::parse("http://i.imgur.com/ViQ2WED.jpg").unwrap(), None),
                                             BookElement::Cover(PathBuf::from("examples/cover.png")),
                                             BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
                                             BookElement::Language("en-GB".to_string())]),
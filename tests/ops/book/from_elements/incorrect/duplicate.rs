@@ -1,13 +1,14 @@
 use gen_epub_book::ops::{BookElement, EPubBook};
 use gen_epub_book::Error;
 use chrono::DateTime;
+use uuid::Uuid;
 
 
 #[test]
 fn name() {
     assert_eq!(EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
                                             BookElement::Name("Simple ePub demonstration".to_string()),
-                                            BookElement::Author("nabijaczleweli".to_string()),
+                                            BookElement::Author("nabijaczleweli".to_string(), None),
                                             BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
                                             BookElement::Language("en-GB".to_string())]),
                Err(Error::WrongElementAmount {
@@ -20,14 +21,14 @@ fn name() {
 
 
 #[test]
-fn author() {
+fn date() {
     assert_eq!(EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
-                                            BookElement::Author("nabijaczleweli".to_string()),
-                                            BookElement::Author("nabijaczleweli".to_string()),
+                                            BookElement::Author("nabijaczleweli".to_string(), None),
+                                            BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
                                             BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
                                             BookElement::Language("en-GB".to_string())]),
                Err(Error::WrongElementAmount {
-                   element: "Author",
+                   element: "Date",
                    actual: 2,
                    relation: "exactly",
                    bound: 1,
@@ -35,11 +36,25 @@ fn author() {
 }
 
 #[test]
-fn date() {
+fn language() {
     assert_eq!(EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
-                                            BookElement::Author("nabijaczleweli".to_string()),
+                                            BookElement::Author("nabijaczleweli".to_string(), None),
                                             BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
+                                            BookElement::Language("en-GB".to_string()),
+                                            BookElement::Language("en-GB".to_string())]),
+               Err(Error::WrongElementAmount {
+                   element: "Language",
+                   actual: 2,
+                   relation: "exactly",
+                   bound: 1,
+               }));
+}
+
+#[test]
+fn ordering() {
+    assert_eq!(EPubBook::from_elements(vec![BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
                                             BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
+                                            BookElement::Language("en-GB".to_string()),
                                             BookElement::Language("en-GB".to_string())]),
                Err(Error::WrongElementAmount {
                    element: "Date",
@@ -50,14 +65,15 @@ fn date() {
 }
 
 #[test]
-fn language() {
+fn uuid() {
     assert_eq!(EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
-                                            BookElement::Author("nabijaczleweli".to_string()),
+                                            BookElement::Uuid(Uuid::parse_str("2d3b2c9b-0a7e-4c0b-9a5e-5f2f4f1d6c3a").unwrap()),
+                                            BookElement::Author("nabijaczleweli".to_string(), None),
                                             BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
                                             BookElement::Language("en-GB".to_string()),
-                                            BookElement::Language("en-GB".to_string())]),
+                                            BookElement::Uuid(Uuid::parse_str("2d3b2c9b-0a7e-4c0b-9a5e-5f2f4f1d6c3a").unwrap())]),
                Err(Error::WrongElementAmount {
-                   element: "Language",
+                   element: "UUID",
                    actual: 2,
                    relation: "exactly",
                    bound: 1,
@@ -65,13 +81,31 @@ fn language() {
 }
 
 #[test]
-fn ordering() {
-    assert_eq!(EPubBook::from_elements(vec![BookElement::Author("nabijaczleweli".to_string()),
-                                            BookElement::Author("nabijaczleweli".to_string()),
+fn isbn() {
+    assert_eq!(EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
+                                            BookElement::Author("nabijaczleweli".to_string(), None),
+                                            BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
                                             BookElement::Language("en-GB".to_string()),
-                                            BookElement::Language("en-GB".to_string())]),
+                                            BookElement::Isbn("9780306406157".to_string()),
+                                            BookElement::Isbn("080442957X".to_string())]),
+               Err(Error::WrongElementAmount {
+                   element: "ISBN",
+                   actual: 2,
+                   relation: "exactly",
+                   bound: 1,
+               }));
+}
+
+#[test]
+fn series() {
+    assert_eq!(EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
+                                            BookElement::Author("nabijaczleweli".to_string(), None),
+                                            BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
+                                            BookElement::Language("en-GB".to_string()),
+                                            BookElement::Series("Discworld".to_string()),
+                                            BookElement::Series("Good Omens".to_string())]),
                Err(Error::WrongElementAmount {
-                   element: "Author",
+                   element: "Series",
                    actual: 2,
                    relation: "exactly",
                    bound: 1,
@@ -5,7 +5,7 @@ use chrono::DateTime;
 
 #[test]
 fn name() {
-    assert_eq!(EPubBook::from_elements(vec![BookElement::Author("nabijaczleweli".to_string()),
+    assert_eq!(EPubBook::from_elements(vec![BookElement::Author("nabijaczleweli".to_string(), None),
                                             BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
                                             BookElement::Language("en-GB".to_string())]),
                Err(Error::RequiredElementMissing("Name")));
@@ -18,12 +18,17 @@ fn author() {
                                             BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
                                             BookElement::Language("en-GB".to_string())]),
                Err(Error::RequiredElementMissing("Author")));
+    assert_eq!(EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
+                                            BookElement::Contributor("trl".to_string(), "nabijaczleweli".to_string(), None),
+                                            BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
+                                            BookElement::Language("en-GB".to_string())]),
+               Err(Error::RequiredElementMissing("Author")));
 }
 
 #[test]
 fn date() {
     assert_eq!(EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
-                                            BookElement::Author("nabijaczleweli".to_string()),
+                                            BookElement::Author("nabijaczleweli".to_string(), None),
                                             BookElement::Language("en-GB".to_string())]),
                Err(Error::RequiredElementMissing("Date")));
 }
@@ -31,7 +36,7 @@ fn date() {
 #[test]
 fn language() {
     assert_eq!(EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
-                                            BookElement::Author("nabijaczleweli".to_string()),
+                                            BookElement::Author("nabijaczleweli".to_string(), None),
                                             BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap())]),
                Err(Error::RequiredElementMissing("Language")));
 }
@@ -41,10 +46,21 @@ fn ordering() {
     assert_eq!(EPubBook::from_elements(vec![]), Err(Error::RequiredElementMissing("Name")));
     assert_eq!(EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string())]),
                Err(Error::RequiredElementMissing("Author")));
-    assert_eq!(EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()), BookElement::Author("nabijaczleweli".to_string())]),
+    assert_eq!(EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
+                                            BookElement::Author("nabijaczleweli".to_string(), None)]),
                Err(Error::RequiredElementMissing("Date")));
     assert_eq!(EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
-                                            BookElement::Author("nabijaczleweli".to_string()),
+                                            BookElement::Author("nabijaczleweli".to_string(), None),
                                             BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap())]),
                Err(Error::RequiredElementMissing("Language")));
 }
+
+#[test]
+fn series() {
+    assert_eq!(EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
+                                            BookElement::Author("nabijaczleweli".to_string(), None),
+                                            BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
+                                            BookElement::Language("en-GB".to_string()),
+                                            BookElement::SeriesIndex("2".to_string())]),
+               Err(Error::RequiredElementMissing("Series")));
+}
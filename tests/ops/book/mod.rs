@@ -1,2 +1,3 @@
 mod normalise_paths;
 mod from_elements;
+mod write_zip_ext;
@@ -18,7 +18,7 @@ fn correct() {
 
     let mut buf = vec![];
     let mut book = EPubBook::from_elements(vec![BookElement::Name("".to_string()),
-                                                BookElement::Author("".to_string()),
+                                                BookElement::Author("".to_string(), None),
                                                 BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
                                                 BookElement::Language("".to_string()),
                                                 BookElement::Content(PathBuf::from("ch01.html")),
@@ -51,7 +51,7 @@ fn nonexistant() {
 
     let mut buf = vec![];
     let mut book = EPubBook::from_elements(vec![BookElement::Name("".to_string()),
-                                                BookElement::Author("".to_string()),
+                                                BookElement::Author("".to_string(), None),
                                                 BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
                                                 BookElement::Language("".to_string()),
                                                 BookElement::Cover(PathBuf::from("cover.png"))])
@@ -78,7 +78,7 @@ fn bad_type() {
 
     let mut buf = vec![];
     let mut book = EPubBook::from_elements(vec![BookElement::Name("".to_string()),
-                                                BookElement::Author("".to_string()),
+                                                BookElement::Author("".to_string(), None),
                                                 BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
                                                 BookElement::Language("".to_string()),
                                                 BookElement::Cover(PathBuf::from("cover.png"))])
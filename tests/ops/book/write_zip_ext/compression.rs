@@ -0,0 +1,62 @@
+use self::super::{simple_book, read_file, write};
+use gen_epub_book::ops::{Compression, BookElement, EPubBook};
+use zip::CompressionMethod;
+use std::path::PathBuf;
+use chrono::DateTime;
+
+
+fn image_book() -> EPubBook {
+    EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
+                                 BookElement::ImageContent(PathBuf::from("examples/simple/chapter_image.png")),
+                                 BookElement::Author("nabijaczleweli".to_string(), None),
+                                 BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
+                                 BookElement::Language("en-GB".to_string())])
+        .unwrap()
+}
+
+#[test]
+fn mimetype_first_stored() {
+    for &compression in &[Compression::Stored, Compression::Deflated] {
+        let mut book = simple_book();
+        book.compression = compression;
+        book.media_compression = compression;
+        let mut zip = write(&book);
+
+        {
+            let mimetype = zip.by_index(0).unwrap();
+            assert_eq!(mimetype.name(), "mimetype");
+            assert_eq!(mimetype.compression(), CompressionMethod::Stored);
+        }
+        assert_eq!(read_file(&mut zip, "mimetype"), Some("application/epub+zip".to_string()));
+    }
+}
+
+#[test]
+fn default() {
+    let mut zip = write(&image_book());
+    assert_eq!(zip.by_name("content.opf").unwrap().compression(), CompressionMethod::Deflated);
+    assert_eq!(zip.by_name("image-data-1.html").unwrap().compression(), CompressionMethod::Deflated);
+    assert_eq!(zip.by_name("examples-simple-chapter_image.png").unwrap().compression(), CompressionMethod::Deflated);
+}
+
+#[test]
+fn store_media() {
+    let mut book = image_book();
+    book.media_compression = Compression::Stored;
+    let mut zip = write(&book);
+    assert_eq!(zip.by_name("content.opf").unwrap().compression(), CompressionMethod::Deflated);
+    assert_eq!(zip.by_name("image-data-1.html").unwrap().compression(), CompressionMethod::Deflated);
+    assert_eq!(zip.by_name("examples-simple-chapter_image.png").unwrap().compression(), CompressionMethod::Stored);
+}
+
+#[test]
+fn store_text() {
+    let mut book = image_book();
+    book.compression = Compression::Stored;
+    let mut zip = write(&book);
+    assert_eq!(zip.by_name("META-INF/container.xml").unwrap().compression(), CompressionMethod::Stored);
+    assert_eq!(zip.by_name("content.opf").unwrap().compression(), CompressionMethod::Stored);
+    assert_eq!(zip.by_name("toc.ncx").unwrap().compression(), CompressionMethod::Stored);
+    assert_eq!(zip.by_name("image-data-1.html").unwrap().compression(), CompressionMethod::Stored);
+    assert_eq!(zip.by_name("examples-simple-chapter_image.png").unwrap().compression(), CompressionMethod::Deflated);
+}
@@ -0,0 +1,48 @@
+use gen_epub_book::ops::{BookElement, EPubBook, EPubVersion, check_epub};
+use self::super::read_file;
+use std::io::Cursor;
+use chrono::DateTime;
+use zip::ZipArchive;
+
+
+fn creators_book(version: EPubVersion) -> ZipArchive<Cursor<Vec<u8>>> {
+    let mut book = EPubBook::from_elements(vec![BookElement::Name("Creators demonstration".to_string()),
+                                                BookElement::Author("Terry Pratchett".to_string(), Some("Pratchett, Terry".to_string())),
+                                                BookElement::Author("Neil Gaiman".to_string(), None),
+                                                BookElement::Contributor("ill".to_string(), "Tom & Jerry".to_string(), None),
+                                                BookElement::Contributor("trl".to_string(), "Anthea Bell".to_string(), Some("Bell, Anthea".to_string())),
+                                                BookElement::Date(DateTime::parse_from_rfc3339("2018-07-20T11:02:43+02:00").unwrap()),
+                                                BookElement::Language("en-GB".to_string())])
+        .unwrap();
+    book.version = version;
+
+    let mut buf = Cursor::new(vec![]);
+    book.write_zip(&mut buf, false, &mut vec![]).unwrap();
+    assert_eq!(check_epub(&mut buf, false, &mut vec![]), Ok(()));
+    ZipArchive::new(Cursor::new(buf.into_inner())).unwrap()
+}
+
+#[test]
+fn v2() {
+    let opf = read_file(&mut creators_book(EPubVersion::V2), "content.opf").unwrap();
+    assert!(opf.contains(r#"<dc:creator opf:role="aut" opf:file-as="Pratchett, Terry">Terry Pratchett</dc:creator>
+    <dc:creator opf:role="aut">Neil Gaiman</dc:creator>
+    <dc:contributor opf:role="ill">Tom &amp; Jerry</dc:contributor>
+    <dc:contributor opf:role="trl" opf:file-as="Bell, Anthea">Anthea Bell</dc:contributor>"#));
+}
+
+#[test]
+fn v3() {
+    let opf = read_file(&mut creators_book(EPubVersion::V3), "content.opf").unwrap();
+    assert!(opf.contains(r##"<dc:creator id="creator-1">Terry Pratchett</dc:creator>
+    <meta refines="#creator-1" property="role" scheme="marc:relators">aut</meta>
+    <meta refines="#creator-1" property="file-as">Pratchett, Terry</meta>
+    <dc:creator id="creator-2">Neil Gaiman</dc:creator>
+    <meta refines="#creator-2" property="role" scheme="marc:relators">aut</meta>
+    <dc:contributor id="contributor-1">Tom &amp; Jerry</dc:contributor>
+    <meta refines="#contributor-1" property="role" scheme="marc:relators">ill</meta>
+    <dc:contributor id="contributor-2">Anthea Bell</dc:contributor>
+    <meta refines="#contributor-2" property="role" scheme="marc:relators">trl</meta>
+    <meta refines="#contributor-2" property="file-as">Bell, Anthea</meta>"##));
+    assert!(!opf.contains("opf:"));
+}
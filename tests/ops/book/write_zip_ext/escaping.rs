@@ -0,0 +1,40 @@
+use gen_epub_book::ops::{EPubVersion, BookElement, EPubBook};
+use self::super::{read_file, write};
+use chrono::DateTime;
+
+
+fn special_book() -> EPubBook {
+    EPubBook::from_elements(vec![BookElement::Name("Tom & Jerry <Redux>".to_string()),
+                                 BookElement::StringContent(r#"<!-- ePub title: "Cat & Mouse <1>" -->Chapter text"#.to_string()),
+                                 BookElement::StringDescription("<em>Very</em> & truly".to_string()),
+                                 BookElement::Author("Hanna & Barbera".to_string(), None),
+                                 BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
+                                 BookElement::Language("en-GB".to_string())])
+        .unwrap()
+}
+
+#[test]
+fn content_table() {
+    let opf = read_file(&mut write(&special_book()), "content.opf").unwrap();
+    assert!(opf.contains("<dc:title>Tom &amp; Jerry &lt;Redux&gt;</dc:title>"));
+    assert!(opf.contains(r#"<dc:creator opf:role="aut">Hanna &amp; Barbera</dc:creator>"#));
+    assert!(opf.contains("&lt;em&gt;Very&lt;/em&gt; &amp; truly"));
+    assert!(!opf.contains("Tom & Jerry"));
+}
+
+#[test]
+fn table_of_contents() {
+    let ncx = read_file(&mut write(&special_book()), "toc.ncx").unwrap();
+    assert!(ncx.contains("<text>Tom &amp; Jerry &lt;Redux&gt;</text>"));
+    assert!(ncx.contains("<text>Cat &amp; Mouse &lt;1&gt;</text>"));
+}
+
+#[test]
+fn navigation_document() {
+    let mut book = special_book();
+    book.version = EPubVersion::V3;
+
+    let nav = read_file(&mut write(&book), "nav.xhtml").unwrap();
+    assert!(nav.contains("<title>Tom &amp; Jerry &lt;Redux&gt;</title>"));
+    assert!(nav.contains(r#"<a href="string-data-1.html">Cat &amp; Mouse &lt;1&gt;</a>"#));
+}
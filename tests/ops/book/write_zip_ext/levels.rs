@@ -0,0 +1,115 @@
+use gen_epub_book::ops::{EPubVersion, BookElement, EPubBook};
+use self::super::{read_file, write};
+use chrono::DateTime;
+
+
+fn levelled_book(levels: &[usize]) -> EPubBook {
+    let mut elems = vec![BookElement::Name("Levelled ePub demonstration".to_string()),
+                         BookElement::Author("nabijaczleweli".to_string(), None),
+                         BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
+                         BookElement::Language("en-GB".to_string())];
+    elems.extend(levels.iter()
+        .enumerate()
+        .map(|(i, level)| BookElement::StringContent(format!(r#"<!-- ePub title: "Entry {}" level: {} -->Text"#, i + 1, level))));
+
+    let mut book = EPubBook::from_elements(elems).unwrap();
+    book.version = EPubVersion::V3;
+    book
+}
+
+/// Get the non-empty lines of the specified file between the lines containing the specified markers, trimmed.
+fn lines_between(file: &str, from: &str, to: &str) -> Vec<String> {
+    file.lines()
+        .skip_while(|l| !l.contains(from))
+        .skip(1)
+        .take_while(|l| !l.contains(to))
+        .map(|l| l.trim().to_string())
+        .collect()
+}
+
+#[test]
+fn flat() {
+    let mut zip = write(&levelled_book(&[1, 1]));
+
+    let ncx = read_file(&mut zip, "toc.ncx").unwrap();
+    assert!(ncx.contains(r#"<meta content="1" name="dtb:depth"/>"#));
+    assert_eq!(lines_between(&ncx, "<navMap>", "</navMap>")
+                   .into_iter()
+                   .filter(|l| l.contains("navPoint"))
+                   .collect::<Vec<_>>(),
+               vec![r#"<navPoint id="navpoint-1" playOrder="1">"#,
+                    "</navPoint>",
+                    r#"<navPoint id="navpoint-2" playOrder="2">"#,
+                    "</navPoint>"]);
+
+    let nav = read_file(&mut zip, "nav.xhtml").unwrap();
+    assert_eq!(lines_between(&nav, "<h1>", "</nav>"),
+               vec!["<ol>",
+                    r#"<li><a href="string-data-4.html">Entry 1</a></li>"#,
+                    r#"<li><a href="string-data-5.html">Entry 2</a></li>"#,
+                    "</ol>"]);
+}
+
+#[test]
+fn nested() {
+    let mut zip = write(&levelled_book(&[1, 2, 3, 2, 1, 2]));
+
+    let ncx = read_file(&mut zip, "toc.ncx").unwrap();
+    assert!(ncx.contains(r#"<meta content="3" name="dtb:depth"/>"#));
+    assert_eq!(lines_between(&ncx, "<navMap>", "</navMap>")
+                   .into_iter()
+                   .filter(|l| l.contains("navPoint"))
+                   .collect::<Vec<_>>(),
+               vec![r#"<navPoint id="navpoint-1" playOrder="1">"#,
+                    r#"<navPoint id="navpoint-2" playOrder="2">"#,
+                    r#"<navPoint id="navpoint-3" playOrder="3">"#,
+                    "</navPoint>",
+                    "</navPoint>",
+                    r#"<navPoint id="navpoint-4" playOrder="4">"#,
+                    "</navPoint>",
+                    "</navPoint>",
+                    r#"<navPoint id="navpoint-5" playOrder="5">"#,
+                    r#"<navPoint id="navpoint-6" playOrder="6">"#,
+                    "</navPoint>",
+                    "</navPoint>"]);
+    assert!(ncx.contains("\n        <navPoint id=\"navpoint-3\" playOrder=\"3\">\n"));
+
+    let nav = read_file(&mut zip, "nav.xhtml").unwrap();
+    assert_eq!(lines_between(&nav, "<h1>", "</nav>"),
+               vec!["<ol>",
+                    r#"<li><a href="string-data-4.html">Entry 1</a>"#,
+                    "<ol>",
+                    r#"<li><a href="string-data-5.html">Entry 2</a>"#,
+                    "<ol>",
+                    r#"<li><a href="string-data-6.html">Entry 3</a></li>"#,
+                    "</ol>",
+                    "</li>",
+                    r#"<li><a href="string-data-7.html">Entry 4</a></li>"#,
+                    "</ol>",
+                    "</li>",
+                    r#"<li><a href="string-data-8.html">Entry 5</a>"#,
+                    "<ol>",
+                    r#"<li><a href="string-data-9.html">Entry 6</a></li>"#,
+                    "</ol>",
+                    "</li>",
+                    "</ol>"]);
+}
+
+#[test]
+fn clamped() {
+    let mut zip = write(&levelled_book(&[3, 1, 4]));
+
+    let ncx = read_file(&mut zip, "toc.ncx").unwrap();
+    assert!(ncx.contains(r#"<meta content="2" name="dtb:depth"/>"#));
+
+    let nav = read_file(&mut zip, "nav.xhtml").unwrap();
+    assert_eq!(lines_between(&nav, "<h1>", "</nav>"),
+               vec!["<ol>",
+                    r#"<li><a href="string-data-4.html">Entry 1</a></li>"#,
+                    r#"<li><a href="string-data-5.html">Entry 2</a>"#,
+                    "<ol>",
+                    r#"<li><a href="string-data-6.html">Entry 3</a></li>"#,
+                    "</ol>",
+                    "</li>",
+                    "</ol>"]);
+}
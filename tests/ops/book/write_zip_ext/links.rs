@@ -0,0 +1,62 @@
+use gen_epub_book::ops::{IncludeDirectory, BookElement, EPubBook};
+use self::super::{read_file, write};
+use std::io::Write as IoWrite;
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::env::temp_dir;
+use chrono::DateTime;
+
+
+fn make_test_folder(f: &str) -> PathBuf {
+    let td = temp_dir().join("gen-epub-book.rs-test").join(format!("ops-book-write-zip-ext-links-{}", f));
+    let _ = fs::create_dir_all(td.join("first").join("part1"));
+    let _ = fs::create_dir_all(td.join("second").join("part2"));
+    for &(path, content) in &[("first/part1/ch01.html", r#"<p>See <a href="../part2/ch02.html#fn3">note 3</a>.</p>"#),
+                              ("second/part2/ch02.html",
+                               concat!(r#"<p><a href="../part1/ch01.html">Back</a> <a href="ch03.html#start">Next</a> "#,
+                                       r##"<a href="#top">Top</a> <a href="https://example.com/">Elsewhere</a></p>"##))] {
+        File::create(td.join(path)).unwrap().write_all(content.as_bytes()).unwrap();
+    }
+    td
+}
+
+fn links_book(tf: &PathBuf, verb_out: &mut Vec<u8>) -> EPubBook {
+    let mut book = EPubBook::from_elements(vec![BookElement::Name("Link rewriting demonstration".to_string()),
+                                                BookElement::Content(PathBuf::from("part1/ch01.html")),
+                                                BookElement::Content(PathBuf::from("part2/ch02.html")),
+                                                BookElement::Author("nabijaczleweli".to_string(), None),
+                                                BookElement::Date(DateTime::parse_from_rfc3339("2018-07-11T14:52:07+02:00").unwrap()),
+                                                BookElement::Language("en-GB".to_string())])
+        .unwrap();
+    book.normalise_paths(&[IncludeDirectory::Unnamed { dir: ("first/".to_string(), tf.join("first")) },
+                           IncludeDirectory::Named {
+                               name: "second".to_string(),
+                               dir: ("second/".to_string(), tf.join("second")),
+                           }],
+                         false,
+                         verb_out)
+        .unwrap();
+    book
+}
+
+#[test]
+fn rewritten() {
+    let tf = make_test_folder("rewritten");
+    let mut zip = write(&links_book(&tf, &mut vec![]));
+
+    assert_eq!(read_file(&mut zip, "part1-ch01.html").unwrap(),
+               r#"<p>See <a href="second/part2-ch02.html#fn3">note 3</a>.</p>"#);
+    assert_eq!(read_file(&mut zip, "second/part2-ch02.html").unwrap(),
+               concat!(r#"<p><a href="../part1-ch01.html">Back</a> <a href="ch03.html#start">Next</a> "#,
+                       r##"<a href="#top">Top</a> <a href="https://example.com/">Elsewhere</a></p>"##));
+}
+
+#[test]
+fn not_in_book() {
+    let tf = make_test_folder("not_in_book");
+    let mut verb_out = vec![];
+    links_book(&tf, &mut verb_out);
+
+    assert_eq!(String::from_utf8(verb_out).unwrap(),
+               "Warning: part2/ch02.html links to part2/ch03.html, which isn't in the book.\n");
+}
@@ -0,0 +1,52 @@
+use gen_epub_book::ops::{IncludeDirectory, BookElement, EPubBook};
+use self::super::{read_file, write};
+use std::path::PathBuf;
+use chrono::DateTime;
+
+
+fn markdown_book() -> EPubBook {
+    let mut book = EPubBook::from_elements(vec![BookElement::Name("Markdown demonstration".to_string()),
+                                                BookElement::MarkdownContent(PathBuf::from("markdown/first.md")),
+                                                BookElement::MarkdownContent(PathBuf::from("markdown/second.md")),
+                                                BookElement::StringMarkdownContent("# Afterword\n\nTom & *Jerry*".to_string()),
+                                                BookElement::Author("nabijaczleweli".to_string(), None),
+                                                BookElement::Date(DateTime::parse_from_rfc3339("2018-07-02T17:12:46+02:00").unwrap()),
+                                                BookElement::Language("en-GB".to_string())])
+        .unwrap();
+    book.normalise_paths(&[IncludeDirectory::Unnamed { dir: ("examples/".to_string(), PathBuf::from("examples")) }],
+                         false,
+                         &mut vec![])
+        .unwrap();
+    book
+}
+
+#[test]
+fn rendered() {
+    let mut zip = write(&markdown_book());
+
+    let first = read_file(&mut zip, "markdown-first.html").unwrap();
+    assert!(first.starts_with(r#"<html xmlns="http://www.w3.org/1999/xhtml">"#));
+    assert!(first.contains("<h1>Chapter 1, <em>Where it begins</em></h1>"));
+    assert!(first.contains("<li><strong>emphasis</strong>,</li>"));
+    assert!(read_file(&mut zip, "markdown-second.md").is_none());
+
+    let string = read_file(&mut zip, "string-markdown-data-3.html").unwrap();
+    assert!(string.contains("<p>Tom &amp; <em>Jerry</em></p>"));
+}
+
+#[test]
+fn manifest() {
+    let opf = read_file(&mut write(&markdown_book()), "content.opf").unwrap();
+    assert!(opf.contains(r#"<item href="markdown-first.html" id="markdown-first_md" media-type="application/xhtml+xml" />"#));
+    assert!(opf.contains(r#"<item href="string-markdown-data-3.html" id="string-markdown-content-3" media-type="application/xhtml+xml" />"#));
+    assert!(opf.contains(r#"<itemref idref="markdown-second_md" />"#));
+}
+
+#[test]
+fn table_of_contents() {
+    let ncx = read_file(&mut write(&markdown_book()), "toc.ncx").unwrap();
+    assert!(ncx.contains("<text>Chapter 1, Where it begins</text>"));
+    assert!(ncx.contains(r#"<content src="markdown-first.html"/>"#));
+    assert!(ncx.contains("<text>Chapter 2, Explicitly named</text>"));
+    assert!(ncx.contains("<text>Afterword</text>"));
+}
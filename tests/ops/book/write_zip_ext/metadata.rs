@@ -0,0 +1,63 @@
+use gen_epub_book::ops::{BookElement, EPubBook, EPubVersion, check_epub};
+use self::super::read_file;
+use std::io::Cursor;
+use chrono::DateTime;
+use zip::ZipArchive;
+
+
+fn metadata_book(version: EPubVersion) -> ZipArchive<Cursor<Vec<u8>>> {
+    let mut book = EPubBook::from_elements(vec![BookElement::Name("Metadata demonstration".to_string()),
+                                                BookElement::Author("nabijaczleweli".to_string(), None),
+                                                BookElement::Date(DateTime::parse_from_rfc3339("2018-07-20T11:02:43+02:00").unwrap()),
+                                                BookElement::Language("en-GB".to_string()),
+                                                BookElement::Publisher("Tom & Jerry Ltd.".to_string()),
+                                                BookElement::Rights("CC-BY-SA 4.0".to_string()),
+                                                BookElement::Subject("Fantasy".to_string()),
+                                                BookElement::Subject("Humour".to_string()),
+                                                BookElement::Source("urn:isbn:080442957X".to_string()),
+                                                BookElement::Relation("https://example.com/sequel".to_string()),
+                                                BookElement::Isbn("9780306406157".to_string()),
+                                                BookElement::Identifier("DOI".to_string(), "10.1000/182".to_string()),
+                                                BookElement::Modified(DateTime::parse_from_rfc3339("2018-07-21T12:00:00+02:00").unwrap()),
+                                                BookElement::Series("Discworld".to_string()),
+                                                BookElement::SeriesIndex("2.5".to_string())])
+        .unwrap();
+    book.version = version;
+
+    let mut buf = Cursor::new(vec![]);
+    book.write_zip(&mut buf, false, &mut vec![]).unwrap();
+    assert_eq!(check_epub(&mut buf, false, &mut vec![]), Ok(()));
+    ZipArchive::new(Cursor::new(buf.into_inner())).unwrap()
+}
+
+#[test]
+fn v2() {
+    let opf = read_file(&mut metadata_book(EPubVersion::V2), "content.opf").unwrap();
+    assert!(opf.contains(r#"<dc:date opf:event="modification">2018-07-21T12:00:00+02:00</dc:date>
+    <dc:publisher>Tom &amp; Jerry Ltd.</dc:publisher>
+    <dc:rights>CC-BY-SA 4.0</dc:rights>
+    <dc:source>urn:isbn:080442957X</dc:source>
+    <dc:subject>Fantasy</dc:subject>
+    <dc:subject>Humour</dc:subject>
+    <dc:relation>https://example.com/sequel</dc:relation>
+    <dc:identifier opf:scheme="ISBN">9780306406157</dc:identifier>
+    <dc:identifier opf:scheme="DOI">10.1000/182</dc:identifier>
+    <meta name="calibre:series" content="Discworld" />
+    <meta name="calibre:series_index" content="2.5" />"#));
+    assert!(!opf.contains("belongs-to-collection"));
+}
+
+#[test]
+fn v3() {
+    let opf = read_file(&mut metadata_book(EPubVersion::V3), "content.opf").unwrap();
+    assert!(opf.contains(r#"<meta property="dcterms:modified">2018-07-21T10:00:00Z</meta>"#));
+    assert!(opf.contains(r#"<dc:identifier id="identifier-1">urn:isbn:9780306406157</dc:identifier>"#));
+    assert!(opf.contains(r##"<dc:identifier id="identifier-2">10.1000/182</dc:identifier>
+    <meta refines="#identifier-2" property="identifier-type">DOI</meta>"##));
+    assert!(opf.contains(r##"<meta name="calibre:series" content="Discworld" />
+    <meta name="calibre:series_index" content="2.5" />
+    <meta property="belongs-to-collection" id="series">Discworld</meta>
+    <meta refines="#series" property="collection-type">series</meta>
+    <meta refines="#series" property="group-position">2.5</meta>"##));
+    assert!(!opf.contains("opf:event"));
+}
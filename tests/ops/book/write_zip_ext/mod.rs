@@ -0,0 +1,65 @@
+mod compression;
+mod creators;
+mod escaping;
+mod levels;
+mod links;
+mod markdown;
+mod metadata;
+mod network_content;
+mod network_names;
+mod offline;
+mod reproducible;
+mod resources;
+mod version;
+mod xhtml;
+
+use gen_epub_book::ops::{BookElement, EPubBook, check_epub};
+use std::io::{Cursor, Read, Write};
+use std::fs::{self, File};
+use chrono::DateTime;
+use zip::ZipArchive;
+use url::Url;
+
+
+fn simple_book() -> EPubBook {
+    EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
+                                 BookElement::StringContent(r#"<!-- ePub title: "Chapter 1" -->Chapter text"#.to_string()),
+                                 BookElement::Author("nabijaczleweli".to_string(), None),
+                                 BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
+                                 BookElement::Language("en-GB".to_string())])
+        .unwrap()
+}
+
+fn write(book: &EPubBook) -> ZipArchive<Cursor<Vec<u8>>> {
+    write_ext(book, &mut vec![])
+}
+
+fn write_ext(book: &EPubBook, verb_out: &mut Vec<u8>) -> ZipArchive<Cursor<Vec<u8>>> {
+    let mut buf = Cursor::new(vec![]);
+    book.write_zip_ext(true, &mut buf, false, verb_out).unwrap();
+    assert_eq!(check_epub(&mut buf, false, &mut vec![]), Ok(()));
+    ZipArchive::new(Cursor::new(buf.into_inner())).unwrap()
+}
+
+/// Put `content` and `headers` into `book`'s download cache for `url`, or remove them if `content` is `None`.
+fn cache(book: &EPubBook, url: &str, content: Option<&str>, headers: &str) {
+    let cached = book.download.cache_path(&Url::parse(url).unwrap()).unwrap();
+    match content {
+        Some(content) => {
+            File::create(&cached).unwrap().write_all(content.as_bytes()).unwrap();
+            File::create(cached.with_extension("headers")).unwrap().write_all(headers.as_bytes()).unwrap();
+        }
+        None => {
+            let _ = fs::remove_file(cached.with_extension("headers"));
+            let _ = fs::remove_file(cached);
+        }
+    }
+}
+
+fn read_file(zip: &mut ZipArchive<Cursor<Vec<u8>>>, name: &str) -> Option<String> {
+    zip.by_name(name).ok().map(|mut f| {
+        let mut out = String::new();
+        f.read_to_string(&mut out).unwrap();
+        out
+    })
+}
@@ -0,0 +1,95 @@
+use gen_epub_book::ops::{BookElement, EPubBook};
+use gen_epub_book::util::DownloadConfig;
+use self::super::{read_file, write_ext, cache};
+use std::env::temp_dir;
+use chrono::DateTime;
+use std::fs;
+use url::Url;
+
+
+static CHAPTER_1: &'static str = r##"<!DOCTYPE html>
+<html>
+<head>
+  <title>Chapter 1 | Serial</title>
+  <link rel="stylesheet" href="/style.css">
+</head>
+<body>
+  <p id="top"><img src="images/fig1.png" alt="Figure 1"><img src="missing.png" alt="Missing">
+  <p><a href="#top">Top</a> <a href="chapter-2#top">Next</a> <a href="https://example.com/">Elsewhere</a>
+</body>
+</html>
+"##;
+static CHAPTER_2: &'static str = r#"<!-- ePub title: "Chapter 2" --><p id="top">Chapter 2 text</p>"#;
+
+fn network_book(f: &str, network_images: bool) -> EPubBook {
+    let td = temp_dir().join("gen-epub-book.rs-test").join(format!("ops-book-write-zip-ext-network-content-{}", f));
+    let _ = fs::create_dir_all(&td);
+
+    let mut book = EPubBook::from_elements(vec![BookElement::Name("Network content demonstration".to_string()),
+                                                BookElement::NetworkContent(Url::parse("https://serial.example.com/chapter-1").unwrap(), None),
+                                                BookElement::NetworkContent(Url::parse("https://serial.example.com/chapter-2").unwrap(), None),
+                                                BookElement::Author("nabijaczleweli".to_string(), None),
+                                                BookElement::Date(DateTime::parse_from_rfc3339("2018-07-20T11:02:43+02:00").unwrap()),
+                                                BookElement::Language("en-GB".to_string())])
+        .unwrap();
+    book.normalise_html = true;
+    book.network_images = network_images;
+    book.download = DownloadConfig {
+        cache_dir: Some(td),
+        offline: true,
+        ..DownloadConfig::default()
+    };
+
+    cache(&book, "https://serial.example.com/chapter-1", Some(CHAPTER_1), "Content-Type: text/html; charset=utf-8\n");
+    cache(&book, "https://serial.example.com/chapter-2", Some(CHAPTER_2), "Content-Type: text/html\n");
+    cache(&book, "https://serial.example.com/images/fig1.png", Some("PNG"), "Content-Type: image/png\n");
+    cache(&book, "https://serial.example.com/missing.png", None, "");
+    book
+}
+
+#[test]
+fn spine() {
+    let mut zip = write_ext(&network_book("spine", false), &mut vec![]);
+    let opf = read_file(&mut zip, "content.opf").unwrap();
+    assert!(opf.contains(r#"<item href="chapter-1.html" id="chapter-1" media-type="application/xhtml+xml" />"#));
+    assert!(opf.contains(r#"<item href="chapter-2.html" id="chapter-2" media-type="application/xhtml+xml" />"#));
+    assert!(opf.contains("<itemref idref=\"chapter-1\" />\n    <itemref idref=\"chapter-2\" />"));
+}
+
+#[test]
+fn titles() {
+    let mut zip = write_ext(&network_book("titles", false), &mut vec![]);
+    let ncx = read_file(&mut zip, "toc.ncx").unwrap();
+    assert!(ncx.contains("<text>Chapter 1 | Serial</text>"));
+    assert!(ncx.contains("<text>Chapter 2</text>"));
+}
+
+#[test]
+fn references() {
+    let mut zip = write_ext(&network_book("references", false), &mut vec![]);
+    assert!(zip.by_name("fig1.png").is_err());
+
+    let chapter = read_file(&mut zip, "chapter-1.html").unwrap();
+    assert!(chapter.contains(r#"href="https://serial.example.com/style.css""#));
+    assert!(chapter.contains(r#"src="https://serial.example.com/images/fig1.png""#));
+    assert!(chapter.contains(r##"href="#top""##));
+    assert!(chapter.contains(r##"href="chapter-2.html#top""##));
+    assert!(chapter.contains(r#"href="https://example.com/""#));
+}
+
+#[test]
+fn images() {
+    let mut verb_out = vec![];
+    let mut zip = write_ext(&network_book("images", true), &mut verb_out);
+    assert_eq!(read_file(&mut zip, "fig1.png").unwrap(), "PNG");
+    assert!(read_file(&mut zip, "content.opf").unwrap().contains(r#"<item href="fig1.png" id="fig1_png" media-type="image/png" />"#));
+
+    let chapter = read_file(&mut zip, "chapter-1.html").unwrap();
+    assert!(chapter.contains(r#"src="fig1.png""#));
+    assert!(chapter.contains(r#"src="https://serial.example.com/missing.png""#));
+    assert!(chapter.contains(r#"href="https://serial.example.com/style.css""#));
+
+    assert_eq!(String::from_utf8(verb_out).unwrap(),
+               "Warning: couldn't download https://serial.example.com/missing.png referenced from https://serial.example.com/chapter-1, leaving as-is: \
+                https://serial.example.com/missing.png not cached, but offline.\n");
+}
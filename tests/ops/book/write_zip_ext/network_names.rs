@@ -0,0 +1,139 @@
+use gen_epub_book::ops::{BookElement, EPubBook};
+use gen_epub_book::util::DownloadConfig;
+use self::super::{read_file, write, cache};
+use std::fs::{self, File};
+use std::env::temp_dir;
+use chrono::DateTime;
+use std::io::Write;
+use url::Url;
+
+
+fn network_book(f: &str, elements: Vec<BookElement>) -> EPubBook {
+    let td = temp_dir().join("gen-epub-book.rs-test").join(format!("ops-book-write-zip-ext-network-names-{}", f));
+    let _ = fs::create_dir_all(&td);
+
+    let mut book = EPubBook::from_elements(vec![BookElement::Name("Network names demonstration".to_string()),
+                                                BookElement::Author("nabijaczleweli".to_string(), None),
+                                                BookElement::Date(DateTime::parse_from_rfc3339("2018-07-20T11:02:43+02:00").unwrap()),
+                                                BookElement::Language("en-GB".to_string())]
+            .into_iter()
+            .chain(elements))
+        .unwrap();
+    book.download = DownloadConfig {
+        cache_dir: Some(td),
+        offline: true,
+        ..DownloadConfig::default()
+    };
+    book
+}
+
+#[test]
+fn content_type() {
+    let book = network_book("content_type", vec![BookElement::NetworkImageContent(Url::parse("https://cdn.example.com/img?id=42").unwrap(), None)]);
+    cache(&book, "https://cdn.example.com/img?id=42", Some("PNG"), "Content-Type: image/png\n");
+
+    let mut zip = write(&book);
+    assert_eq!(read_file(&mut zip, "img.png").unwrap(), "PNG");
+    let opf = read_file(&mut zip, "content.opf").unwrap();
+    assert!(opf.contains(r#"href="img.png" id="img" media-type="image/png""#));
+    assert!(read_file(&mut zip, "network-image-data-4.html").unwrap().contains(r#"<img src="img.png" alt="img.png"></img>"#));
+}
+
+#[test]
+fn wrong_extension() {
+    let book = network_book("wrong_extension", vec![BookElement::NetworkInclude(Url::parse("https://example.com/image.php").unwrap(), None)]);
+    cache(&book, "https://example.com/image.php", Some("JPEG"), "Content-Type: image/jpeg\n");
+
+    let mut zip = write(&book);
+    assert_eq!(read_file(&mut zip, "image.php").unwrap(), "JPEG");
+    assert!(read_file(&mut zip, "content.opf").unwrap().contains(r#"href="image.php" id="image_php" media-type="image/jpeg""#));
+}
+
+#[test]
+fn content_disposition() {
+    let book = network_book("content_disposition", vec![BookElement::NetworkInclude(Url::parse("https://example.com").unwrap(), None)]);
+    cache(&book,
+          "https://example.com",
+          Some("body {}"),
+          "Content-Type: text/css; charset=utf-8\nContent-Disposition: attachment; filename=\"../style.css\"\n");
+
+    let mut zip = write(&book);
+    assert_eq!(read_file(&mut zip, "style.css").unwrap(), "body {}");
+    assert!(read_file(&mut zip, "content.opf").unwrap().contains(r#"href="style.css" id="example_com" media-type="text/css""#));
+}
+
+#[test]
+fn collisions() {
+    let book = network_book("collisions",
+                            vec![BookElement::StringContent("Chapter".to_string()),
+                                 BookElement::NetworkInclude(Url::parse("https://a.example.com/style.css").unwrap(), None),
+                                 BookElement::NetworkInclude(Url::parse("https://b.example.com/style.css").unwrap(), None),
+                                 BookElement::NetworkInclude(Url::parse("https://c.example.com/string-data-4.html").unwrap(), None)]);
+    cache(&book, "https://a.example.com/style.css", Some("/* a */"), "");
+    cache(&book, "https://b.example.com/style.css", Some("/* b */"), "");
+    cache(&book, "https://c.example.com/string-data-4.html", Some("<p>c</p>"), "");
+
+    let mut zip = write(&book);
+    assert_eq!(read_file(&mut zip, "style.css").unwrap(), "/* a */");
+    assert_eq!(read_file(&mut zip, "style-2.css").unwrap(), "/* b */");
+    assert_eq!(read_file(&mut zip, "string-data-4-2.html").unwrap(), "<p>c</p>");
+    let opf = read_file(&mut zip, "content.opf").unwrap();
+    assert!(opf.contains(r#"href="style.css" id="style_css""#));
+    assert!(opf.contains(r#"href="style-2.css" id="style_css-2""#));
+    assert!(opf.contains(r#"href="string-data-4-2.html" id="string-data-4_html""#));
+}
+
+#[test]
+fn reserved_names() {
+    let book = network_book("reserved_names",
+                            vec![BookElement::NetworkInclude(Url::parse("https://example.com/content.opf").unwrap(), None),
+                                 BookElement::NetworkInclude(Url::parse("https://example.com/toc").unwrap(), None)]);
+    cache(&book, "https://example.com/content.opf", Some("<package />"), "");
+    cache(&book,
+          "https://example.com/toc",
+          Some("<ncx />"),
+          "Content-Type: application/xml\nContent-Disposition: attachment; filename=\"toc.ncx\"\n");
+
+    let mut zip = write(&book);
+    assert_eq!(read_file(&mut zip, "content-2.opf").unwrap(), "<package />");
+    assert_eq!(read_file(&mut zip, "toc-2.ncx").unwrap(), "<ncx />");
+    let opf = read_file(&mut zip, "content.opf").unwrap();
+    assert!(opf.contains(r#"href="content-2.opf" id="content_opf""#));
+    assert!(opf.contains(r#"href="toc-2.ncx" id="toc-2""#));
+    assert!(opf.contains(r#"href="toc.ncx" id="toc""#));
+}
+
+#[test]
+fn data_url() {
+    let book = network_book("data_url", vec![BookElement::NetworkImageContent(Url::parse("data:image/png;base64,UE5H").unwrap(), None)]);
+
+    let mut zip = write(&book);
+    assert_eq!(read_file(&mut zip, "network-data.png").unwrap(), "PNG");
+    let opf = read_file(&mut zip, "content.opf").unwrap();
+    assert!(opf.contains(r#"href="network-data.png" id="network-data" media-type="image/png""#));
+}
+
+#[test]
+fn image_page_collisions() {
+    let book = network_book("image_page_collisions",
+                            vec![BookElement::NetworkImageContent(Url::parse("data:image/png;base64,UE5HMQ==").unwrap(), None),
+                                 BookElement::NetworkImageContent(Url::parse("data:image/png;base64,UE5HMg==").unwrap(), None)]);
+
+    let mut zip = write(&book);
+    assert_eq!(read_file(&mut zip, "network-data.png").unwrap(), "PNG1");
+    assert_eq!(read_file(&mut zip, "network-data-2.png").unwrap(), "PNG2");
+    assert!(read_file(&mut zip, "network-image-data-4.html").unwrap().contains(r#"<img src="network-data.png" alt="network-data.png"></img>"#));
+    assert!(read_file(&mut zip, "network-image-data-5.html").unwrap().contains(r#"<img src="network-data-2.png" alt="network-data-2.png"></img>"#));
+}
+
+#[test]
+fn file_url() {
+    let book = network_book("file_url", vec![]);
+    let path = book.download.cache_dir.as_ref().unwrap().join("local.css");
+    File::create(&path).unwrap().write_all(b"body {}").unwrap();
+    let book = network_book("file_url", vec![BookElement::NetworkInclude(Url::from_file_path(&path).unwrap(), None)]);
+
+    let mut zip = write(&book);
+    assert_eq!(read_file(&mut zip, "local.css").unwrap(), "body {}");
+    assert!(read_file(&mut zip, "content.opf").unwrap().contains(r#"href="local.css" id="local_css" media-type="text/css""#));
+}
@@ -0,0 +1,175 @@
+use gen_epub_book::ops::{BookElement, EPubBook};
+use gen_epub_book::util::{DownloadConfig, sha256_hex};
+use self::super::{read_file, write, cache};
+use gen_epub_book::Error;
+use std::fs;
+use std::env::temp_dir;
+use std::io::{Cursor, Read, Write};
+use std::time::{Duration, Instant};
+use std::net::TcpListener;
+use chrono::DateTime;
+use std::thread;
+use url::Url;
+
+
+static STYLESHEETS: &'static [&'static str] = &["https://example.com/style.css", "https://example.com/print.css", "https://example.com/screen.css"];
+
+fn network_book(f: &str, jobs: usize) -> EPubBook {
+    let td = temp_dir().join("gen-epub-book.rs-test").join(format!("ops-book-write-zip-ext-offline-{}", f));
+    let _ = fs::create_dir_all(&td);
+
+    let mut book = EPubBook::from_elements(vec![BookElement::Name("Offline demonstration".to_string()),
+                                                BookElement::Author("nabijaczleweli".to_string(), None),
+                                                BookElement::Date(DateTime::parse_from_rfc3339("2018-07-20T11:02:43+02:00").unwrap()),
+                                                BookElement::Language("en-GB".to_string())]
+            .into_iter()
+            .chain(STYLESHEETS.iter().map(|u| BookElement::NetworkInclude(Url::parse(u).unwrap(), None))))
+        .unwrap();
+    book.download = DownloadConfig {
+        cache_dir: Some(td),
+        offline: true,
+        jobs: jobs,
+        ..DownloadConfig::default()
+    };
+    book
+}
+
+#[test]
+fn cached() {
+    for &jobs in &[1, 2, 8] {
+        let book = network_book(&format!("cached-{}", jobs), jobs);
+        for url in STYLESHEETS {
+            cache(&book, url, Some(&format!("/* {} */", url)), "");
+        }
+
+        let mut zip = write(&book);
+        assert_eq!(read_file(&mut zip, "style.css").unwrap(), "/* https://example.com/style.css */");
+        assert_eq!(read_file(&mut zip, "print.css").unwrap(), "/* https://example.com/print.css */");
+        assert_eq!(read_file(&mut zip, "screen.css").unwrap(), "/* https://example.com/screen.css */");
+    }
+}
+
+#[test]
+fn verbose() {
+    let book = network_book("verbose", 2);
+    for url in STYLESHEETS {
+        cache(&book, url, Some("body {}"), "");
+    }
+
+    let mut verb_out = vec![];
+    book.write_zip(&mut Cursor::new(vec![]), true, &mut verb_out).unwrap();
+    let verb_out = String::from_utf8(verb_out).unwrap();
+    assert!(verb_out.contains("Downloading 3 network files, 2 at a time.\n"));
+    for url in STYLESHEETS {
+        assert!(verb_out.contains(&format!("Downloaded {} (7 bytes).\n", url)));
+    }
+}
+
+#[test]
+fn not_cached() {
+    let book = network_book("not_cached", 4);
+    cache(&book, STYLESHEETS[0], Some("body {}"), "");
+    cache(&book, STYLESHEETS[1], Some("body {}"), "");
+    cache(&book, STYLESHEETS[2], None, "");
+
+    let mut verb_out = vec![];
+    assert_eq!(book.write_zip(&mut Cursor::new(vec![]), false, &mut verb_out),
+               Err(Error::NotCached { url: "https://example.com/screen.css".to_string() }));
+    assert!(verb_out.is_empty());
+}
+
+#[test]
+fn aggregated() {
+    let book = network_book("aggregated", 4);
+    cache(&book, STYLESHEETS[0], None, "");
+    cache(&book, STYLESHEETS[1], Some("body {}"), "");
+    cache(&book, STYLESHEETS[2], None, "");
+
+    let mut verb_out = vec![];
+    assert_eq!(book.write_zip(&mut Cursor::new(vec![]), false, &mut verb_out),
+               Err(Error::NotCached { url: "https://example.com/screen.css".to_string() }));
+    assert_eq!(String::from_utf8(verb_out).unwrap(),
+               "Downloading https://example.com/screen.css failed: https://example.com/screen.css not cached, but offline.\n\
+                Downloading https://example.com/style.css failed: https://example.com/style.css not cached, but offline.\n");
+}
+
+#[test]
+fn pinned() {
+    let mut book = network_book("pinned", 2);
+    for url in STYLESHEETS {
+        cache(&book, url, Some("body {}"), "");
+    }
+    book.network_hashes.insert(Url::parse(STYLESHEETS[0]).unwrap(), sha256_hex(b"body {}"));
+
+    let mut verb_out = vec![];
+    book.write_zip(&mut Cursor::new(vec![]), true, &mut verb_out).unwrap();
+    let verb_out = String::from_utf8(verb_out).unwrap();
+    assert!(verb_out.contains("Verified https://example.com/style.css.\n"));
+    assert!(!verb_out.contains("Verified https://example.com/print.css.\n"));
+}
+
+#[test]
+fn pin_mismatch() {
+    let mut book = network_book("pin_mismatch", 2);
+    for url in STYLESHEETS {
+        cache(&book, url, Some("body {}"), "");
+    }
+    book.network_hashes.insert(Url::parse(STYLESHEETS[2]).unwrap(), sha256_hex(b"body { color: red; }"));
+
+    assert_eq!(book.write_zip(&mut Cursor::new(vec![]), false, &mut vec![]),
+               Err(Error::HashMismatch {
+                   url: "https://example.com/screen.css".to_string(),
+                   expected: sha256_hex(b"body { color: red; }"),
+                   actual: sha256_hex(b"body {}"),
+               }));
+}
+
+#[test]
+fn hashes() {
+    let mut book = network_book("hashes", 2);
+    for url in STYLESHEETS {
+        cache(&book, url, Some(url), "");
+    }
+    book.network_hashes.insert(Url::parse(STYLESHEETS[1]).unwrap(), sha256_hex(b""));
+
+    assert_eq!(book.hash_network_content(false, &mut vec![]).unwrap(),
+               STYLESHEETS.iter().map(|u| (Url::parse(u).unwrap(), sha256_hex(u.as_bytes()))).collect());
+}
+
+#[test]
+fn concurrent() {
+    // Every response takes half a second, so four sequential downloads would take at least two
+    let server = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = server.local_addr().unwrap().port();
+    thread::spawn(move || for stream in server.incoming() {
+        let mut stream = stream.unwrap();
+        thread::spawn(move || {
+            let mut request = vec![];
+            let mut buf = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                match stream.read(&mut buf) {
+                    Ok(0) | Err(_) => return,
+                    Ok(read) => request.extend_from_slice(&buf[..read]),
+                }
+            }
+            thread::sleep(Duration::from_millis(500));
+            let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/css\r\nContent-Length: 7\r\nConnection: close\r\n\r\nbody {}");
+        });
+    });
+
+    let mut book = EPubBook::from_elements(vec![BookElement::Name("Concurrent demonstration".to_string()),
+                                                BookElement::Author("nabijaczleweli".to_string(), None),
+                                                BookElement::Date(DateTime::parse_from_rfc3339("2018-07-20T11:02:43+02:00").unwrap()),
+                                                BookElement::Language("en-GB".to_string())]
+            .into_iter()
+            .chain((1..5).map(|i| BookElement::NetworkInclude(Url::parse(&format!("http://127.0.0.1:{}/{}.css", port, i)).unwrap(), None))))
+        .unwrap();
+    book.download = DownloadConfig { jobs: 4, ..DownloadConfig::default() };
+
+    let start = Instant::now();
+    let mut zip = write(&book);
+    assert!(start.elapsed() < Duration::from_millis(1500), "downloads took {:?}", start.elapsed());
+    for i in 1..5 {
+        assert_eq!(read_file(&mut zip, &format!("{}.css", i)).unwrap(), "body {}");
+    }
+}
@@ -0,0 +1,79 @@
+use gen_epub_book::ops::{EPubVersion, BookElement, EPubBook};
+use self::super::{simple_book, read_file, write};
+use std::io::Cursor;
+use chrono::DateTime;
+use uuid::Uuid;
+use std::env;
+
+
+fn write_raw(book: &EPubBook) -> Vec<u8> {
+    let mut buf = Cursor::new(vec![]);
+    book.write_zip_ext(true, &mut buf, false, &mut vec![]).unwrap();
+    buf.into_inner()
+}
+
+#[test]
+fn same_book() {
+    env::set_var("SOURCE_DATE_EPOCH", "1486564218");
+
+    for &version in &[EPubVersion::V2, EPubVersion::V3] {
+        let mut first = simple_book();
+        first.version = version;
+        let mut second = simple_book();
+        second.version = version;
+
+        assert_eq!(write_raw(&first), write_raw(&second));
+    }
+}
+
+#[test]
+fn modification_date() {
+    env::set_var("SOURCE_DATE_EPOCH", "1486564218");
+
+    let mut book = simple_book();
+    book.version = EPubVersion::V3;
+    let mut zip = write(&book);
+
+    assert!(read_file(&mut zip, "content.opf").unwrap().contains(r#"<meta property="dcterms:modified">2017-02-08T14:30:18Z</meta>"#));
+    let mtime = zip.by_name("content.opf").unwrap().last_modified();
+    assert_eq!((mtime.year(), mtime.month(), mtime.day(), mtime.hour(), mtime.minute(), mtime.second()),
+               (2017, 2, 8, 14, 30, 18));
+}
+
+#[test]
+fn navpoint_ids() {
+    let ncx = read_file(&mut write(&simple_book()), "toc.ncx").unwrap();
+    assert!(ncx.contains(r#"<navPoint id="navpoint-1" playOrder="1">"#));
+}
+
+#[test]
+fn derived_uuid() {
+    let first = read_file(&mut write(&simple_book()), "content.opf").unwrap();
+    let second = read_file(&mut write(&simple_book()), "content.opf").unwrap();
+    let uuid_line = |opf: &str| opf.lines().find(|l| l.contains("<dc:identifier")).unwrap().to_string();
+    assert_eq!(uuid_line(&first), uuid_line(&second));
+
+    let other = EPubBook::from_elements(vec![BookElement::Name("Different ePub demonstration".to_string()),
+                                             BookElement::Author("nabijaczleweli".to_string(), None),
+                                             BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
+                                             BookElement::Language("en-GB".to_string())])
+        .unwrap();
+    assert!(uuid_line(&first) != uuid_line(&read_file(&mut write(&other), "content.opf").unwrap()));
+}
+
+#[test]
+fn specified_uuid() {
+    let uuid = Uuid::parse_str("2d3b2c9b-0a7e-4c0b-9a5e-5f2f4f1d6c3a").unwrap();
+    let book = EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
+                                            BookElement::Uuid(uuid),
+                                            BookElement::Author("nabijaczleweli".to_string(), None),
+                                            BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
+                                            BookElement::Language("en-GB".to_string())])
+        .unwrap();
+
+    let mut zip = write(&book);
+    assert!(read_file(&mut zip, "content.opf")
+        .unwrap()
+        .contains(r#"<dc:identifier id="uuid" opf:scheme="uuid">2d3b2c9b-0a7e-4c0b-9a5e-5f2f4f1d6c3a</dc:identifier>"#));
+    assert!(read_file(&mut zip, "toc.ncx").unwrap().contains(r#"<meta content="2d3b2c9b-0a7e-4c0b-9a5e-5f2f4f1d6c3a" name="dtb:uid"/>"#));
+}
@@ -0,0 +1,90 @@
+use gen_epub_book::ops::{IncludeDirectory, BookElement, EPubBook};
+use self::super::{read_file, write};
+use std::io::Write as IoWrite;
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::env::temp_dir;
+use chrono::DateTime;
+
+
+fn make_test_folder(f: &str) -> PathBuf {
+    let td = temp_dir().join("gen-epub-book.rs-test").join(format!("ops-book-write-zip-ext-resources-{}", f));
+    let _ = fs::create_dir_all(td.join("book").join("text").join("img"));
+    let _ = fs::create_dir_all(td.join("book").join("style"));
+    let _ = fs::create_dir_all(td.join("book").join("img"));
+    let _ = fs::create_dir_all(td.join("extra").join("text"));
+    for &(path, content) in &[("book/text/ch01.html",
+                               concat!(r#"<!-- ePub title: "Chapter 1" -->"#,
+                                       r#"<link href="../style/main.css" rel="stylesheet" />"#,
+                                       r#"<img src="img/fig%201.png" /><img src='notes.png' /><img src="missing.png" />"#,
+                                       r#"<img src="https://example.com/remote.png" /><a href="ch02.html">Next</a>"#)),
+                              ("book/text/ch02.md", "# Chapter 2\n\n![Figure 1](img/fig%201.png)\n"),
+                              ("book/style/main.css", "body { background: url('../img/bg.png'); }"),
+                              ("book/text/img/fig 1.png", ""),
+                              ("book/img/bg.png", ""),
+                              ("extra/text/notes.png", "")] {
+        File::create(td.join(path)).unwrap().write_all(content.as_bytes()).unwrap();
+    }
+    td
+}
+
+fn resources_book(tf: &PathBuf, verb_out: &mut Vec<u8>) -> EPubBook {
+    let mut book = EPubBook::from_elements(vec![BookElement::Name("Resource discovery demonstration".to_string()),
+                                                BookElement::Content(PathBuf::from("text/ch01.html")),
+                                                BookElement::MarkdownContent(PathBuf::from("text/ch02.md")),
+                                                BookElement::Author("nabijaczleweli".to_string(), None),
+                                                BookElement::Date(DateTime::parse_from_rfc3339("2018-07-09T20:11:38+02:00").unwrap()),
+                                                BookElement::Language("en-GB".to_string())])
+        .unwrap();
+    book.normalise_paths(&[IncludeDirectory::Unnamed { dir: ("book/".to_string(), tf.join("book")) },
+                           IncludeDirectory::Named {
+                               name: "extra".to_string(),
+                               dir: ("extra/".to_string(), tf.join("extra")),
+                           }],
+                         true,
+                         verb_out)
+        .unwrap();
+    book
+}
+
+#[test]
+fn discovered() {
+    let tf = make_test_folder("discovered");
+    let opf = read_file(&mut write(&resources_book(&tf, &mut vec![])), "content.opf").unwrap();
+    assert!(opf.contains(r#"<item href="style-main.css" id="style-main_css" media-type="text/css" />"#));
+    assert!(opf.contains(r#"<item href="text-img-fig 1.png" id="text-img-fig 1_png" media-type="image/png" />"#));
+    assert!(opf.contains(r#"<item href="extra/text-notes.png" id="extra--text-notes_png" media-type="image/png" />"#));
+    assert!(opf.contains(r#"<item href="img-bg.png" id="img-bg_png" media-type="image/png" />"#));
+    assert!(!opf.contains("missing"));
+    assert!(!opf.contains("remote"));
+    assert!(!opf.contains(r#"href="ch02.html""#));
+}
+
+#[test]
+fn rewritten() {
+    let tf = make_test_folder("rewritten");
+    let mut zip = write(&resources_book(&tf, &mut vec![]));
+
+    let ch01 = read_file(&mut zip, "text-ch01.html").unwrap();
+    assert!(ch01.contains(r#"<link href="style-main.css" rel="stylesheet" />"#));
+    assert!(ch01.contains(r#"<img src="text-img-fig%201.png" /><img src='extra/text-notes.png' /><img src="missing.png" />"#));
+    assert!(ch01.contains(r#"<img src="https://example.com/remote.png" /><a href="ch02.html">Next</a>"#));
+
+    let ch02 = read_file(&mut zip, "text-ch02.html").unwrap();
+    assert!(ch02.contains(r#"<img src="text-img-fig%201.png" alt="Figure 1" />"#));
+
+    assert_eq!(read_file(&mut zip, "style-main.css").unwrap(), "body { background: url('img-bg.png'); }");
+}
+
+#[test]
+fn verbose() {
+    let tf = make_test_folder("verbose");
+    let mut verb_out = vec![];
+    resources_book(&tf, &mut verb_out);
+    let verb_out = String::from_utf8(verb_out).unwrap();
+
+    assert!(verb_out.contains("Found book/style/main.css referenced from text/ch01.html, packing as style-main.css.\n"));
+    assert!(verb_out.contains("Found extra/text/notes.png referenced from text/ch01.html, packing as extra/text-notes.png.\n"));
+    assert!(verb_out.contains("Found book/img/bg.png referenced from style/main.css, packing as img-bg.png.\n"));
+    assert!(verb_out.contains("Couldn't find text/missing.png referenced from text/ch01.html, leaving as-is.\n"));
+}
@@ -0,0 +1,100 @@
+use self::super::{simple_book, read_file, write};
+use gen_epub_book::ops::{EPubVersion, BookElement, EPubBook};
+use std::path::PathBuf;
+use chrono::DateTime;
+
+
+fn image_book() -> EPubBook {
+    EPubBook::from_elements(vec![BookElement::Name("Simple ePub demonstration".to_string()),
+                                 BookElement::Cover(PathBuf::from("examples/cover.png")),
+                                 BookElement::ImageContent(PathBuf::from("examples/simple/chapter_image.png")),
+                                 BookElement::Author("nabijaczleweli".to_string(), None),
+                                 BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
+                                 BookElement::Language("en-GB".to_string())])
+        .unwrap()
+}
+
+#[test]
+fn v2() {
+    let mut zip = write(&simple_book());
+
+    let opf = read_file(&mut zip, "content.opf").unwrap();
+    assert!(opf.contains(r#"version="2.0""#));
+    assert!(opf.contains(r#"<spine toc="toc">"#));
+    assert!(!opf.contains("dcterms:modified"));
+    assert!(!opf.contains("nav.xhtml"));
+
+    assert!(read_file(&mut zip, "toc.ncx").unwrap().contains("<text>Chapter 1</text>"));
+    assert_eq!(read_file(&mut zip, "nav.xhtml"), None);
+}
+
+#[test]
+fn v3() {
+    let mut book = simple_book();
+    book.version = EPubVersion::V3;
+    let mut zip = write(&book);
+
+    let opf = read_file(&mut zip, "content.opf").unwrap();
+    assert!(opf.contains(r#"version="3.0""#));
+    assert!(opf.contains(r#"<meta property="dcterms:modified">"#));
+    assert!(opf.contains(r#"<item href="nav.xhtml" id="nav" media-type="application/xhtml+xml" properties="nav" />"#));
+    assert!(opf.contains(r#"<item href="toc.ncx" id="toc" media-type="application/x-dtbncx+xml"/>"#));
+    assert!(opf.contains(r#"<spine toc="toc">"#));
+    assert!(!opf.contains("opf:role"));
+
+    assert!(read_file(&mut zip, "toc.ncx").unwrap().contains("<text>Chapter 1</text>"));
+    assert!(read_file(&mut zip, "nav.xhtml").unwrap().contains(r#"<li><a href="string-data-1.html">Chapter 1</a></li>"#));
+}
+
+#[test]
+fn v3_no_ncx() {
+    let mut book = simple_book();
+    book.version = EPubVersion::V3;
+    book.legacy_ncx = false;
+    let mut zip = write(&book);
+
+    let opf = read_file(&mut zip, "content.opf").unwrap();
+    assert!(opf.contains("<spine>"));
+    assert!(!opf.contains("toc.ncx"));
+
+    assert_eq!(read_file(&mut zip, "toc.ncx"), None);
+    assert!(read_file(&mut zip, "nav.xhtml").is_some());
+}
+
+#[test]
+fn v2_images() {
+    let mut zip = write(&image_book());
+
+    assert!(!read_file(&mut zip, "content.opf").unwrap().contains("cover-image"));
+    assert!(read_file(&mut zip, "cover-data-1.html").unwrap().contains(r#"<center><img src="examples-cover.png" alt="examples-cover.png"></img></center>"#));
+    assert!(read_file(&mut zip, "image-data-2.html").unwrap().contains("<head></head>"));
+}
+
+#[test]
+fn v3_images() {
+    let mut book = image_book();
+    book.version = EPubVersion::V3;
+    let mut zip = write(&book);
+
+    let opf = read_file(&mut zip, "content.opf").unwrap();
+    assert!(opf.contains(r#"<item href="examples-cover.png" id="examples-cover_png" media-type="image/png" properties="cover-image" />"#));
+    assert!(opf.contains(r#"<item href="examples-simple-chapter_image.png" id="examples-simple-chapter_image_png" media-type="image/png" />"#));
+
+    let cover = read_file(&mut zip, "cover-data-1.html").unwrap();
+    assert!(cover.contains(r#"<div style="text-align: center;"><img src="examples-cover.png" alt="examples-cover.png" /></div>"#));
+    assert!(cover.contains("<title>Simple ePub demonstration</title>"));
+    assert!(!cover.contains("<center>"));
+
+    let image = read_file(&mut zip, "image-data-2.html").unwrap();
+    assert!(image.contains(r#"<div style="text-align: center;"><img src="examples-simple-chapter_image.png" alt="examples-simple-chapter_image.png" /></div>"#));
+    assert!(image.contains("<title>Simple ePub demonstration</title>"));
+}
+
+#[test]
+fn v3_string_title() {
+    let mut book = simple_book();
+    book.version = EPubVersion::V3;
+    let mut zip = write(&book);
+
+    assert!(read_file(&mut zip, "string-data-1.html").unwrap().contains("<title>Chapter 1</title>"));
+}
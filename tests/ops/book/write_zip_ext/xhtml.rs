@@ -0,0 +1,55 @@
+use gen_epub_book::ops::{BookElement, EPubBook, check_epub};
+use self::super::{read_file, write};
+use std::io::Cursor;
+use chrono::DateTime;
+use zip::ZipArchive;
+
+
+fn sloppy_book(normalise_html: bool) -> EPubBook {
+    let mut book = EPubBook::from_elements(vec![BookElement::Name("Tag soup demonstration".to_string()),
+                                                BookElement::StringContent(r#"<!-- ePub title: "Chapter 1" --><p>Tom & Jerry<br><p>Redux"#.to_string()),
+                                                BookElement::StringContent("<p>Untitled".to_string()),
+                                                BookElement::StringMarkdownContent("# Chapter 2\n\n<div>Unclosed".to_string()),
+                                                BookElement::Author("nabijaczleweli".to_string(), None),
+                                                BookElement::Date(DateTime::parse_from_rfc3339("2018-07-16T18:24:51+02:00").unwrap()),
+                                                BookElement::Language("en-GB".to_string())])
+        .unwrap();
+    book.normalise_html = normalise_html;
+    book
+}
+
+#[test]
+fn normalised() {
+    let mut zip = write(&sloppy_book(true));
+
+    let first = read_file(&mut zip, "string-data-1.html").unwrap();
+    assert!(first.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                               <html xmlns=\"http://www.w3.org/1999/xhtml\"><head><title>Chapter 1</title></head>"));
+    assert!(first.contains(r#"<!-- ePub title: "Chapter 1" --><p>Tom &amp; Jerry<br /></p><p>Redux"#));
+
+    assert!(read_file(&mut zip, "string-data-2.html").unwrap().contains("<title>Tag soup demonstration</title>"));
+
+    let markdown = read_file(&mut zip, "string-markdown-data-3.html").unwrap();
+    assert!(markdown.contains("<title>Chapter 2</title>"));
+    assert!(markdown.contains("<div>Unclosed"));
+    assert!(markdown.ends_with("</div></body></html>\n"));
+}
+
+#[test]
+fn valid() {
+    let mut epub = Cursor::new(vec![]);
+    sloppy_book(true).write_zip_ext(true, &mut epub, false, &mut vec![]).unwrap();
+    assert_eq!(check_epub(&mut epub, false, &mut vec![]), Ok(()));
+}
+
+#[test]
+fn verbatim() {
+    let mut epub = Cursor::new(vec![]);
+    sloppy_book(false).write_zip_ext(true, &mut epub, false, &mut vec![]).unwrap();
+    assert!(check_epub(&mut epub, false, &mut vec![]).is_err());
+
+    let mut zip = ZipArchive::new(Cursor::new(epub.into_inner())).unwrap();
+    let first = read_file(&mut zip, "string-data-1.html").unwrap();
+    assert!(first.starts_with(r#"<html xmlns="http://www.w3.org/1999/xhtml">"#));
+    assert!(first.contains("<p>Tom & Jerry<br><p>Redux"));
+}
@@ -0,0 +1,39 @@
+use gen_epub_book::ops::{BookElement, EPubVersion, EPubBook, check_epub};
+use std::path::PathBuf;
+use std::io::Cursor;
+use chrono::DateTime;
+use self::super::epub;
+
+
+#[test]
+fn handmade() {
+    let mut out = vec![];
+    assert_eq!(check_epub(&mut epub(&[]), true, &mut out), Ok(()));
+    assert_eq!(String::from_utf8(out).unwrap(),
+               "Found package document OEBPS/content.opf.\n\
+                Found 3 manifest items.\n\
+                Checked OEBPS/text/chapter 1.xhtml.\n\
+                Checked OEBPS/text/nav.xhtml.\n\
+                Checked OEBPS/toc.ncx.\n");
+}
+
+#[test]
+fn generated() {
+    let book = EPubBook::from_elements(vec![BookElement::Name("Tom & Jerry <Redux>".to_string()),
+                                            BookElement::StringContent(r#"<!-- ePub title: "Chapter 1" --><p>Chapter text</p>"#.to_string()),
+                                            BookElement::ImageContent(PathBuf::from("examples/simple/chapter_image.png")),
+                                            BookElement::Author("nabijaczleweli".to_string(), None),
+                                            BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
+                                            BookElement::Language("en-GB".to_string())])
+        .unwrap();
+
+    for &(version, legacy_ncx) in &[(EPubVersion::V2, true), (EPubVersion::V3, true), (EPubVersion::V3, false)] {
+        let mut book = book.clone();
+        book.version = version;
+        book.legacy_ncx = legacy_ncx;
+
+        let mut buf = Cursor::new(vec![]);
+        book.write_zip_ext(true, &mut buf, false, &mut vec![]).unwrap();
+        assert_eq!(check_epub(&mut buf, false, &mut vec![]), Ok(()));
+    }
+}
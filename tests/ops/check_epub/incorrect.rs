@@ -0,0 +1,150 @@
+use self::super::{CONTAINER, CHAPTER, archive, epub};
+use gen_epub_book::ops::check_epub;
+use gen_epub_book::Error;
+use zip::CompressionMethod;
+use std::io::Cursor;
+
+
+fn invalid(file: &str, what: &'static str, more: Option<&str>) -> Result<(), Error> {
+    Err(Error::InvalidEPub {
+        file: file.to_string(),
+        what: what,
+        more: more.map(str::to_string),
+    })
+}
+
+#[test]
+fn not_zip() {
+    assert_eq!(check_epub(&mut Cursor::new(b"application/epub+zip".to_vec()), false, &mut vec![]),
+               invalid("ePub", "not a ZIP archive", None));
+}
+
+#[test]
+fn empty() {
+    assert_eq!(check_epub(&mut archive(&[]), false, &mut vec![]), invalid("ePub", "empty", None));
+}
+
+#[test]
+fn mimetype_not_first() {
+    assert_eq!(check_epub(&mut archive(&[("META-INF/container.xml", CompressionMethod::Stored, CONTAINER),
+                                         ("mimetype", CompressionMethod::Stored, "application/epub+zip")]),
+                          false,
+                          &mut vec![]),
+               invalid("mimetype", "not the first entry", None));
+}
+
+#[test]
+fn mimetype_compressed() {
+    assert_eq!(check_epub(&mut archive(&[("mimetype", CompressionMethod::Deflated, "application/epub+zip")]), false, &mut vec![]),
+               invalid("mimetype", "compressed", None));
+}
+
+#[test]
+fn mimetype_wrong_content() {
+    assert_eq!(check_epub(&mut epub(&[("mimetype", "application/zip")]), false, &mut vec![]),
+               invalid("mimetype", "has wrong content", Some("application/zip")));
+}
+
+#[test]
+fn container_missing() {
+    assert_eq!(check_epub(&mut epub(&[("META-INF/container.xml", "")]), false, &mut vec![]),
+               invalid("META-INF/container.xml", "missing", None));
+}
+
+#[test]
+fn no_rootfile() {
+    assert_eq!(check_epub(&mut epub(&[("META-INF/container.xml", "<container><rootfiles></rootfiles></container>")]), false, &mut vec![]),
+               invalid("META-INF/container.xml", "has no rootfile", None));
+}
+
+#[test]
+fn package_missing() {
+    assert_eq!(check_epub(&mut epub(&[("OEBPS/content.opf", "")]), false, &mut vec![]),
+               invalid("OEBPS/content.opf", "missing", None));
+}
+
+#[test]
+fn manifest_item_missing() {
+    assert_eq!(check_epub(&mut epub(&[("OEBPS/text/chapter 1.xhtml", "")]), false, &mut vec![]),
+               invalid("OEBPS/content.opf", "references missing file", Some("OEBPS/text/chapter 1.xhtml")));
+}
+
+#[test]
+fn unresolvable_itemref() {
+    assert_eq!(check_epub(&mut epub(&[("OEBPS/content.opf",
+                                       r#"<package><manifest><item href="toc.ncx" id="toc" media-type="application/x-dtbncx+xml"/></manifest>
+                                          <spine toc="toc"><itemref idref="chapter" /></spine></package>"#)]),
+                          false,
+                          &mut vec![]),
+               invalid("OEBPS/content.opf", "has unresolvable spine reference", Some("chapter")));
+}
+
+#[test]
+fn unresolvable_toc() {
+    assert_eq!(check_epub(&mut epub(&[("OEBPS/content.opf",
+                                       r#"<package><manifest><item href="toc.ncx" id="ncx" media-type="application/x-dtbncx+xml"/></manifest>
+                                          <spine toc="toc"></spine></package>"#)]),
+                          false,
+                          &mut vec![]),
+               invalid("OEBPS/content.opf", "has unresolvable spine reference", Some("toc")));
+}
+
+#[test]
+fn duplicate_id() {
+    assert_eq!(check_epub(&mut epub(&[("OEBPS/content.opf",
+                                       r#"<package><metadata><identifier id="toc">x</identifier></metadata>
+                                          <manifest><item href="toc.ncx" id="toc" media-type="application/x-dtbncx+xml"/></manifest></package>"#)]),
+                          false,
+                          &mut vec![]),
+               invalid("OEBPS/content.opf", "has duplicate ID", Some("toc")));
+    assert_eq!(check_epub(&mut epub(&[("OEBPS/text/chapter 1.xhtml", r#"<html><body id="top"><p id="top"></p></body></html>"#)]),
+                          false,
+                          &mut vec![]),
+               invalid("OEBPS/text/chapter 1.xhtml", "has duplicate ID", Some("top")));
+}
+
+#[test]
+fn malformed_xhtml() {
+    match check_epub(&mut epub(&[("OEBPS/text/chapter 1.xhtml", "<html><body><p>Unclosed</body></html>")]), false, &mut vec![]) {
+        Err(Error::InvalidEPub { file, what, more }) => {
+            assert_eq!(file, "OEBPS/text/chapter 1.xhtml");
+            assert_eq!(what, "not well-formed");
+            assert!(more.is_some());
+        }
+        r => panic!("{:?}", r),
+    }
+}
+
+#[test]
+fn ncx_target_missing() {
+    assert_eq!(check_epub(&mut epub(&[("OEBPS/toc.ncx", r#"<ncx><navMap><navPoint><content src="text/chapter2.xhtml"/></navPoint></navMap></ncx>"#)]),
+                          false,
+                          &mut vec![]),
+               invalid("OEBPS/toc.ncx", "references missing file", Some("OEBPS/text/chapter2.xhtml")));
+}
+
+#[test]
+fn nav_target_missing() {
+    assert_eq!(check_epub(&mut epub(&[("OEBPS/text/nav.xhtml", r#"<html><body><nav><a href="../chapter%201.xhtml">C</a></nav></body></html>"#)]),
+                          false,
+                          &mut vec![]),
+               invalid("OEBPS/text/nav.xhtml", "references missing file", Some("OEBPS/chapter 1.xhtml")));
+}
+
+#[test]
+fn nav_fragment_missing() {
+    assert_eq!(check_epub(&mut epub(&[("OEBPS/text/nav.xhtml", r#"<html><body><nav><a href="chapter%201.xhtml#bottom">C</a></nav></body></html>"#)]),
+                          false,
+                          &mut vec![]),
+               invalid("OEBPS/text/nav.xhtml", "references missing fragment", Some("OEBPS/text/chapter 1.xhtml#bottom")));
+}
+
+#[test]
+fn external_targets_ignored() {
+    assert_eq!(check_epub(&mut epub(&[("OEBPS/text/chapter 1.xhtml", CHAPTER),
+                                      ("OEBPS/text/nav.xhtml",
+                                       r#"<html><body><nav><a href="https://example.com/chapter%201.xhtml">C</a></nav></body></html>"#)]),
+                          false,
+                          &mut vec![]),
+               Ok(()));
+}
@@ -0,0 +1,77 @@
+use zip::write::{FileOptions, ZipWriter};
+use zip::CompressionMethod;
+use std::io::{Cursor, Write};
+
+mod incorrect;
+mod correct;
+
+
+static CONTAINER: &'static str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml" />
+  </rootfiles>
+</container>"#;
+
+static CHAPTER: &'static str = r#"<html xmlns="http://www.w3.org/1999/xhtml"><head></head><body id="top"><p>Chapter</p></body></html>"#;
+
+
+/// A minimal valid ePub, with the files' contents substituted according to `replace`, if any.
+fn epub(replace: &[(&str, &str)]) -> Cursor<Vec<u8>> {
+    let opf = r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" unique-identifier="uuid" version="3.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="uuid">urn:uuid:2d3b2c9b-0a7e-4c0b-9a5e-5f2f4f1d6c3a</dc:identifier>
+  </metadata>
+  <manifest>
+    <item href="toc.ncx" id="toc" media-type="application/x-dtbncx+xml"/>
+    <item href="text/nav.xhtml" id="nav" media-type="application/xhtml+xml" properties="nav" />
+    <item href="text/chapter%201.xhtml" id="chapter" media-type="application/xhtml+xml" />
+  </manifest>
+  <spine toc="toc">
+    <itemref idref="chapter" />
+  </spine>
+</package>"#;
+    let ncx = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1">
+  <navMap>
+    <navPoint id="navpoint-1" playOrder="1">
+      <navLabel><text>Chapter</text></navLabel>
+      <content src="text/chapter%201.xhtml"/>
+    </navPoint>
+  </navMap>
+</ncx>"#;
+    let nav = r#"<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops">
+  <head></head>
+  <body>
+    <nav epub:type="toc" id="toc"><ol><li><a href="chapter%201.xhtml#top">Chapter</a></li></ol></nav>
+  </body>
+</html>"#;
+
+    let files = [("mimetype", "application/epub+zip"),
+                 ("META-INF/container.xml", CONTAINER),
+                 ("OEBPS/content.opf", opf),
+                 ("OEBPS/toc.ncx", ncx),
+                 ("OEBPS/text/nav.xhtml", nav),
+                 ("OEBPS/text/chapter 1.xhtml", CHAPTER)];
+    archive(&files.iter()
+        .filter_map(|&(name, content)| match replace.iter().find(|&&(n, _)| n == name) {
+            Some(&(_, "")) => None,
+            Some(&(_, content)) => Some((name, CompressionMethod::Stored, content)),
+            None => Some((name, CompressionMethod::Stored, content)),
+        })
+        .collect::<Vec<_>>())
+}
+
+fn archive(files: &[(&str, CompressionMethod, &str)]) -> Cursor<Vec<u8>> {
+    let mut buf = Cursor::new(vec![]);
+    {
+        let mut w = ZipWriter::new(&mut buf);
+        for &(name, compression, content) in files {
+            w.start_file(name, FileOptions::default().compression_method(compression)).unwrap();
+            w.write_all(content.as_bytes()).unwrap();
+        }
+        w.finish().unwrap();
+    }
+    buf
+}
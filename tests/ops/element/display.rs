@@ -3,6 +3,7 @@ use gen_epub_book::ops::BookElement;
 use std::path::PathBuf;
 use chrono::DateTime;
 use url::Url;
+use uuid::Uuid;
 
 
 #[test]
@@ -16,12 +17,30 @@ fn content() {
                "Content: test/two/ch1.html");
 }
 
+#[test]
+fn network_content() {
+    assert_eq!(&BookElement::NetworkContent(Url::parse("https://example.com/serial/chapter-1").unwrap(), None).to_string(),
+               "Network-Content: https://example.com/serial/chapter-1");
+}
+
 #[test]
 fn string_content() {
     assert_eq!(&BookElement::StringContent("<i>Italics</i>".to_string()).to_string(),
                "String-Content: <i>Italics</i>");
 }
 
+#[test]
+fn markdown_content() {
+    assert_eq!(&BookElement::MarkdownContent(PathBuf::from("content/ch01.md")).to_string(),
+               "Markdown-Content: content/ch01.md");
+}
+
+#[test]
+fn string_markdown_content() {
+    assert_eq!(&BookElement::StringMarkdownContent("*Italics*".to_string()).to_string(),
+               "String-Markdown-Content: *Italics*");
+}
+
 #[test]
 fn image_content() {
     assert_eq!(&BookElement::ImageContent(PathBuf::from("images/ch1.png")).to_string(),
@@ -30,7 +49,7 @@ fn image_content() {
 
 #[test]
 fn network_image_content() {
-    assert_eq!(BookElement::NetworkImageContent(Url::parse(PURSUER_URL).unwrap()).to_string(),
+    assert_eq!(BookElement::NetworkImageContent(Url::parse(PURSUER_URL).unwrap(), None).to_string(),
                format!("Network-Image-Content: {}", PURSUER_URL));
 }
 
@@ -41,7 +60,7 @@ fn cover() {
 
 #[test]
 fn network_cover() {
-    assert_eq!(BookElement::NetworkImageContent(Url::parse(TAIWAN_RECYCLING_SYMBOL_URL).unwrap()).to_string(),
+    assert_eq!(BookElement::NetworkImageContent(Url::parse(TAIWAN_RECYCLING_SYMBOL_URL).unwrap(), None).to_string(),
                format!("Network-Image-Content: {}", TAIWAN_RECYCLING_SYMBOL_URL));
 }
 
@@ -52,13 +71,23 @@ fn include() {
 
 #[test]
 fn network_include() {
-    assert_eq!(BookElement::NetworkInclude(Url::parse(TAIWAN_RECYCLING_SYMBOL_URL).unwrap()).to_string(),
+    assert_eq!(BookElement::NetworkInclude(Url::parse(TAIWAN_RECYCLING_SYMBOL_URL).unwrap(), None).to_string(),
                format!("Network-Include: {}", TAIWAN_RECYCLING_SYMBOL_URL));
 }
 
 #[test]
 fn author() {
-    assert_eq!(&BookElement::Author("nabijaczleweli".to_string()).to_string(), "Author: nabijaczleweli");
+    assert_eq!(&BookElement::Author("nabijaczleweli".to_string(), None).to_string(), "Author: nabijaczleweli");
+    assert_eq!(&BookElement::Author("Terry Pratchett".to_string(), Some("Pratchett, Terry".to_string())).to_string(),
+               "Author: Terry Pratchett file-as=Pratchett, Terry");
+}
+
+#[test]
+fn contributor() {
+    assert_eq!(&BookElement::Contributor("ill".to_string(), "nicohman".to_string(), None).to_string(),
+               "Contributor: ill nicohman");
+    assert_eq!(&BookElement::Contributor("trl".to_string(), "Anthea Bell".to_string(), Some("Bell, Anthea".to_string())).to_string(),
+               "Contributor: trl Anthea Bell file-as=Bell, Anthea");
 }
 
 #[test]
@@ -71,3 +100,32 @@ fn date() {
 fn language() {
     assert_eq!(&BookElement::Language("en-GB".to_string()).to_string(), "Language: en-GB");
 }
+
+#[test]
+fn uuid() {
+    assert_eq!(&BookElement::Uuid(Uuid::parse_str("2d3b2c9b-0a7e-4c0b-9a5e-5f2f4f1d6c3a").unwrap()).to_string(),
+               "UUID: 2d3b2c9b-0a7e-4c0b-9a5e-5f2f4f1d6c3a");
+}
+
+#[test]
+fn network_pinned() {
+    assert_eq!(BookElement::NetworkInclude(Url::parse(TAIWAN_RECYCLING_SYMBOL_URL).unwrap(),
+                                           Some("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad".to_string()))
+                   .to_string(),
+               format!("Network-Include: {} sha256=ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad", TAIWAN_RECYCLING_SYMBOL_URL));
+}
+
+#[test]
+fn metadata() {
+    assert_eq!(&BookElement::Publisher("Tom & Jerry Ltd.".to_string()).to_string(), "Publisher: Tom & Jerry Ltd.");
+    assert_eq!(&BookElement::Rights("CC-BY-SA 4.0".to_string()).to_string(), "Rights: CC-BY-SA 4.0");
+    assert_eq!(&BookElement::Subject("Fantasy".to_string()).to_string(), "Subject: Fantasy");
+    assert_eq!(&BookElement::Source("urn:isbn:9780306406157".to_string()).to_string(), "Source: urn:isbn:9780306406157");
+    assert_eq!(&BookElement::Relation("https://example.com/sequel".to_string()).to_string(), "Relation: https://example.com/sequel");
+    assert_eq!(&BookElement::Isbn("9780306406157".to_string()).to_string(), "ISBN: 9780306406157");
+    assert_eq!(&BookElement::Identifier("DOI".to_string(), "10.1000/182".to_string()).to_string(), "Identifier: DOI 10.1000/182");
+    assert_eq!(&BookElement::Modified(DateTime::parse_from_rfc3339("2018-07-20T11:02:43+02:00").unwrap()).to_string(),
+               "Modified: 2018-07-20T11:02:43+02:00");
+    assert_eq!(&BookElement::Series("Discworld".to_string()).to_string(), "Series: Discworld");
+    assert_eq!(&BookElement::SeriesIndex("2.5".to_string()).to_string(), "Series-Index: 2.5");
+}
@@ -1,6 +1,7 @@
 mod display;
 mod parse;
 mod name;
+mod names;
 
 
 static PURSUER_URL: &'static str = "https://img09.deviantart.net/e6c8/i/2015/138/8/0/the_pursuer_by_artsed-d7lbiua.jpg";
@@ -3,6 +3,7 @@ use gen_epub_book::ops::BookElement;
 use std::path::PathBuf;
 use chrono::DateTime;
 use url::Url;
+use uuid::Uuid;
 
 
 #[test]
@@ -15,11 +16,26 @@ fn content() {
     assert_eq!(BookElement::Content(PathBuf::from("test/two/ch1.html")).name(), "Content");
 }
 
+#[test]
+fn network_content() {
+    assert_eq!(BookElement::NetworkContent(Url::parse("https://example.com/serial/chapter-1").unwrap(), None).name(), "Network-Content");
+}
+
 #[test]
 fn string_content() {
     assert_eq!(BookElement::StringContent("<i>Italics</i>".to_string()).name(), "String-Content");
 }
 
+#[test]
+fn markdown_content() {
+    assert_eq!(BookElement::MarkdownContent(PathBuf::from("content/ch01.md")).name(), "Markdown-Content");
+}
+
+#[test]
+fn string_markdown_content() {
+    assert_eq!(BookElement::StringMarkdownContent("*Italics*".to_string()).name(), "String-Markdown-Content");
+}
+
 #[test]
 fn image_content() {
     assert_eq!(BookElement::ImageContent(PathBuf::from("images/ch1.png")).name(), "Image-Content");
@@ -27,7 +43,7 @@ fn image_content() {
 
 #[test]
 fn network_image_content() {
-    assert_eq!(BookElement::NetworkImageContent(Url::parse(PURSUER_URL).unwrap()).name(),
+    assert_eq!(BookElement::NetworkImageContent(Url::parse(PURSUER_URL).unwrap(), None).name(),
                "Network-Image-Content");
 }
 
@@ -38,7 +54,7 @@ fn cover() {
 
 #[test]
 fn network_cover() {
-    assert_eq!(BookElement::NetworkImageContent(Url::parse(TAIWAN_RECYCLING_SYMBOL_URL).unwrap()).name(),
+    assert_eq!(BookElement::NetworkImageContent(Url::parse(TAIWAN_RECYCLING_SYMBOL_URL).unwrap(), None).name(),
                "Network-Image-Content");
 }
 
@@ -49,13 +65,18 @@ fn include() {
 
 #[test]
 fn network_include() {
-    assert_eq!(BookElement::NetworkInclude(Url::parse(TAIWAN_RECYCLING_SYMBOL_URL).unwrap()).name(),
+    assert_eq!(BookElement::NetworkInclude(Url::parse(TAIWAN_RECYCLING_SYMBOL_URL).unwrap(), None).name(),
                "Network-Include");
 }
 
 #[test]
 fn author() {
-    assert_eq!(BookElement::Author("nabijaczleweli".to_string()).name(), "Author");
+    assert_eq!(BookElement::Author("nabijaczleweli".to_string(), None).name(), "Author");
+}
+
+#[test]
+fn contributor() {
+    assert_eq!(BookElement::Contributor("trl".to_string(), "nabijaczleweli".to_string(), None).name(), "Contributor");
 }
 
 #[test]
@@ -68,3 +89,22 @@ fn date() {
 fn language() {
     assert_eq!(BookElement::Language("en-GB".to_string()).name(), "Language");
 }
+
+#[test]
+fn uuid() {
+    assert_eq!(BookElement::Uuid(Uuid::parse_str("2d3b2c9b-0a7e-4c0b-9a5e-5f2f4f1d6c3a").unwrap()).name(), "UUID");
+}
+
+#[test]
+fn metadata() {
+    assert_eq!(BookElement::Publisher("Tom & Jerry Ltd.".to_string()).name(), "Publisher");
+    assert_eq!(BookElement::Rights("CC-BY-SA 4.0".to_string()).name(), "Rights");
+    assert_eq!(BookElement::Subject("Fantasy".to_string()).name(), "Subject");
+    assert_eq!(BookElement::Source("urn:isbn:9780306406157".to_string()).name(), "Source");
+    assert_eq!(BookElement::Relation("https://example.com/sequel".to_string()).name(), "Relation");
+    assert_eq!(BookElement::Isbn("9780306406157".to_string()).name(), "ISBN");
+    assert_eq!(BookElement::Identifier("DOI".to_string(), "10.1000/182".to_string()).name(), "Identifier");
+    assert_eq!(BookElement::Modified(DateTime::parse_from_rfc3339("2018-07-20T11:02:43+02:00").unwrap()).name(), "Modified");
+    assert_eq!(BookElement::Series("Discworld".to_string()).name(), "Series");
+    assert_eq!(BookElement::SeriesIndex("2.5".to_string()).name(), "Series-Index");
+}
@@ -0,0 +1,88 @@
+use gen_epub_book::ops::BookElement;
+use std::path::PathBuf;
+use chrono::DateTime;
+use url::Url;
+use uuid::Uuid;
+
+
+#[test]
+fn unique() {
+    let names = BookElement::names();
+    for (i, name) in names.iter().enumerate() {
+        assert!(!names[i + 1..].contains(name), "{} repeated", name);
+    }
+}
+
+#[test]
+fn complete() {
+    let url = Url::parse("https://example.com").unwrap();
+    let date = DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap();
+    let elements = vec![BookElement::Name("value".to_string()),
+                        BookElement::Content(PathBuf::from("value")),
+                        BookElement::NetworkContent(url.clone(), None),
+                        BookElement::StringContent("value".to_string()),
+                        BookElement::MarkdownContent(PathBuf::from("value")),
+                        BookElement::StringMarkdownContent("value".to_string()),
+                        BookElement::ImageContent(PathBuf::from("value")),
+                        BookElement::NetworkImageContent(url.clone(), None),
+                        BookElement::Cover(PathBuf::from("value")),
+                        BookElement::NetworkCover(url.clone(), None),
+                        BookElement::Include(PathBuf::from("value")),
+                        BookElement::NetworkInclude(url.clone(), None),
+                        BookElement::Description(PathBuf::from("value")),
+                        BookElement::StringDescription("value".to_string()),
+                        BookElement::NetworkDescription(url, None),
+                        BookElement::Author("value".to_string(), None),
+                        BookElement::Contributor("value".to_string(), "edt".to_string(), None),
+                        BookElement::Date(date),
+                        BookElement::Language("value".to_string()),
+                        BookElement::Uuid(Uuid::parse_str("8a71c1ae-9da1-4b7c-88f8-4d69bbfd40b4").unwrap()),
+                        BookElement::Publisher("value".to_string()),
+                        BookElement::Rights("value".to_string()),
+                        BookElement::Subject("value".to_string()),
+                        BookElement::Source("value".to_string()),
+                        BookElement::Relation("value".to_string()),
+                        BookElement::Isbn("value".to_string()),
+                        BookElement::Identifier("value".to_string(), "value".to_string()),
+                        BookElement::Modified(date),
+                        BookElement::Series("value".to_string()),
+                        BookElement::SeriesIndex("value".to_string())];
+
+    assert_eq!(elements.len(), BookElement::names().len());
+    for elem in elements {
+        assert!(BookElement::names().contains(&elem.name()), "{} missing", elem.name());
+    }
+}
+
+#[test]
+fn parseable() {
+    for name in BookElement::names() {
+        let value = match *name {
+            "Network-Content" | "Network-Image-Content" | "Network-Cover" | "Network-Include" | "Network-Description" => "https://example.com",
+            "Contributor" => "edt Editor",
+            "Date" | "Modified" => "2017-02-08T15:30:18+01:00",
+            "UUID" => "8a71c1ae-9da1-4b7c-88f8-4d69bbfd40b4",
+            "ISBN" => "978-3-16-148410-0",
+            "Identifier" => "DOI 10.1000/182",
+            "Series-Index" => "2",
+            _ => "value",
+        };
+        assert_eq!(BookElement::parse(&format!("{}: {}", name, value), ":", false).unwrap().unwrap().name(), *name);
+    }
+}
+
+#[test]
+fn similar() {
+    assert_eq!(BookElement::similar_name("Contnet"), Some("Content"));
+    assert_eq!(BookElement::similar_name("Autor"), Some("Author"));
+    assert_eq!(BookElement::similar_name("Network-Contents"), Some("Network-Content"));
+    assert_eq!(BookElement::similar_name("uuid"), Some("UUID"));
+    assert_eq!(BookElement::similar_name("Isbn"), Some("ISBN"));
+}
+
+#[test]
+fn dissimilar() {
+    assert_eq!(BookElement::similar_name("Title"), None);
+    assert_eq!(BookElement::similar_name("what"), None);
+    assert_eq!(BookElement::similar_name("Chapter"), None);
+}
@@ -3,6 +3,7 @@ use gen_epub_book::ops::BookElement;
 use std::path::PathBuf;
 use chrono::DateTime;
 use url::Url;
+use uuid::Uuid;
 
 
 #[test]
@@ -17,12 +18,30 @@ fn content() {
                Ok(Some(BookElement::Content(PathBuf::from("test/two/ch1.html")))));
 }
 
+#[test]
+fn network_content() {
+    assert_eq!(BookElement::parse("Network-Content: https://example.com/serial/chapter-1", ":", false),
+               Ok(Some(BookElement::NetworkContent(Url::parse("https://example.com/serial/chapter-1").unwrap(), None))));
+}
+
 #[test]
 fn string_content() {
     assert_eq!(BookElement::parse("String-Content: <i>Italics</i>", ":", false),
                Ok(Some(BookElement::StringContent("<i>Italics</i>".to_string()))));
 }
 
+#[test]
+fn markdown_content() {
+    assert_eq!(BookElement::parse("Markdown-Content: content/ch01.md", ":", false),
+               Ok(Some(BookElement::MarkdownContent(PathBuf::from("content/ch01.md")))));
+}
+
+#[test]
+fn string_markdown_content() {
+    assert_eq!(BookElement::parse("String-Markdown-Content: *Italics*", ":", false),
+               Ok(Some(BookElement::StringMarkdownContent("*Italics*".to_string()))));
+}
+
 #[test]
 fn image_content() {
     assert_eq!(BookElement::parse("Image-Content: images/ch1.png", ":", false),
@@ -32,7 +51,7 @@ fn image_content() {
 #[test]
 fn network_image_content() {
     assert_eq!(BookElement::parse(&format!("Network-Image-Content: {}", PURSUER_URL), ":", false),
-               Ok(Some(BookElement::NetworkImageContent(Url::parse(PURSUER_URL).unwrap()))));
+               Ok(Some(BookElement::NetworkImageContent(Url::parse(PURSUER_URL).unwrap(), None))));
 }
 
 #[test]
@@ -44,7 +63,7 @@ fn cover() {
 #[test]
 fn network_cover() {
     assert_eq!(BookElement::parse(&format!("Network-Cover: {}", TAIWAN_RECYCLING_SYMBOL_URL), ":", false),
-               Ok(Some(BookElement::NetworkCover(Url::parse(TAIWAN_RECYCLING_SYMBOL_URL).unwrap()))));
+               Ok(Some(BookElement::NetworkCover(Url::parse(TAIWAN_RECYCLING_SYMBOL_URL).unwrap(), None))));
 }
 
 #[test]
@@ -56,13 +75,25 @@ fn include() {
 #[test]
 fn network_include() {
     assert_eq!(BookElement::parse(&format!("Network-Include: {}", TAIWAN_RECYCLING_SYMBOL_URL), ":", false),
-               Ok(Some(BookElement::NetworkInclude(Url::parse(TAIWAN_RECYCLING_SYMBOL_URL).unwrap()))));
+               Ok(Some(BookElement::NetworkInclude(Url::parse(TAIWAN_RECYCLING_SYMBOL_URL).unwrap(), None))));
 }
 
 #[test]
 fn author() {
     assert_eq!(BookElement::parse("Author: nabijaczleweli", ":", false),
-               Ok(Some(BookElement::Author("nabijaczleweli".to_string()))));
+               Ok(Some(BookElement::Author("nabijaczleweli".to_string(), None))));
+    assert_eq!(BookElement::parse("Author: Terry Pratchett  file-as= Pratchett, Terry ", ":", false),
+               Ok(Some(BookElement::Author("Terry Pratchett".to_string(), Some("Pratchett, Terry".to_string())))));
+    assert_eq!(BookElement::parse("Author: Profile-as=nabijaczleweli", ":", false),
+               Ok(Some(BookElement::Author("Profile-as=nabijaczleweli".to_string(), None))));
+}
+
+#[test]
+fn contributor() {
+    assert_eq!(BookElement::parse("Contributor: ill nicohman", ":", false),
+               Ok(Some(BookElement::Contributor("ill".to_string(), "nicohman".to_string(), None))));
+    assert_eq!(BookElement::parse("Contributor: TRL Anthea Bell file-as=Bell, Anthea", ":", false),
+               Ok(Some(BookElement::Contributor("trl".to_string(), "Anthea Bell".to_string(), Some("Bell, Anthea".to_string())))));
 }
 
 #[test]
@@ -86,3 +117,68 @@ fn language() {
     assert_eq!(BookElement::parse("Language: en-GB", ":", false),
                Ok(Some(BookElement::Language("en-GB".to_string()))));
 }
+
+#[test]
+fn uuid() {
+    assert_eq!(BookElement::parse("UUID: 2d3b2c9b-0a7e-4c0b-9a5e-5f2f4f1d6c3a", ":", false),
+               Ok(Some(BookElement::Uuid(Uuid::parse_str("2d3b2c9b-0a7e-4c0b-9a5e-5f2f4f1d6c3a").unwrap()))));
+    assert_eq!(BookElement::parse("UUID: 2D3B2C9B-0A7E-4C0B-9A5E-5F2F4F1D6C3A", ":", false),
+               Ok(Some(BookElement::Uuid(Uuid::parse_str("2d3b2c9b-0a7e-4c0b-9a5e-5f2f4f1d6c3a").unwrap()))));
+}
+
+#[test]
+fn network_pinned() {
+    assert_eq!(BookElement::parse(&format!("Network-Cover: {}  sha256={}",
+                                           TAIWAN_RECYCLING_SYMBOL_URL,
+                                           "BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD"),
+                                  ":",
+                                  false),
+               Ok(Some(BookElement::NetworkCover(Url::parse(TAIWAN_RECYCLING_SYMBOL_URL).unwrap(),
+                                                 Some("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad".to_string())))));
+}
+
+#[test]
+fn metadata() {
+    assert_eq!(BookElement::parse("Publisher: Tom & Jerry Ltd.", ":", false),
+               Ok(Some(BookElement::Publisher("Tom & Jerry Ltd.".to_string()))));
+    assert_eq!(BookElement::parse("Rights: CC-BY-SA 4.0", ":", false),
+               Ok(Some(BookElement::Rights("CC-BY-SA 4.0".to_string()))));
+    assert_eq!(BookElement::parse("Subject: Fantasy", ":", false),
+               Ok(Some(BookElement::Subject("Fantasy".to_string()))));
+    assert_eq!(BookElement::parse("Source: urn:isbn:9780306406157", ":", false),
+               Ok(Some(BookElement::Source("urn:isbn:9780306406157".to_string()))));
+    assert_eq!(BookElement::parse("Relation: https://example.com/sequel", ":", false),
+               Ok(Some(BookElement::Relation("https://example.com/sequel".to_string()))));
+    assert_eq!(BookElement::parse("Series: Discworld", ":", false),
+               Ok(Some(BookElement::Series("Discworld".to_string()))));
+}
+
+#[test]
+fn isbn() {
+    assert_eq!(BookElement::parse("ISBN: 978-0-306-40615-7", ":", false),
+               Ok(Some(BookElement::Isbn("9780306406157".to_string()))));
+    assert_eq!(BookElement::parse("ISBN: 0 8044 2957 x", ":", false),
+               Ok(Some(BookElement::Isbn("080442957X".to_string()))));
+}
+
+#[test]
+fn identifier() {
+    assert_eq!(BookElement::parse("Identifier: DOI  10.1000/182", ":", false),
+               Ok(Some(BookElement::Identifier("DOI".to_string(), "10.1000/182".to_string()))));
+}
+
+#[test]
+fn modified() {
+    assert_eq!(BookElement::parse("Modified: 2018-07-20T11:02:43+02:00", ":", false),
+               Ok(Some(BookElement::Modified(DateTime::parse_from_rfc3339("2018-07-20T11:02:43+02:00").unwrap()))));
+    assert_eq!(BookElement::parse("Modified: 1532077363+0200", ":", true),
+               Ok(Some(BookElement::Modified(DateTime::parse_from_rfc3339("2018-07-20T11:02:43+02:00").unwrap()))));
+}
+
+#[test]
+fn series_index() {
+    assert_eq!(BookElement::parse("Series-Index: 3", ":", false),
+               Ok(Some(BookElement::SeriesIndex("3".to_string()))));
+    assert_eq!(BookElement::parse("Series-Index: 2.5", ":", false),
+               Ok(Some(BookElement::SeriesIndex("2.5".to_string()))));
+}
@@ -31,3 +31,79 @@ fn datetime_free() {
                    more: Some("not RFC3339, RFC2822, nor Unix timestamp w/timezone"),
                }));
 }
+
+#[test]
+fn uuid() {
+    assert_eq!(BookElement::parse("UUID: 2d3b2c9b-0a7e-4c0b-9a5e", ":", false),
+               Err(Error::Parse {
+                   tp: "UUID",
+                   wher: "book element",
+                   more: None,
+               }));
+}
+
+#[test]
+fn sha256() {
+    for hash in &["00",
+                  "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad00",
+                  "zz7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"] {
+        assert_eq!(BookElement::parse(&format!("Network-Cover: http://i.imgur.com/ViQ2WED.jpg sha256={}", hash),
+                                      ":",
+                                      false),
+                   Err(Error::Parse {
+                       tp: "SHA-256 hash",
+                       wher: "book element",
+                       more: Some("not 64 hexadecimal digits"),
+                   }));
+    }
+}
+
+#[test]
+fn contributor() {
+    assert_eq!(BookElement::parse("Contributor: translator Anthea Bell", ":", false),
+               Err(Error::Parse {
+                   tp: "MARC relator code",
+                   wher: "book element",
+                   more: Some("not three letters"),
+               }));
+    assert_eq!(BookElement::parse("Contributor: trl", ":", false),
+               Err(Error::Parse {
+                   tp: "contributor",
+                   wher: "book element",
+                   more: Some("no name"),
+               }));
+}
+
+#[test]
+fn isbn() {
+    for isbn in &["978-0-306-40615-8", "0-8044-2957-1", "97803064061", "X780306406157", "978030640615X"] {
+        assert_eq!(BookElement::parse(&format!("ISBN: {}", isbn), ":", false),
+                   Err(Error::Parse {
+                       tp: "ISBN",
+                       wher: "book element",
+                       more: Some("not a valid ISBN-10 or ISBN-13"),
+                   }));
+    }
+}
+
+#[test]
+fn identifier() {
+    assert_eq!(BookElement::parse("Identifier: 10.1000/182", ":", false),
+               Err(Error::Parse {
+                   tp: "identifier",
+                   wher: "book element",
+                   more: Some("no scheme"),
+               }));
+}
+
+#[test]
+fn series_index() {
+    for index in &["third", "2.", ".5", "1.2.3", "-1"] {
+        assert_eq!(BookElement::parse(&format!("Series-Index: {}", index), ":", false),
+                   Err(Error::Parse {
+                       tp: "series index",
+                       wher: "book element",
+                       more: Some("not a decimal number"),
+                   }));
+    }
+}
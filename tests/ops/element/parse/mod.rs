@@ -6,7 +6,7 @@ mod elements;
 
 #[test]
 fn unrecognised() {
-    assert_eq!(BookElement::parse("Network-Content: https://nabijaczleweli.xyz/capitalism/extensive_list_of_rust_puns/index.html",
+    assert_eq!(BookElement::parse("Network-Chapter: https://nabijaczleweli.xyz/capitalism/extensive_list_of_rust_puns/index.html",
                                   ":",
                                   false),
                Ok(None));
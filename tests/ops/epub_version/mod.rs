@@ -0,0 +1,32 @@
+use gen_epub_book::ops::EPubVersion;
+use gen_epub_book::Error;
+
+
+#[test]
+fn parse() {
+    assert_eq!("2".parse(), Ok(EPubVersion::V2));
+    assert_eq!("3".parse(), Ok(EPubVersion::V3));
+}
+
+#[test]
+fn parse_incorrect() {
+    for v in &["", "1", "2.0", "3.1", "three"] {
+        assert_eq!(v.parse::<EPubVersion>(),
+                   Err(Error::Parse {
+                       tp: "ePub version",
+                       wher: "book",
+                       more: Some("neither 2 nor 3"),
+                   }));
+    }
+}
+
+#[test]
+fn display() {
+    assert_eq!(&EPubVersion::V2.to_string(), "2");
+    assert_eq!(&EPubVersion::V3.to_string(), "3");
+}
+
+#[test]
+fn default() {
+    assert_eq!(EPubVersion::default(), EPubVersion::V2);
+}
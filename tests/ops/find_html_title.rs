@@ -0,0 +1,33 @@
+use gen_epub_book::ops::{find_html_title_ext, find_html_title};
+use std::fs::File;
+
+
+#[test]
+fn title() {
+    assert_eq!(find_html_title(&mut &b"<!DOCTYPE html><html><head><title>Chapter 1</title></head><body><h1>Chapter 2</h1></body></html>"[..]),
+               Some("Chapter 1".to_string()));
+    assert_eq!(find_html_title(&mut &b"<title>\n  Chapter 1:\n  The &lt;Beginning&gt;\n</title>"[..]),
+               Some("Chapter 1: The <Beginning>".to_string()));
+}
+
+#[test]
+fn comment() {
+    assert_eq!(find_html_title(&mut File::open("examples/simple/ctnt.html").unwrap()),
+               Some("Chapter 1, Where everything's still going mostly right".to_string()));
+    assert_eq!(find_html_title(&mut &b"<title>Web page</title><!-- ePub title: \"Chapter 1\" -->"[..]),
+               Some("Chapter 1".to_string()));
+}
+
+#[test]
+fn non_containing() {
+    assert_eq!(find_html_title(&mut &b"<h1>Heading</h1><p>Just text</p>"[..]), None);
+    assert_eq!(find_html_title(&mut &b"<title> </title>"[..]), None);
+    assert_eq!(find_html_title(&mut &b""[..]), None);
+}
+
+#[test]
+fn level() {
+    assert_eq!(find_html_title_ext(&mut &b"<title>Section 1.1</title>"[..]), Some(("Section 1.1".to_string(), 1)));
+    assert_eq!(find_html_title_ext(&mut &b"<!-- ePub title: \"Section 1.1\" level: 3 --><title>Web page</title>"[..]),
+               Some(("Section 1.1".to_string(), 3)));
+}
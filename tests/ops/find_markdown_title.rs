@@ -0,0 +1,32 @@
+use gen_epub_book::ops::{find_markdown_title_ext, find_markdown_title};
+use std::fs::File;
+
+
+#[test]
+fn heading() {
+    assert_eq!(find_markdown_title(&mut File::open("examples/markdown/first.md").unwrap()),
+               Some("Chapter 1, Where it begins".to_string()));
+    assert_eq!(find_markdown_title(&mut &b"Text\n\n### `Code` &amp; *emphasis*\n\n# Second"[..]),
+               Some("Code & emphasis".to_string()));
+}
+
+#[test]
+fn comment() {
+    assert_eq!(find_markdown_title(&mut File::open("examples/markdown/second.md").unwrap()),
+               Some("Chapter 2, Explicitly named".to_string()));
+}
+
+#[test]
+fn non_containing() {
+    assert_eq!(find_markdown_title(&mut &b"Just text\n\n    # Code block\n"[..]), None);
+    assert_eq!(find_markdown_title(&mut &b""[..]), None);
+}
+
+#[test]
+fn level() {
+    assert_eq!(find_markdown_title_ext(&mut File::open("examples/markdown/first.md").unwrap()),
+               Some(("Chapter 1, Where it begins".to_string(), 1)));
+    assert_eq!(find_markdown_title_ext(&mut &b"### Section 1.1"[..]), Some(("Section 1.1".to_string(), 1)));
+    assert_eq!(find_markdown_title_ext(&mut &b"<!-- ePub title: \"Section 1.1\" level: 3 -->\n\n# Heading"[..]),
+               Some(("Section 1.1".to_string(), 3)));
+}
@@ -1,4 +1,4 @@
-use gen_epub_book::ops::find_title;
+use gen_epub_book::ops::{find_title_ext, find_title};
 use std::fs::File;
 
 
@@ -24,3 +24,15 @@ fn non_containing() {
     assert_eq!(find_title(&mut &br#"<!-- ePub title: "TOC_NAME -->"#[..]), None);
     assert_eq!(find_title(&mut &br#"<!-- ePub title: "" -->"#[..]), None);
 }
+
+#[test]
+fn level() {
+    assert_eq!(find_title(&mut &br#"<!-- ePub title: "TOC_NAME" level: 2 -->"#[..]), Some("TOC_NAME".to_string()));
+    assert_eq!(find_title_ext(&mut &br#"<!-- ePub title: "TOC_NAME" -->"#[..]), Some(("TOC_NAME".to_string(), 1)));
+    assert_eq!(find_title_ext(&mut &br#"<!-- ePub title: "TOC_NAME" level: 1 -->"#[..]), Some(("TOC_NAME".to_string(), 1)));
+    assert_eq!(find_title_ext(&mut &br#"<!-- ePub title: "TOC_NAME" level: 12 -->"#[..]), Some(("TOC_NAME".to_string(), 12)));
+    assert_eq!(find_title_ext(&mut &br#"<!-- ePub title: "TOC_NAME" level: 0 -->"#[..]), None);
+    assert_eq!(find_title_ext(&mut &br#"<!-- ePub title: "TOC_NAME" level: -1 -->"#[..]), None);
+    assert_eq!(find_title_ext(&mut &br#"<!-- ePub title: "TOC_NAME" level: 99999999999999999999999 -->"#[..]),
+               Some(("TOC_NAME".to_string(), 1)));
+}
@@ -1,5 +1,10 @@
 mod parse_descriptor;
+mod parse_descriptor_ext;
 mod include_dir;
+mod epub_version;
+mod check_epub;
+mod find_html_title;
+mod find_markdown_title;
 mod find_title;
 mod element;
 mod book;
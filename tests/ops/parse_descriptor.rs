@@ -1,28 +1,90 @@
-use gen_epub_book::ops::{BookElement, parse_descriptor};
+use gen_epub_book::ops::{IncludeDirectory, BookElement, DescriptorConfig, parse_descriptor, parse_descriptor_ext};
 use gen_epub_book::Error;
+use std::fs::{self, File};
 use std::path::PathBuf;
+use std::env::{self, temp_dir};
+use std::io::Write;
 use chrono::DateTime;
 
 
 #[test]
 fn bubbling() {
     assert_eq!(parse_descriptor("test string", &mut &b"Date: Thu, 29 Dec 2016 15:37:19 +0100\n"[..], ":", false),
-               Err(Error::Parse {
-                   tp: "datetime",
-                   wher: "book element",
-                   more: Some("not RFC3339"),
+               Err(Error::Descriptor {
+                   descriptor: "test string".to_string(),
+                   line: 1,
+                   column: 7,
+                   text: "Date: Thu, 29 Dec 2016 15:37:19 +0100".to_string(),
+                   key: "Date".to_string(),
+                   value: "Thu, 29 Dec 2016 15:37:19 +0100".to_string(),
+                   error: Box::new(Error::Parse {
+                       tp: "datetime",
+                       wher: "book element",
+                       more: Some("not RFC3339"),
+                   }),
                }));
     assert_eq!(parse_descriptor("test string", &mut &b"Date: 994518299\n"[..], ":", true),
-               Err(Error::Parse {
-                   tp: "datetime",
-                   wher: "book element",
-                   more: Some("not RFC3339, RFC2822, nor Unix timestamp w/timezone"),
+               Err(Error::Descriptor {
+                   descriptor: "test string".to_string(),
+                   line: 1,
+                   column: 7,
+                   text: "Date: 994518299".to_string(),
+                   key: "Date".to_string(),
+                   value: "994518299".to_string(),
+                   error: Box::new(Error::Parse {
+                       tp: "datetime",
+                       wher: "book element",
+                       more: Some("not RFC3339, RFC2822, nor Unix timestamp w/timezone"),
+                   }),
                }));
     assert_eq!(parse_descriptor("test string", &mut &b"Network-Cover: http/i.imgur.com/ViQ2WED.jpg\n"[..], ":", false),
-               Err(Error::Parse {
-                   tp: "URL",
-                   wher: "book element",
-                   more: None,
+               Err(Error::Descriptor {
+                   descriptor: "test string".to_string(),
+                   line: 1,
+                   column: 16,
+                   text: "Network-Cover: http/i.imgur.com/ViQ2WED.jpg".to_string(),
+                   key: "Network-Cover".to_string(),
+                   value: "http/i.imgur.com/ViQ2WED.jpg".to_string(),
+                   error: Box::new(Error::Parse {
+                       tp: "URL",
+                       wher: "book element",
+                       more: None,
+                   }),
+               }));
+}
+
+#[test]
+fn position() {
+    assert_eq!(parse_descriptor("book.epupp",
+                                &mut &b"Name: Position\n\n\t  Language  :\t  en-GB\nDate :  yesterday  \n"[..],
+                                ":",
+                                false),
+               Err(Error::Descriptor {
+                   descriptor: "book.epupp".to_string(),
+                   line: 4,
+                   column: 9,
+                   text: "Date :  yesterday  ".to_string(),
+                   key: "Date".to_string(),
+                   value: "yesterday".to_string(),
+                   error: Box::new(Error::Parse {
+                       tp: "datetime",
+                       wher: "book element",
+                       more: Some("not RFC3339"),
+                   }),
+               }));
+    assert_eq!(parse_descriptor("book.epupp", &mut &"Name: Pozycja\n  Autor: Żółć\nDate → jutro\n".as_bytes()[..], "→", false),
+               Err(Error::Descriptor {
+                   descriptor: "book.epupp".to_string(),
+                   line: 3,
+                   column: 8,
+                   text: "Date → jutro".to_string(),
+                   key: "Date".to_string(),
+                   value: "jutro".to_string(),
+                   error: Box::new(Error::Parse {
+                       tp: "datetime",
+                       wher: "book element",
+                       more: Some("not RFC3339"),
+                   }),
                }));
 }
 
@@ -46,7 +108,8 @@ Date: 2017-02-08T15:30:18+01:00
 Language: en-GB
 "
                                           [..],
-                                ":", false),
+                                ":",
+                                false),
                Ok(parsed()));
 
     assert_eq!(parse_descriptor("test string",
@@ -67,17 +130,636 @@ Date - - - > Wed, 08 Feb 2017 15:30:18 +0100
 Language - - - > en-GB
 "
                                           [..],
-                                "- - - >", true),
+                                "- - - >",
+                                true),
                Ok(parsed()));
 }
 
+#[test]
+fn glob_verbatim() {
+    assert_eq!(parse_descriptor("test string", &mut &b"Content: examples/simple/*.html\nImage-Content: nonexistent/*.png\n"[..], ":", false),
+               Ok(vec![BookElement::Content(PathBuf::from("examples/simple/*.html")),
+                       BookElement::ImageContent(PathBuf::from("nonexistent/*.png"))]));
+}
+
+#[test]
+fn strict() {
+    assert_eq!(parse_descriptor_ext("test string",
+                                    &mut &b"
+Name: Simple ePub demonstration
+Cover: cover.png
+
+Image-Content: examples/simple/chapter_image.png
+Content: simple/ctnt.html
+
+Author: nabijaczleweli
+Date: 2017-02-08T15:30:18+01:00
+Language: en-GB
+"
+                                          [..],
+                                    ":",
+                                    false,
+                                    &DescriptorConfig {
+                                        strict: true,
+                                        ..DescriptorConfig::default()
+                                    }),
+               Ok(parsed()));
+}
+
+#[test]
+fn strict_unknown() {
+    assert_eq!(parse_descriptor_ext("test string",
+                                    &mut &b"Name: Typo\nContnet: ch3.html\n"[..],
+                                    ":",
+                                    false,
+                                    &DescriptorConfig {
+                                        strict: true,
+                                        ..DescriptorConfig::default()
+                                    }),
+               Err(Error::Descriptor {
+                   descriptor: "test string".to_string(),
+                   line: 2,
+                   column: 1,
+                   text: "Contnet: ch3.html".to_string(),
+                   key: "Contnet".to_string(),
+                   value: "ch3.html".to_string(),
+                   error: Box::new(Error::UnknownElement {
+                       name: "Contnet".to_string(),
+                       suggestion: Some("Content"),
+                   }),
+               }));
+    assert_eq!(parse_descriptor_ext("test string",
+                                    &mut &b"  Title: Unknown\n"[..],
+                                    ":",
+                                    false,
+                                    &DescriptorConfig {
+                                        strict: true,
+                                        ..DescriptorConfig::default()
+                                    }),
+               Err(Error::Descriptor {
+                   descriptor: "test string".to_string(),
+                   line: 1,
+                   column: 3,
+                   text: "  Title: Unknown".to_string(),
+                   key: "Title".to_string(),
+                   value: "Unknown".to_string(),
+                   error: Box::new(Error::UnknownElement {
+                       name: "Title".to_string(),
+                       suggestion: None,
+                   }),
+               }));
+}
+
+#[test]
+fn strict_malformed() {
+    assert_eq!(parse_descriptor_ext("test string",
+                                    &mut &b"Name: Malformed\nsimple/ctnt.html\n"[..],
+                                    ":",
+                                    false,
+                                    &DescriptorConfig {
+                                        strict: true,
+                                        ..DescriptorConfig::default()
+                                    }),
+               Err(Error::Descriptor {
+                   descriptor: "test string".to_string(),
+                   line: 2,
+                   column: 1,
+                   text: "simple/ctnt.html".to_string(),
+                   key: "".to_string(),
+                   value: "simple/ctnt.html".to_string(),
+                   error: Box::new(Error::Parse {
+                       tp: "element",
+                       wher: "descriptor",
+                       more: Some("no separator"),
+                   }),
+               }));
+    assert_eq!(parse_descriptor_ext("test string",
+                                    &mut &b"Name: Malformed\n: simple/ctnt.html\n"[..],
+                                    ":",
+                                    false,
+                                    &DescriptorConfig {
+                                        strict: true,
+                                        ..DescriptorConfig::default()
+                                    }),
+               Err(Error::Descriptor {
+                   descriptor: "test string".to_string(),
+                   line: 2,
+                   column: 3,
+                   text: ": simple/ctnt.html".to_string(),
+                   key: "".to_string(),
+                   value: "simple/ctnt.html".to_string(),
+                   error: Box::new(Error::Parse {
+                       tp: "element",
+                       wher: "descriptor",
+                       more: Some("no key"),
+                   }),
+               }));
+    assert_eq!(parse_descriptor_ext("test string",
+                                    &mut &b"Name: Malformed\nContent:  \n"[..],
+                                    ":",
+                                    false,
+                                    &DescriptorConfig {
+                                        strict: true,
+                                        ..DescriptorConfig::default()
+                                    }),
+               Err(Error::Descriptor {
+                   descriptor: "test string".to_string(),
+                   line: 2,
+                   column: 9,
+                   text: "Content:  ".to_string(),
+                   key: "Content".to_string(),
+                   value: "".to_string(),
+                   error: Box::new(Error::Parse {
+                       tp: "element",
+                       wher: "descriptor",
+                       more: Some("empty value"),
+                   }),
+               }));
+}
+
+
+#[test]
+fn comments() {
+    assert_eq!(parse_descriptor_ext("test string",
+                                    &mut &b"# Simple ePub demonstration
+Name: Simple ePub demonstration
+Cover: cover.png
+  # Cover: other.png
+
+Image-Content: examples/simple/chapter_image.png
+#Content: simple/skipped.html
+Content: simple/ctnt.html
+
+Author: nabijaczleweli
+Date: 2017-02-08T15:30:18+01:00
+	# and the language:
+Language: en-GB
+"
+                                          [..],
+                                    ":",
+                                    false,
+                                    &DescriptorConfig {
+                                        strict: true,
+                                        ..DescriptorConfig::default()
+                                    }),
+               Ok(parsed()));
+}
+
+#[test]
+fn heredoc() {
+    assert_eq!(parse_descriptor_ext("test string",
+                                    &mut &b"Name: Heredoc
+String-Description: <<END
+First paragraph,
+  with a second line.
+
+# Not a comment
+Content: not an element
+END
+String-Content:<<ch-1_end
+<p>Chapter 1</p>
+  ch-1_end
+Language: en-GB
+"
+                                          [..],
+                                    ":",
+                                    false,
+                                    &DescriptorConfig {
+                                        strict: true,
+                                        ..DescriptorConfig::default()
+                                    }),
+               Ok(vec![BookElement::Name("Heredoc".to_string()),
+                       BookElement::StringDescription("First paragraph,\n  with a second line.\n\n# Not a comment\nContent: not an element"
+                           .to_string()),
+                       BookElement::StringContent("<p>Chapter 1</p>".to_string()),
+                       BookElement::Language("en-GB".to_string())]));
+
+    assert_eq!(parse_descriptor("test string", &mut &b"String-Content - - - > <<EOF\n<p>One</p>\n<p>Two</p>\nEOF\n"[..], "- - - >", false),
+               Ok(vec![BookElement::StringContent("<p>One</p>\n<p>Two</p>".to_string())]));
+}
+
+#[test]
+fn heredoc_indented() {
+    assert_eq!(parse_descriptor("test string",
+                                &mut &b"String-Content: <<END
+    <pre>
+  Indented</pre>
+END
+"
+                                          [..],
+                                ":",
+                                false),
+               Ok(vec![BookElement::StringContent("    <pre>\n  Indented</pre>".to_string())]));
+}
+
+#[test]
+fn heredoc_not() {
+    assert_eq!(parse_descriptor("test string", &mut &b"String-Content: <<b>Bold</b>\nString-Description: <<\n"[..], ":", false),
+               Ok(vec![BookElement::StringContent("<<b>Bold</b>".to_string()), BookElement::StringDescription("<<".to_string())]));
+}
+
+#[test]
+fn heredoc_unterminated() {
+    assert_eq!(parse_descriptor("test string", &mut &b"Name: Heredoc\nString-Description: <<END\nFirst paragraph.\nEND.\n"[..], ":", false),
+               Err(Error::Descriptor {
+                   descriptor: "test string".to_string(),
+                   line: 2,
+                   column: 21,
+                   text: "String-Description: <<END".to_string(),
+                   key: "String-Description".to_string(),
+                   value: "<<END".to_string(),
+                   error: Box::new(Error::Parse {
+                       tp: "heredoc",
+                       wher: "descriptor",
+                       more: Some("no terminator"),
+                   }),
+               }));
+}
+
+#[test]
+fn heredoc_error() {
+    assert_eq!(parse_descriptor("test string", &mut &b"Date: <<END\nyesterday\nEND\n"[..], ":", false),
+               Err(Error::Descriptor {
+                   descriptor: "test string".to_string(),
+                   line: 1,
+                   column: 7,
+                   text: "Date: <<END".to_string(),
+                   key: "Date".to_string(),
+                   value: "<<END".to_string(),
+                   error: Box::new(Error::Parse {
+                       tp: "datetime",
+                       wher: "book element",
+                       more: Some("not RFC3339"),
+                   }),
+               }));
+    assert_eq!(parse_descriptor_ext("test string",
+                                    &mut &b"String-Content: <<END\n\nEND\n"[..],
+                                    ":",
+                                    false,
+                                    &DescriptorConfig {
+                                        strict: true,
+                                        ..DescriptorConfig::default()
+                                    }),
+               Err(Error::Descriptor {
+                   descriptor: "test string".to_string(),
+                   line: 1,
+                   column: 17,
+                   text: "String-Content: <<END".to_string(),
+                   key: "String-Content".to_string(),
+                   value: "<<END".to_string(),
+                   error: Box::new(Error::Parse {
+                       tp: "element",
+                       wher: "descriptor",
+                       more: Some("empty value"),
+                   }),
+               }));
+}
+
+
+#[test]
+fn include() {
+    let tf = make_include_folder("include");
+    assert_eq!(parse_descriptor_ext("test string",
+                                    &mut &b"Name: Included\nDescriptor-Include: common.epupp\nContent: ch1.html\n"[..],
+                                    ":",
+                                    false,
+                                    &DescriptorConfig {
+                                        strict: true,
+                                        include_order: include_order(&tf),
+                                        ..DescriptorConfig::default()
+                                    }),
+               Ok(vec![BookElement::Name("Included".to_string()),
+                       BookElement::Author("nabijaczleweli".to_string(), None),
+                       BookElement::Contributor("edt".to_string(), "Editor".to_string(), None),
+                       BookElement::Language("en-GB".to_string()),
+                       BookElement::Content(PathBuf::from("ch1.html"))]));
+}
+
+#[test]
+fn include_not_found() {
+    let tf = make_include_folder("include-not-found");
+    assert_eq!(parse_descriptor_ext("test string",
+                                    &mut &b"Descriptor-Include: uncommon.epupp\n"[..],
+                                    ":",
+                                    false,
+                                    &DescriptorConfig {
+                                        include_order: include_order(&tf),
+                                        ..DescriptorConfig::default()
+                                    }),
+               Err(Error::Descriptor {
+                   descriptor: "test string".to_string(),
+                   line: 1,
+                   column: 21,
+                   text: "Descriptor-Include: uncommon.epupp".to_string(),
+                   key: "Descriptor-Include".to_string(),
+                   value: "uncommon.epupp".to_string(),
+                   error: Box::new(Error::FileNotFound {
+                       who: "Descriptor-Include",
+                       path: PathBuf::from("uncommon.epupp"),
+                   }),
+               }));
+}
+
+#[test]
+fn include_error() {
+    let tf = make_include_folder("include-error");
+    assert_eq!(parse_descriptor_ext("test string",
+                                    &mut &b"Name: Included\n\nDescriptor-Include: broken.epupp\n"[..],
+                                    ":",
+                                    false,
+                                    &DescriptorConfig {
+                                        include_order: include_order(&tf),
+                                        ..DescriptorConfig::default()
+                                    }),
+               Err(Error::Descriptor {
+                   descriptor: "test string".to_string(),
+                   line: 3,
+                   column: 21,
+                   text: "Descriptor-Include: broken.epupp".to_string(),
+                   key: "Descriptor-Include".to_string(),
+                   value: "broken.epupp".to_string(),
+                   error: Box::new(Error::Descriptor {
+                       descriptor: "broken.epupp".to_string(),
+                       line: 2,
+                       column: 7,
+                       text: "Date: yesterday".to_string(),
+                       key: "Date".to_string(),
+                       value: "yesterday".to_string(),
+                       error: Box::new(Error::Parse {
+                           tp: "datetime",
+                           wher: "book element",
+                           more: Some("not RFC3339"),
+                       }),
+                   }),
+               }));
+}
+
+#[test]
+fn include_cycle() {
+    let tf = make_include_folder("include-cycle");
+    assert_eq!(parse_descriptor_ext("test string",
+                                    &mut &b"Descriptor-Include: cycle-a.epupp\n"[..],
+                                    ":",
+                                    false,
+                                    &DescriptorConfig {
+                                        include_order: include_order(&tf),
+                                        ..DescriptorConfig::default()
+                                    }),
+               Err(Error::Descriptor {
+                   descriptor: "test string".to_string(),
+                   line: 1,
+                   column: 21,
+                   text: "Descriptor-Include: cycle-a.epupp".to_string(),
+                   key: "Descriptor-Include".to_string(),
+                   value: "cycle-a.epupp".to_string(),
+                   error: Box::new(Error::Descriptor {
+                       descriptor: "cycle-a.epupp".to_string(),
+                       line: 1,
+                       column: 21,
+                       text: "Descriptor-Include: cycle-b.epupp".to_string(),
+                       key: "Descriptor-Include".to_string(),
+                       value: "cycle-b.epupp".to_string(),
+                       error: Box::new(Error::Descriptor {
+                           descriptor: "cycle-b.epupp".to_string(),
+                           line: 2,
+                           column: 21,
+                           text: "Descriptor-Include: cycle-a.epupp".to_string(),
+                           key: "Descriptor-Include".to_string(),
+                           value: "cycle-a.epupp".to_string(),
+                           error: Box::new(Error::IncludeCycle {
+                               chain: vec!["cycle-a.epupp".to_string(), "cycle-b.epupp".to_string(), "cycle-a.epupp".to_string()],
+                           }),
+                       }),
+                   }),
+               }));
+}
+
+#[test]
+fn include_cycle_root() {
+    let tf = make_include_folder("include-cycle-root");
+    assert_eq!(parse_descriptor_ext("root.epupp",
+                                    &mut File::open(tf.join("first").join("root.epupp")).unwrap(),
+                                    ":",
+                                    false,
+                                    &DescriptorConfig {
+                                        include_order: include_order(&tf),
+                                        path: Some(tf.join("first").join("root.epupp")),
+                                        ..DescriptorConfig::default()
+                                    }),
+               Err(Error::Descriptor {
+                   descriptor: "root.epupp".to_string(),
+                   line: 2,
+                   column: 21,
+                   text: "Descriptor-Include: includes-root.epupp".to_string(),
+                   key: "Descriptor-Include".to_string(),
+                   value: "includes-root.epupp".to_string(),
+                   error: Box::new(Error::Descriptor {
+                       descriptor: "includes-root.epupp".to_string(),
+                       line: 1,
+                       column: 21,
+                       text: "Descriptor-Include: root.epupp".to_string(),
+                       key: "Descriptor-Include".to_string(),
+                       value: "root.epupp".to_string(),
+                       error: Box::new(Error::IncludeCycle {
+                           chain: vec!["root.epupp".to_string(), "includes-root.epupp".to_string(), "root.epupp".to_string()],
+                       }),
+                   }),
+               }));
+}
+
+#[test]
+fn include_twice() {
+    let tf = make_include_folder("include-twice");
+    assert_eq!(parse_descriptor_ext("test string",
+                                    &mut &b"Descriptor-Include: contributors.epupp\nDescriptor-Include: contributors.epupp\n"[..],
+                                    ":",
+                                    false,
+                                    &DescriptorConfig {
+                                        include_order: include_order(&tf),
+                                        ..DescriptorConfig::default()
+                                    }),
+               Ok(vec![BookElement::Contributor("edt".to_string(), "Editor".to_string(), None),
+                       BookElement::Contributor("edt".to_string(), "Editor".to_string(), None)]));
+}
+
+
+#[test]
+fn variables() {
+    env::set_var("GEN_EPUB_BOOK_TEST_LANGUAGE", "en-GB");
+    assert_eq!(parse_descriptor_ext("test string",
+                                    &mut &b"Set: VERSION = 1.2
+Set: TITLE=Variables v${VERSION}
+Name: ${TITLE}
+Set: VERSION=1.3
+String-Content: <<END
+<p>${TITLE}, now ${VERSION}, by ${AUTHOR}</p>
+END
+Language: ${GEN_EPUB_BOOK_TEST_LANGUAGE}
+String-Description: $${TITLE} ${UNDEFINED} $$ ${}
+"
+                                          [..],
+                                    ":",
+                                    false,
+                                    &DescriptorConfig {
+                                        defines: vec![("AUTHOR".to_string(), "nabijaczleweli".to_string()), ("VERSION".to_string(), "2.0".to_string())],
+                                        environment: true,
+                                        ..DescriptorConfig::default()
+                                    }),
+               Ok(vec![BookElement::Name("Variables v2.0".to_string()),
+                       BookElement::StringContent("<p>Variables v2.0, now 2.0, by nabijaczleweli</p>".to_string()),
+                       BookElement::Language("en-GB".to_string()),
+                       BookElement::StringDescription("${TITLE} ${UNDEFINED} $$ ${}".to_string())]));
+}
+
+#[test]
+fn variables_no_environment() {
+    env::set_var("GEN_EPUB_BOOK_TEST_NAME", "Environment");
+    assert_eq!(parse_descriptor("test string", &mut &b"Name: ${GEN_EPUB_BOOK_TEST_NAME}\n"[..], ":", false),
+               Ok(vec![BookElement::Name("${GEN_EPUB_BOOK_TEST_NAME}".to_string())]));
+    assert_eq!(parse_descriptor_ext("test string",
+                                    &mut &b"Name: ${GEN_EPUB_BOOK_TEST_NAME}\n"[..],
+                                    ":",
+                                    false,
+                                    &DescriptorConfig {
+                                        environment: true,
+                                        ..DescriptorConfig::default()
+                                    }),
+               Ok(vec![BookElement::Name("Environment".to_string())]));
+}
+
+#[test]
+fn variables_builtin() {
+    env::set_var("SOURCE_DATE_EPOCH", "1486564218");
+    assert_eq!(parse_descriptor_ext("test string",
+                                    &mut &b"Date: ${DATE}\nRights: Copyright ${YEAR}\n"[..],
+                                    ":",
+                                    false,
+                                    &DescriptorConfig {
+                                        strict: true,
+                                        ..DescriptorConfig::default()
+                                    }),
+               Ok(vec![BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T14:30:18+00:00").unwrap()),
+                       BookElement::Rights("Copyright 2017".to_string())]));
+}
+
+#[test]
+fn variables_undefined() {
+    assert_eq!(parse_descriptor_ext("test string",
+                                    &mut &b"Name: Undefined\nPublisher: ${UNDEFINED_PUBLISHER}\n"[..],
+                                    ":",
+                                    false,
+                                    &DescriptorConfig {
+                                        strict: true,
+                                        ..DescriptorConfig::default()
+                                    }),
+               Err(Error::Descriptor {
+                   descriptor: "test string".to_string(),
+                   line: 2,
+                   column: 12,
+                   text: "Publisher: ${UNDEFINED_PUBLISHER}".to_string(),
+                   key: "Publisher".to_string(),
+                   value: "${UNDEFINED_PUBLISHER}".to_string(),
+                   error: Box::new(Error::UndefinedVariable { name: "UNDEFINED_PUBLISHER".to_string() }),
+               }));
+}
+
+#[test]
+fn variables_empty() {
+    assert_eq!(parse_descriptor_ext("test string",
+                                    &mut &b"Name: Empty\nSet: E=\nLanguage: ${E}\n"[..],
+                                    ":",
+                                    false,
+                                    &DescriptorConfig {
+                                        strict: true,
+                                        ..DescriptorConfig::default()
+                                    }),
+               Err(Error::Descriptor {
+                   descriptor: "test string".to_string(),
+                   line: 3,
+                   column: 11,
+                   text: "Language: ${E}".to_string(),
+                   key: "Language".to_string(),
+                   value: "${E}".to_string(),
+                   error: Box::new(Error::Parse {
+                       tp: "element",
+                       wher: "descriptor",
+                       more: Some("empty value"),
+                   }),
+               }));
+    assert_eq!(parse_descriptor("test string", &mut &b"Name: Empty\nSet: E=\nLanguage: ${E}\n"[..], ":", false),
+               Ok(vec![BookElement::Name("Empty".to_string())]));
+}
+
+#[test]
+fn variables_malformed() {
+    assert_eq!(parse_descriptor("test string", &mut &b"Set: RELEASE-DATE=today\n"[..], ":", false),
+               Err(Error::Descriptor {
+                   descriptor: "test string".to_string(),
+                   line: 1,
+                   column: 6,
+                   text: "Set: RELEASE-DATE=today".to_string(),
+                   key: "Set".to_string(),
+                   value: "RELEASE-DATE=today".to_string(),
+                   error: Box::new(Error::Parse {
+                       tp: "variable definition",
+                       wher: "descriptor",
+                       more: Some("not NAME=value"),
+                   }),
+               }));
+}
+
+#[test]
+fn variables_include() {
+    let tf = make_include_folder("variables-include");
+    assert_eq!(parse_descriptor_ext("test string",
+                                    &mut &b"Set: NAME=common\nDescriptor-Include: ${NAME}.epupp\nPublisher: ${PUBLISHER}\n"[..],
+                                    ":",
+                                    false,
+                                    &DescriptorConfig {
+                                        strict: true,
+                                        include_order: include_order(&tf),
+                                        ..DescriptorConfig::default()
+                                    }),
+               Ok(vec![BookElement::Author("nabijaczleweli".to_string(), None),
+                       BookElement::Contributor("edt".to_string(), "Editor".to_string(), None),
+                       BookElement::Language("en-GB".to_string()),
+                       BookElement::Publisher("nabijaczleweli".to_string())]));
+}
+
+
+fn make_include_folder(f: &str) -> PathBuf {
+    let td = temp_dir().join("gen-epub-book.rs-test").join(format!("ops-parse-descriptor-{}", f));
+    let _ = fs::create_dir_all(td.join("first"));
+    let _ = fs::create_dir_all(td.join("second"));
+    for &(dir, name, content) in &[("first",
+                                    "common.epupp",
+                                    "# Shared\nSet: PUBLISHER=nabijaczleweli\nAuthor: ${PUBLISHER}\nDescriptor-Include: contributors.epupp\nLanguage: en-GB\n"),
+                                   ("first", "broken.epupp", "Language: en-GB\nDate: yesterday\n"),
+                                   ("first", "cycle-a.epupp", "Descriptor-Include: cycle-b.epupp\n"),
+                                   ("second", "contributors.epupp", "Contributor: edt Editor\n"),
+                                   ("second", "cycle-b.epupp", "Language: en-GB\nDescriptor-Include: cycle-a.epupp\n"),
+                                   ("first", "root.epupp", "Name: Root\nDescriptor-Include: includes-root.epupp\n"),
+                                   ("second", "includes-root.epupp", "Descriptor-Include: root.epupp\n"),
+                                   ("second", "common.epupp", "Include: style.css\n")] {
+        File::create(td.join(dir).join(name)).unwrap().write_all(content.as_bytes()).unwrap();
+    }
+    td
+}
+
+fn include_order(tf: &PathBuf) -> Vec<IncludeDirectory> {
+    vec![IncludeDirectory::Unnamed { dir: ("first/".to_string(), tf.join("first")) },
+         IncludeDirectory::Named {
+             name: "second".to_string(),
+             dir: ("second/".to_string(), tf.join("second")),
+         }]
+}
 
 fn parsed() -> Vec<BookElement> {
     vec![BookElement::Name("Simple ePub demonstration".to_string()),
          BookElement::Cover(PathBuf::from("cover.png")),
          BookElement::ImageContent(PathBuf::from("examples/simple/chapter_image.png")),
          BookElement::Content(PathBuf::from("simple/ctnt.html")),
-         BookElement::Author("nabijaczleweli".to_string()),
+         BookElement::Author("nabijaczleweli".to_string(), None),
          BookElement::Date(DateTime::parse_from_rfc3339("2017-02-08T15:30:18+01:00").unwrap()),
          BookElement::Language("en-GB".to_string())]
 }
@@ -0,0 +1,124 @@
+use gen_epub_book::ops::{IncludeDirectory, BookElement, DescriptorConfig, parse_descriptor_ext};
+use gen_epub_book::Error;
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::env::temp_dir;
+
+
+fn make_test_folder(f: &str) -> PathBuf {
+    let td = temp_dir().join("gen-epub-book.rs-test").join(format!("ops-parse-descriptor-ext-{}", f));
+    let _ = fs::create_dir_all(td.join("first").join("chapters"));
+    let _ = fs::create_dir_all(td.join("second").join("chapters").join("ch9.html"));
+    let _ = fs::create_dir_all(td.join("second").join("images"));
+    for f in &["ch1.html", "ch2.html", "ch10.html", "notes.txt", "notes[1].html"] {
+        File::create(td.join("first").join("chapters").join(f)).unwrap();
+    }
+    for f in &["ch2.html", "ch3.html"] {
+        File::create(td.join("second").join("chapters").join(f)).unwrap();
+    }
+    for f in &["fig[1].png", "fig2.png"] {
+        File::create(td.join("second").join("images").join(f)).unwrap();
+    }
+    td
+}
+
+fn config(tf: &PathBuf) -> DescriptorConfig {
+    DescriptorConfig {
+        include_order: vec![IncludeDirectory::Unnamed { dir: ("first/".to_string(), tf.join("first")) },
+                            IncludeDirectory::Named {
+                                name: "second".to_string(),
+                                dir: ("second/".to_string(), tf.join("second")),
+                            }],
+        ..DescriptorConfig::default()
+    }
+}
+
+#[test]
+fn expanded() {
+    let tf = make_test_folder("expanded");
+    assert_eq!(parse_descriptor_ext("test string",
+                                    &mut &b"Content: chapters/ch*.html\n\
+                                            Image-Content: images/fig?.png\n\
+                                            Include: chapters/*.txt\n\
+                                            Markdown-Content: chapters/ch[12].html\n"[..],
+                                    ":",
+                                    false,
+                                    &config(&tf)),
+               Ok(vec![BookElement::Content(PathBuf::from("chapters/ch1.html")),
+                       BookElement::Content(PathBuf::from("chapters/ch2.html")),
+                       BookElement::Content(PathBuf::from("chapters/ch3.html")),
+                       BookElement::Content(PathBuf::from("chapters/ch10.html")),
+                       BookElement::ImageContent(PathBuf::from("images/fig2.png")),
+                       BookElement::Include(PathBuf::from("chapters/notes.txt")),
+                       BookElement::MarkdownContent(PathBuf::from("chapters/ch1.html")),
+                       BookElement::MarkdownContent(PathBuf::from("chapters/ch2.html"))]));
+}
+
+#[test]
+fn verbatim() {
+    let tf = make_test_folder("verbatim");
+    assert_eq!(parse_descriptor_ext("test string",
+                                    &mut &b"Name: *Emphasised* [name]\n\
+                                            Content: chapters/ch9.html\n\
+                                            Content: chapters/notes[1].html\n\
+                                            Image-Content: images/fig[1].png\n\
+                                            Cover: images/fig*.png\n"[..],
+                                    ":",
+                                    false,
+                                    &config(&tf)),
+               Ok(vec![BookElement::Name("*Emphasised* [name]".to_string()),
+                       BookElement::Content(PathBuf::from("chapters/ch9.html")),
+                       BookElement::Content(PathBuf::from("chapters/notes[1].html")),
+                       BookElement::ImageContent(PathBuf::from("images/fig[1].png")),
+                       BookElement::Cover(PathBuf::from("images/fig*.png"))]));
+}
+
+#[test]
+fn no_match() {
+    let tf = make_test_folder("no-match");
+    assert_eq!(parse_descriptor_ext("test string", &mut &b"Name: No match\nContent: chapters/ch9*.html\n"[..], ":", false, &config(&tf)),
+               Err(Error::Descriptor {
+                   descriptor: "test string".to_string(),
+                   line: 2,
+                   column: 10,
+                   text: "Content: chapters/ch9*.html".to_string(),
+                   key: "Content".to_string(),
+                   value: "chapters/ch9*.html".to_string(),
+                   error: Box::new(Error::FileNotFound {
+                       who: "Content",
+                       path: PathBuf::from("chapters/ch9*.html"),
+                   }),
+               }));
+    assert_eq!(parse_descriptor_ext("test string", &mut &b"Include: *.css\n"[..], ":", false, &DescriptorConfig::default()),
+               Err(Error::Descriptor {
+                   descriptor: "test string".to_string(),
+                   line: 1,
+                   column: 10,
+                   text: "Include: *.css".to_string(),
+                   key: "Include".to_string(),
+                   value: "*.css".to_string(),
+                   error: Box::new(Error::FileNotFound {
+                       who: "Include",
+                       path: PathBuf::from("*.css"),
+                   }),
+               }));
+}
+
+#[test]
+fn invalid_pattern() {
+    let tf = make_test_folder("invalid-pattern");
+    assert_eq!(parse_descriptor_ext("test string", &mut &b"Image-Content: images/**a.png\n"[..], ":", false, &config(&tf)),
+               Err(Error::Descriptor {
+                   descriptor: "test string".to_string(),
+                   line: 1,
+                   column: 16,
+                   text: "Image-Content: images/**a.png".to_string(),
+                   key: "Image-Content".to_string(),
+                   value: "images/**a.png".to_string(),
+                   error: Box::new(Error::Parse {
+                       tp: "glob",
+                       wher: "Image-Content",
+                       more: Some("recursive wildcards must form a single path component"),
+                   }),
+               }));
+}
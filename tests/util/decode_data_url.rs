@@ -0,0 +1,40 @@
+use gen_epub_book::util::decode_data_url;
+use gen_epub_book::Error;
+use url::Url;
+
+
+#[test]
+fn plain() {
+    assert_eq!(decode_data_url(&Url::parse("data:,Hello%2C%20World!").unwrap()),
+               Ok((b"Hello, World!".to_vec(), "text/plain".to_string())));
+    assert_eq!(decode_data_url(&Url::parse("data:text/CSS;charset=utf-8,body%20%7B%7D#fragment").unwrap()),
+               Ok((b"body {}".to_vec(), "text/css".to_string())));
+}
+
+#[test]
+fn base64() {
+    assert_eq!(decode_data_url(&Url::parse("data:image/png;base64,UE5H").unwrap()),
+               Ok((b"PNG".to_vec(), "image/png".to_string())));
+    assert_eq!(decode_data_url(&Url::parse("data:text/plain;charset=utf-8;BASE64,SGVs%20bG8s%0AIFdv%20cmxkIQ==").unwrap()),
+               Ok((b"Hello, World!".to_vec(), "text/plain".to_string())));
+}
+
+#[test]
+fn no_data() {
+    assert_eq!(decode_data_url(&Url::parse("data:text/plain").unwrap()),
+               Err(Error::Parse {
+                   tp: "data URL",
+                   wher: "network content",
+                   more: Some("no data"),
+               }));
+}
+
+#[test]
+fn invalid_base64() {
+    assert_eq!(decode_data_url(&Url::parse("data:image/png;base64,U!NH").unwrap()),
+               Err(Error::Parse {
+                   tp: "data URL",
+                   wher: "network content",
+                   more: Some("invalid base64"),
+               }));
+}
@@ -0,0 +1,203 @@
+use gen_epub_book::util::{DownloadConfig, DownloadInfo, download_to_ext};
+use gen_epub_book::Error;
+use std::fs::{self, File};
+use std::env::temp_dir;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread;
+use url::Url;
+
+
+fn offline_config(f: &str) -> DownloadConfig {
+    let td = temp_dir().join("gen-epub-book.rs-test").join(format!("util-download-to-ext-{}", f));
+    let _ = fs::create_dir_all(&td);
+    DownloadConfig {
+        cache_dir: Some(td),
+        offline: true,
+        ..DownloadConfig::default()
+    }
+}
+
+/// Serve the specified raw HTTP response to all requests, returning the URL of the specified path on the server.
+fn serve(response: &'static [u8], path: &str) -> Url {
+    let server = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = Url::parse(&format!("http://{}/{}", server.local_addr().unwrap(), path)).unwrap();
+    thread::spawn(move || for stream in server.incoming() {
+        let mut stream = stream.unwrap();
+        let mut request = vec![];
+        let mut buf = [0; 1024];
+        while !request.ends_with(b"\r\n\r\n") {
+            match stream.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => request.extend_from_slice(&buf[..read]),
+            }
+        }
+        let _ = stream.write_all(response);
+    });
+    url
+}
+
+#[test]
+fn cached() {
+    let config = offline_config("cached");
+    let url = Url::parse("https://example.com/cached.txt").unwrap();
+    File::create(config.cache_path(&url).unwrap()).unwrap().write_all(b"Cached content").unwrap();
+
+    let mut buf = vec![];
+    assert_eq!(download_to_ext(&mut buf, &url, &config), Ok(DownloadInfo::default()));
+    assert_eq!(&buf[..], &b"Cached content"[..]);
+}
+
+#[test]
+fn cached_info() {
+    let config = offline_config("cached_info");
+    let url = Url::parse("https://example.com/image?id=42").unwrap();
+    let cached = config.cache_path(&url).unwrap();
+    File::create(&cached).unwrap().write_all(b"Cached image").unwrap();
+    File::create(cached.with_extension("headers"))
+        .unwrap()
+        .write_all(b"ETag: \"42\"\nContent-Type: image/png\nContent-Disposition: inline; filename=\"figure 42.png\"\n")
+        .unwrap();
+
+    assert_eq!(download_to_ext(&mut vec![], &url, &config),
+               Ok(DownloadInfo {
+                   content_type: Some("image/png".to_string()),
+                   filename: Some("figure 42.png".to_string()),
+               }));
+}
+
+#[test]
+fn not_cached() {
+    let config = offline_config("not_cached");
+    let url = Url::parse("https://example.com/not_cached.txt").unwrap();
+    let _ = fs::remove_file(config.cache_path(&url).unwrap());
+
+    let mut buf = vec![];
+    assert_eq!(download_to_ext(&mut buf, &url, &config),
+               Err(Error::NotCached { url: "https://example.com/not_cached.txt".to_string() }));
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn no_cache_dir() {
+    let config = DownloadConfig {
+        cache_dir: None,
+        offline: true,
+        ..DownloadConfig::default()
+    };
+    assert_eq!(download_to_ext(&mut vec![], &Url::parse("https://example.com/not_cached.txt").unwrap(), &config),
+               Err(Error::NotCached { url: "https://example.com/not_cached.txt".to_string() }));
+}
+
+#[test]
+fn cache_path() {
+    let config = offline_config("cache_path");
+    let first = config.cache_path(&Url::parse("https://example.com/first.png").unwrap()).unwrap();
+    let second = config.cache_path(&Url::parse("https://example.com/second.png").unwrap()).unwrap();
+    assert_eq!(first.parent(), config.cache_dir.as_ref().map(|d| d.as_path()));
+    assert_ne!(first, second);
+    assert_eq!(config.cache_path(&Url::parse("https://example.com/first.png").unwrap()).unwrap(), first);
+}
+
+#[test]
+fn invalid_header_name() {
+    let config = DownloadConfig { headers: vec![("Bad Name".to_string(), "value".to_string())], ..DownloadConfig::default() };
+    assert_eq!(download_to_ext(&mut vec![], &Url::parse("https://example.com/header.txt").unwrap(), &config),
+               Err(Error::Parse {
+                   tp: "HTTP client configuration",
+                   wher: "network content",
+                   more: Some("invalid header name"),
+               }));
+}
+
+#[test]
+fn invalid_header_value() {
+    let config = DownloadConfig { headers: vec![("Authorization".to_string(), "Bearer\ntoken".to_string())], ..DownloadConfig::default() };
+    assert_eq!(download_to_ext(&mut vec![], &Url::parse("https://example.com/header.txt").unwrap(), &config),
+               Err(Error::Parse {
+                   tp: "HTTP client configuration",
+                   wher: "network content",
+                   more: Some("invalid header value"),
+               }));
+}
+
+#[test]
+fn invalid_user_agent() {
+    let config = DownloadConfig { user_agent: Some("gen-epub-book\r\n".to_string()), ..DownloadConfig::default() };
+    assert_eq!(download_to_ext(&mut vec![], &Url::parse("https://example.com/header.txt").unwrap(), &config),
+               Err(Error::Parse {
+                   tp: "HTTP client configuration",
+                   wher: "network content",
+                   more: Some("invalid user agent"),
+               }));
+}
+
+#[test]
+fn file_url() {
+    let config = offline_config("file_url");
+    let path = config.cache_dir.as_ref().unwrap().join("local file.txt");
+    File::create(&path).unwrap().write_all(b"Local content").unwrap();
+
+    let mut buf = vec![];
+    assert_eq!(download_to_ext(&mut buf, &Url::from_file_path(&path).unwrap(), &config), Ok(DownloadInfo::default()));
+    assert_eq!(&buf[..], &b"Local content"[..]);
+}
+
+#[test]
+fn file_url_not_found() {
+    let config = offline_config("file_url_not_found");
+    let path = config.cache_dir.as_ref().unwrap().join("nonexistant.txt");
+
+    assert_eq!(download_to_ext(&mut vec![], &Url::from_file_path(&path).unwrap(), &config),
+               Err(Error::FileNotFound {
+                   who: "network content",
+                   path: path,
+               }));
+}
+
+#[test]
+fn data_url() {
+    let mut buf = vec![];
+    assert_eq!(download_to_ext(&mut buf, &Url::parse("data:image/png;base64,UE5H").unwrap(), &offline_config("data_url")),
+               Ok(DownloadInfo {
+                   content_type: Some("image/png".to_string()),
+                   filename: None,
+               }));
+    assert_eq!(&buf[..], &b"PNG"[..]);
+}
+
+#[test]
+fn cache_written() {
+    let config = DownloadConfig { offline: false, ..offline_config("cache_written") };
+    let url = serve(b"HTTP/1.1 200 OK\r\nETag: \"1\"\r\nContent-Length: 12\r\nConnection: close\r\n\r\nDownloaded 1",
+                    "cache_written.txt");
+    let cached = config.cache_path(&url).unwrap();
+
+    let mut buf = vec![];
+    assert_eq!(download_to_ext(&mut buf, &url, &config), Ok(DownloadInfo::default()));
+    assert_eq!(&buf[..], &b"Downloaded 1"[..]);
+    assert_eq!(fs::read(&cached).unwrap(), b"Downloaded 1");
+    assert_eq!(fs::read_to_string(cached.with_extension("headers")).unwrap(), "ETag: \"1\"\n");
+    assert!(!cached.with_extension("partial").exists());
+    assert!(!cached.with_extension("headers-partial").exists());
+}
+
+#[test]
+fn cache_truncated() {
+    let config = DownloadConfig { offline: false, ..offline_config("cache_truncated") };
+    let url = serve(b"HTTP/1.1 200 OK\r\nETag: \"2\"\r\nContent-Length: 100\r\nConnection: close\r\n\r\nTruncated", "cache_truncated.txt");
+    let cached = config.cache_path(&url).unwrap();
+    File::create(&cached).unwrap().write_all(b"Cached 1").unwrap();
+    File::create(cached.with_extension("headers")).unwrap().write_all(b"ETag: \"1\"\n").unwrap();
+
+    assert_eq!(download_to_ext(&mut vec![], &url, &config),
+               Err(Error::Io {
+                   desc: "network content",
+                   op: "read",
+                   more: None,
+               }));
+    assert_eq!(fs::read(&cached).unwrap(), b"Cached 1");
+    assert_eq!(fs::read_to_string(cached.with_extension("headers")).unwrap(), "ETag: \"1\"\n");
+    assert!(!cached.with_extension("partial").exists());
+    assert!(!cached.with_extension("headers-partial").exists());
+}
@@ -0,0 +1,37 @@
+use gen_epub_book::util::edit_distance;
+
+
+#[test]
+fn equal() {
+    assert_eq!(edit_distance("Content", "Content"), 0);
+    assert_eq!(edit_distance("", ""), 0);
+}
+
+#[test]
+fn substitution() {
+    assert_eq!(edit_distance("Cover", "Covet"), 1);
+    assert_eq!(edit_distance("content", "Content"), 1);
+}
+
+#[test]
+fn insertion() {
+    assert_eq!(edit_distance("Autor", "Author"), 1);
+    assert_eq!(edit_distance("", "Date"), 4);
+}
+
+#[test]
+fn deletion() {
+    assert_eq!(edit_distance("Languagee", "Language"), 1);
+    assert_eq!(edit_distance("UUID", ""), 4);
+}
+
+#[test]
+fn transposition() {
+    assert_eq!(edit_distance("Contnet", "Content"), 2);
+}
+
+#[test]
+fn unicode() {
+    assert_eq!(edit_distance("Żółć", "Zolc"), 4);
+    assert_eq!(edit_distance("Żółć", "Żółw"), 1);
+}
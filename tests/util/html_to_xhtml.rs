@@ -0,0 +1,58 @@
+use gen_epub_book::util::html_to_xhtml;
+
+
+static PROLOGUE: &'static str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n";
+
+#[test]
+fn tag_soup() {
+    assert_eq!(html_to_xhtml("<P>First<p>Second<br>line<hr><img src=fig1.png alt='Fig. 1'>", "Chapter 1"),
+               format!("{}{}{}\n",
+                       PROLOGUE,
+                       r#"<html xmlns="http://www.w3.org/1999/xhtml"><head><title>Chapter 1</title></head><body><p>First</p>"#,
+                       r#"<p>Second<br />line</p><hr /><img alt="Fig. 1" src="fig1.png" /></body></html>"#));
+}
+
+#[test]
+fn existing_title() {
+    assert_eq!(html_to_xhtml("<html lang=en><head><title>Prologue</title><meta charset=utf-8></head><body>Text</body></html>", "Chapter 1"),
+               format!("{}{}{}\n",
+                       PROLOGUE,
+                       r#"<html xmlns="http://www.w3.org/1999/xhtml" lang="en"><head><title>Prologue</title><meta charset="utf-8" /></head>"#,
+                       "<body>Text</body></html>"));
+    assert_eq!(html_to_xhtml("<title> </title>Text", "Chapter 1"),
+               format!("{}{}\n",
+                       PROLOGUE,
+                       r#"<html xmlns="http://www.w3.org/1999/xhtml"><head><title>Chapter 1</title></head><body>Text</body></html>"#));
+}
+
+#[test]
+fn escaped() {
+    assert_eq!(html_to_xhtml("<p title='\"Tom\" & Jerry'>Tom &amp; Jerry &lt;Redux> &nbsp;</p>", "Tom & Jerry"),
+               format!("{}{}{}\n",
+                       PROLOGUE,
+                       r#"<html xmlns="http://www.w3.org/1999/xhtml"><head><title>Tom &amp; Jerry</title></head><body>"#,
+                       "<p title=\"&quot;Tom&quot; &amp; Jerry\">Tom &amp; Jerry &lt;Redux&gt; \u{a0}</p></body></html>"));
+}
+
+#[test]
+fn comments_kept() {
+    let xhtml = html_to_xhtml(r#"<!-- ePub title: "Chapter 1" --><p>Text<!-- a -- b --></p>"#, "Chapter 1");
+    assert!(xhtml.starts_with(&format!(r#"{}<!-- ePub title: "Chapter 1" --><html"#, PROLOGUE)));
+    assert!(xhtml.contains("<p>Text<!-- a - - b --></p>"));
+}
+
+#[test]
+fn comments_dashes() {
+    let xhtml = html_to_xhtml("<p>Text<!--a---b--><!--a---></p>", "Chapter 1");
+    assert!(xhtml.contains("<p>Text<!--a- - -b--><!--a- --></p>"));
+}
+
+#[test]
+fn foreign() {
+    assert_eq!(html_to_xhtml(r##"<svg viewBox="0 0 10 10"><image xlink:href="cover.png"/><use href="#a"></use></svg>"##, "Cover"),
+               format!("{}{}{}{}\n",
+                       PROLOGUE,
+                       r#"<html xmlns="http://www.w3.org/1999/xhtml"><head><title>Cover</title></head><body>"#,
+                       r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" viewBox="0 0 10 10">"#,
+                       r##"<image xlink:href="cover.png" /><use href="#a" /></svg></body></html>"##));
+}
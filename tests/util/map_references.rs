@@ -0,0 +1,69 @@
+use gen_epub_book::util::{map_references, map_css_references};
+
+
+fn references(content: &str) -> Vec<(String, bool)> {
+    let mut found = vec![];
+    map_references(content, |r, link| {
+        found.push((r.to_string(), link));
+        None
+    });
+    found
+}
+
+#[test]
+fn attributes() {
+    assert_eq!(references(r#"<img src="fig1.png" alt="href='no'" /><link rel='stylesheet' href='style.css'/><IMG SRC = "fig2.png">"#),
+               vec![("fig1.png".to_string(), false), ("style.css".to_string(), false)]);
+    assert_eq!(references(r#"<a href="ch02.html#fn3">2</a><area href="map.html" /><image xlink:href="cover.svg" />"#),
+               vec![("ch02.html#fn3".to_string(), true), ("map.html".to_string(), true), ("cover.svg".to_string(), false)]);
+}
+
+#[test]
+fn css() {
+    let mut found = vec![];
+    map_css_references(r#"@font-face { src: url( "font.otf" ); } body { background: url(bg.png) } p { background: url('p.png') }"#,
+                       |r, link| {
+                           found.push((r.to_string(), link));
+                           None
+                       });
+    assert_eq!(found,
+               vec![("font.otf".to_string(), false), ("bg.png".to_string(), false), ("p.png".to_string(), false)]);
+}
+
+#[test]
+fn css_in_html() {
+    assert_eq!(references(r#"<style type="text/css">body { background: url(bg.png) }</style><p style='background: url("p.png")'>Text</p>"#),
+               vec![("p.png".to_string(), false), ("bg.png".to_string(), false)]);
+}
+
+#[test]
+fn css_prose_ignored() {
+    let content = r#"<p>To set a background, use url(foo.png), as in <code>background: url('bar.png')</code>.</p>"#;
+    assert!(references(content).is_empty());
+    assert_eq!(map_references(content, |r, _| Some(format!("packed-{}", r))), content);
+}
+
+#[test]
+fn comments_ignored() {
+    assert!(references(r#"<!-- ePub title: "Chapter 1" --><p>src="nope.png"</p>"#).is_empty());
+}
+
+#[test]
+fn replaced() {
+    assert_eq!(map_references(r#"<a href="ch02.html"><img src='fig1.png' style="background: url(bg.png)" /></a>"#,
+                              |r, link| if link { None } else { Some(format!("packed-{}", r)) }),
+               r#"<a href="ch02.html"><img src='packed-fig1.png' style="background: url(packed-bg.png)" /></a>"#);
+}
+
+#[test]
+fn replaced_style() {
+    assert_eq!(map_references(r#"<style>p { background: url('p.png') }</style><p style="color: red">Text url(q.png)</p>"#,
+                              |r, _| Some(format!("packed-{}", r))),
+               r#"<style>p { background: url('packed-p.png') }</style><p style="color: red">Text url(q.png)</p>"#);
+}
+
+#[test]
+fn untouched() {
+    let content = r#"<p class="x">Text <b>bold</b> &amp; <img src="fig1.png"/></p>"#;
+    assert_eq!(map_references(content, |_, _| None), content);
+}
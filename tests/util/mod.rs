@@ -1 +1,9 @@
+mod decode_data_url;
+mod download_to_ext;
+mod edit_distance;
+mod html_to_xhtml;
+mod map_references;
+mod sha256_hex;
 mod uppercase_first;
+mod url_filename;
+mod xml_escape;
@@ -0,0 +1,32 @@
+use gen_epub_book::util::{url_filename, xhtml_url_id};
+use url::Url;
+
+
+#[test]
+fn last_segment() {
+    assert_eq!(url_filename(&Url::parse("https://example.com/cover.png").unwrap()), "cover.png");
+    assert_eq!(url_filename(&Url::parse("https://example.com/images/cover.png").unwrap()), "cover.png");
+    assert_eq!(url_filename(&Url::parse("https://example.com/images/cover.png?size=large#top").unwrap()), "cover.png");
+    assert_eq!(url_filename(&Url::parse("https://cdn.example.com/img?id=42").unwrap()), "img");
+}
+
+#[test]
+fn trailing_slash() {
+    assert_eq!(url_filename(&Url::parse("https://example.com/chapters/").unwrap()), "chapters");
+    assert_eq!(url_filename(&Url::parse("https://example.com/chapters//").unwrap()), "chapters");
+}
+
+#[test]
+fn no_path() {
+    assert_eq!(url_filename(&Url::parse("https://example.com").unwrap()), "example.com");
+    assert_eq!(url_filename(&Url::parse("https://example.com/?page=1").unwrap()), "example.com");
+    assert_eq!(url_filename(&Url::parse("data:text/plain,content").unwrap()), "network-data");
+    assert_eq!(url_filename(&Url::parse("mailto:someone@example.com").unwrap()), "network-data");
+}
+
+#[test]
+fn id() {
+    assert_eq!(xhtml_url_id(&Url::parse("https://example.com/images/cover.png").unwrap()), "cover_png");
+    assert_eq!(xhtml_url_id(&Url::parse("https://example.com").unwrap()), "example_com");
+    assert_eq!(xhtml_url_id(&Url::parse("data:text/plain,content").unwrap()), "network-data");
+}
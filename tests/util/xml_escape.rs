@@ -0,0 +1,33 @@
+use gen_epub_book::util::xml_escape;
+
+
+#[test]
+fn plain() {
+    assert_eq!(xml_escape("Simple ePub demonstration"), "Simple ePub demonstration".to_string());
+    assert_eq!(xml_escape("Zażółć gęślą jaźń"), "Zażółć gęślą jaźń".to_string());
+}
+
+#[test]
+fn special() {
+    assert_eq!(xml_escape("&"), "&amp;".to_string());
+    assert_eq!(xml_escape("<"), "&lt;".to_string());
+    assert_eq!(xml_escape(">"), "&gt;".to_string());
+    assert_eq!(xml_escape("\""), "&quot;".to_string());
+    assert_eq!(xml_escape("'"), "&apos;".to_string());
+}
+
+#[test]
+fn mixed() {
+    assert_eq!(xml_escape("Tom & Jerry <Redux>"), "Tom &amp; Jerry &lt;Redux&gt;".to_string());
+    assert_eq!(xml_escape("<i>It's \"fine\"</i>"), "&lt;i&gt;It&apos;s &quot;fine&quot;&lt;/i&gt;".to_string());
+}
+
+#[test]
+fn already_escaped() {
+    assert_eq!(xml_escape("&amp;"), "&amp;amp;".to_string());
+}
+
+#[test]
+fn empty() {
+    assert!(xml_escape("").is_empty());
+}